@@ -0,0 +1,83 @@
+/*
+ * benches/check_roles.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+//! Measures the cost of repeatedly failing role checks.
+//!
+//! Run with `cargo bench --bench check_roles`.
+
+extern crate tag_guard;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use tag_guard::prelude::*;
+
+const ITERATIONS: usize = 100_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let mut engine = Engine::default();
+    let roles = ["admin", "moderator", "licensing", "staff"]
+        .iter()
+        .map(|name| engine.add_role(*name).unwrap())
+        .collect::<Vec<_>>();
+
+    let tag = engine
+        .add_tag(
+            "_cc",
+            TemplateTagSpec {
+                needed_roles: roles,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let member = engine.add_role("member").unwrap();
+    let tags = [Tag::clone(&tag)];
+    let held = [member];
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        let result = engine.check_tag_changes(&tags, &[], &tags, &held);
+        assert!(result.is_err());
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("failing check_roles x {}", ITERATIONS);
+    println!("  time:        {:?}", elapsed);
+    println!(
+        "  allocations: {} ({:.2} per check)",
+        allocations,
+        allocations as f64 / ITERATIONS as f64,
+    );
+}
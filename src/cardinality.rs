@@ -0,0 +1,79 @@
+/*
+ * cardinality.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use std::fmt::{self, Display};
+
+/// Limits on how many members of a tag group may be present at once.
+///
+/// Set on a group using [`Engine::set_group_cardinality`].
+///
+/// [`Engine::set_group_cardinality`]: ./struct.Engine.html#method.set_group_cardinality
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cardinality {
+    /// The fewest members which must be present.
+    pub min: usize,
+
+    /// The most members which may be present, if limited.
+    pub max: Option<usize>,
+}
+
+impl Cardinality {
+    /// Requires exactly `count` members to be present.
+    #[inline]
+    pub fn exactly(count: usize) -> Self {
+        Cardinality {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    /// Requires at least `count` members to be present.
+    #[inline]
+    pub fn at_least(count: usize) -> Self {
+        Cardinality {
+            min: count,
+            max: None,
+        }
+    }
+
+    /// Permits at most `count` members to be present.
+    #[inline]
+    pub fn at_most(count: usize) -> Self {
+        Cardinality {
+            min: 0,
+            max: Some(count),
+        }
+    }
+
+    /// Determines if the given number of members is permitted.
+    pub fn allows(&self, count: usize) -> bool {
+        let under = match self.max {
+            Some(max) => count <= max,
+            None => true,
+        };
+
+        count >= self.min && under
+    }
+}
+
+impl Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let noun = |count| if count == 1 { "member" } else { "members" };
+
+        match self.max {
+            Some(max) if max == self.min => write!(f, "exactly {} {}", max, noun(max)),
+            Some(max) if self.min == 0 => write!(f, "at most {} {}", max, noun(max)),
+            Some(max) => write!(f, "between {} and {} members", self.min, max),
+            None => write!(f, "at least {} {}", self.min, noun(self.min)),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+/*
+ * diff.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::{Error, Result};
+
+/// The differences between the registrations of two [`Engine`]s.
+///
+/// Describes what would need to change to turn the first engine into the second.
+/// All lists are sorted.
+///
+/// [`Engine`]: ./struct.Engine.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineDiff {
+    /// Tags present only in the second engine.
+    pub added_tags: Vec<Tag>,
+
+    /// Tags present only in the first engine.
+    pub removed_tags: Vec<Tag>,
+
+    /// Tags present in both engines, but with different specifications.
+    pub changed_tags: Vec<Tag>,
+
+    /// The specifications of each added or changed tag, as found in the second engine.
+    ///
+    /// Sorted by tag.
+    pub specs: Vec<(Tag, TemplateTagSpec)>,
+
+    /// Tag groups present only in the second engine.
+    pub added_groups: Vec<Tag>,
+
+    /// Tag groups present only in the first engine.
+    pub removed_groups: Vec<Tag>,
+
+    /// Roles present only in the second engine.
+    pub added_roles: Vec<Role>,
+
+    /// Roles present only in the first engine.
+    pub removed_roles: Vec<Role>,
+}
+
+impl EngineDiff {
+    /// Determines if the two engines were equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added_tags.is_empty()
+            && self.removed_tags.is_empty()
+            && self.changed_tags.is_empty()
+            && self.added_groups.is_empty()
+            && self.removed_groups.is_empty()
+            && self.added_roles.is_empty()
+            && self.removed_roles.is_empty()
+    }
+}
+
+impl Engine {
+    /// Compares this engine against another, listing what differs.
+    ///
+    /// Additions are things `other` has which this engine lacks, and removals the reverse.
+    pub fn diff(&self, other: &Engine) -> EngineDiff {
+        let mut diff = EngineDiff::default();
+        let (ours, theirs) = (self.get_specs(), other.get_specs());
+
+        for (tag, spec) in theirs {
+            match ours.get(tag) {
+                Some(our_spec) if our_spec != spec => diff.changed_tags.push(Tag::clone(tag)),
+                Some(_) => continue,
+                None => diff.added_tags.push(Tag::clone(tag)),
+            }
+
+            diff.specs
+                .push((Tag::clone(tag), TemplateTagSpec::from(spec)));
+        }
+
+        for tag in ours.keys() {
+            if !theirs.contains_key(tag) {
+                diff.removed_tags.push(Tag::clone(tag));
+            }
+        }
+
+        // A name changing between tag and group is listed as both removed and added
+        let is_pure_group = |engine: &Engine, tag: &Tag| {
+            engine.get_tags().contains(tag) && !engine.get_specs().contains_key(tag)
+        };
+
+        for group in other.get_tags() {
+            if is_pure_group(other, group) && !is_pure_group(self, group) {
+                diff.added_groups.push(Tag::clone(group));
+            }
+        }
+
+        for group in self.get_tags() {
+            if is_pure_group(self, group) && !is_pure_group(other, group) {
+                diff.removed_groups.push(Tag::clone(group));
+            }
+        }
+
+        diff.added_roles = other
+            .get_roles()
+            .difference(self.get_roles())
+            .cloned()
+            .collect();
+
+        diff.removed_roles = self
+            .get_roles()
+            .difference(other.get_roles())
+            .cloned()
+            .collect();
+
+        diff.added_tags.sort();
+        diff.removed_tags.sort();
+        diff.changed_tags.sort();
+        diff.specs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        diff.added_groups.sort();
+        diff.removed_groups.sort();
+        diff.added_roles.sort();
+        diff.removed_roles.sort();
+        diff
+    }
+
+    /// Applies the changes described by an [`EngineDiff`] to this engine.
+    ///
+    /// After applying a diff produced by `engine.diff(&other)`, this engine will have the same
+    /// tags, groups, roles, and specifications as `other`. Fails without making any changes if
+    /// the diff doesn't fit this engine, for instance if a removed tag is not registered.
+    ///
+    /// [`EngineDiff`]: ./struct.EngineDiff.html
+    pub fn apply_diff(&mut self, diff: &EngineDiff) -> Result<()> {
+        let removed =
+            |tag: &Tag| diff.removed_tags.contains(tag) || diff.removed_groups.contains(tag);
+
+        // Check preconditions, names being removed may be reused
+        for tag in diff.added_tags.iter().chain(&diff.added_groups) {
+            if self.get_tags().contains(tag) && !removed(tag) {
+                return Err(Error::NameCollision(str!(&**tag)));
+            }
+        }
+
+        for tag in diff.removed_tags.iter().chain(&diff.changed_tags) {
+            self.get_spec(tag)?;
+        }
+
+        for group in &diff.removed_groups {
+            if !self.get_tags().contains(group) || self.get_specs().contains_key(group) {
+                return Err(Error::NotGroup(Tag::clone(group)));
+            }
+        }
+
+        for role in &diff.added_roles {
+            if self.get_roles().contains(role) {
+                return Err(Error::NameCollision(str!(&**role)));
+            }
+        }
+
+        for role in &diff.removed_roles {
+            if !self.get_roles().contains(role) {
+                return Err(Error::MissingRole(Role::clone(role)));
+            }
+        }
+
+        for tag in diff.added_tags.iter().chain(&diff.changed_tags) {
+            if !diff.specs.iter().any(|(spec_tag, _)| spec_tag == tag) {
+                return Err(Error::Other("Diff is missing a tag specification"));
+            }
+        }
+
+        // Apply changes, restoring the original state on failure
+        let snapshot = self.snapshot();
+        let result = self.apply_diff_unchecked(diff);
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+
+        result
+    }
+
+    fn apply_diff_unchecked(&mut self, diff: &EngineDiff) -> Result<()> {
+        // Deleting a name which is re-added as the other kind drops references to it
+        // from specifications which are otherwise unchanged, so those are restored after.
+        let kind_changed = diff
+            .removed_tags
+            .iter()
+            .chain(&diff.removed_groups)
+            .any(|tag| diff.added_tags.contains(tag) || diff.added_groups.contains(tag));
+
+        let unchanged = if kind_changed {
+            self.get_specs()
+                .iter()
+                .filter(|(tag, _)| !diff.removed_tags.contains(tag))
+                .filter(|(tag, _)| !diff.specs.iter().any(|(spec_tag, _)| spec_tag == *tag))
+                .map(|(tag, spec)| (Tag::clone(tag), TemplateTagSpec::from(spec)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Removals come first, so their names are free to be re-added
+        for tag in &diff.removed_tags {
+            self.delete_tag(tag);
+        }
+
+        for group in &diff.removed_groups {
+            self.delete_group(group);
+        }
+
+        for role in &diff.removed_roles {
+            self.delete_role(role);
+        }
+
+        for role in &diff.added_roles {
+            self.add_role(&**role)?;
+        }
+
+        for group in &diff.added_groups {
+            self.add_group(&**group)?;
+        }
+
+        for (tag, spec) in diff.specs.iter().chain(&unchanged) {
+            self.add_tag(&**tag, spec.clone())?;
+        }
+
+        Ok(())
+    }
+}
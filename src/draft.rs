@@ -0,0 +1,94 @@
+/*
+ * draft.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::{sort_errors, CheckFlags, Result};
+
+/// The outcome of checking an incomplete tagset, from [`Engine::check_tags_draft`].
+///
+/// [`Engine::check_tags_draft`]: ./struct.Engine.html#method.check_tags_draft
+#[derive(Debug, Default, PartialEq)]
+pub struct DraftReport {
+    /// The first rule violated which is not a requirement, if any.
+    ///
+    /// If set, the tagset is invalid even as a draft.
+    pub error: Option<Error>,
+
+    /// Requirements which are not yet met, at most one per tag, sorted by [`sort_errors`].
+    ///
+    /// These must be resolved before the tagset passes [`Engine::check_tags`].
+    /// Only populated if there is no `error`.
+    ///
+    /// [`sort_errors`]: ./fn.sort_errors.html
+    /// [`Engine::check_tags`]: ./struct.Engine.html#method.check_tags
+    pub warnings: Vec<Error>,
+}
+
+impl DraftReport {
+    /// Determines if the tagset is acceptable as a draft.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Determines if the tagset is complete, meeting every rule.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none() && self.warnings.is_empty()
+    }
+
+    /// Converts the report into a `Result`, failing only if the tagset is not a valid draft.
+    #[inline]
+    pub fn into_result(self) -> Result<Vec<Error>> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.warnings),
+        }
+    }
+}
+
+impl Engine {
+    /// Validates a tagset which is still being worked on, such as an unpublished draft.
+    ///
+    /// Everything [`check_tags`] enforces is still enforced, except that unmet requirements
+    /// are collected as warnings instead of failing the check. Conflicts, group cardinalities,
+    /// and unregistered tags are still errors.
+    ///
+    /// [`check_tags`]: #method.check_tags
+    pub fn check_tags_draft(&self, tags: &[Tag]) -> DraftReport {
+        let flags = CheckFlags::all() - CheckFlags::REQUIREMENTS;
+        if let Err(error) = self.check_tags_with_flags(tags, flags) {
+            return DraftReport {
+                error: Some(error),
+                warnings: Vec::new(),
+            };
+        }
+
+        let flags = CheckFlags::REQUIREMENTS;
+        let mut warnings = tags
+            .iter()
+            .filter_map(|tag| {
+                let result = self.get_spec(tag).and_then(|spec| {
+                    spec.check_tag_changes_with_flags(self, tags, &[], &[], &[], flags)
+                });
+
+                result.err()
+            })
+            .collect::<Vec<_>>();
+
+        sort_errors(&mut warnings);
+        DraftReport {
+            error: None,
+            warnings,
+        }
+    }
+}
@@ -11,9 +11,18 @@
  */
 
 use crate::prelude::*;
-use crate::{Error, Result};
+use crate::{
+    sort_errors, Cardinality, CheckFlags, Error, LintWarning, NamePolicy, Result, TagResolver,
+    TagSet,
+};
 use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::iter;
+use std::slice;
+use std::sync::Arc;
 
 /// A representation of a complete configuration of tags, groups, and roles.
 ///
@@ -23,197 +32,1998 @@ use std::collections::{HashMap, HashSet};
 ///
 /// The consumer is responsible for ensuring that referenced tags, groups, and
 /// roles are registered before being used.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Engine {
     specs: HashMap<Tag, TagSpec>,
     tags: HashSet<Tag>,
     roles: HashSet<Role>,
+    parent_groups: HashMap<Tag, Vec<Tag>>,
+    cardinalities: HashMap<Tag, Cardinality>,
+    group_display_names: HashMap<Tag, String>,
+    aliases: HashMap<String, Tag>,
+    exclusion_sets: Vec<Vec<Tag>>,
+    synonym_groups: Vec<Vec<Tag>>,
+    templates: HashMap<String, Vec<Tag>>,
+    disabled_tags: HashSet<Tag>,
+    default_roles: Vec<Role>,
+    inherited_roles: HashMap<Role, Vec<Role>>,
+    role_sets: HashMap<Role, Vec<Role>>,
+    computed_tags: HashMap<Tag, ComputedRule>,
+    hooks: HashMap<Tag, Vec<AppliedHook>>,
+    resolver: Option<Resolver>,
+    policy: NamePolicy,
+    strict: bool,
 }
 
 impl Engine {
+    /// Creates an `Engine` directly from its specifications, tags and groups, and roles.
+    ///
+    /// Every specification must be keyed by its own tag, which must be present in `tags`.
+    /// Any groups or roles referenced by a specification must be registered as well.
+    /// The default [`NamePolicy`] is used, and existing names are not checked against it.
+    /// Strict mode is off.
+    ///
+    /// See [`into_parts`] for the inverse.
+    ///
+    /// [`NamePolicy`]: ./struct.NamePolicy.html
+    /// [`into_parts`]: #method.into_parts
+    pub fn from_parts(
+        specs: HashMap<Tag, TagSpec>,
+        tags: HashSet<Tag>,
+        roles: HashSet<Role>,
+    ) -> Result<Self> {
+        let engine = Engine {
+            specs,
+            tags,
+            roles,
+            parent_groups: HashMap::new(),
+            cardinalities: HashMap::new(),
+            group_display_names: HashMap::new(),
+            aliases: HashMap::new(),
+            exclusion_sets: Vec::new(),
+            synonym_groups: Vec::new(),
+            templates: HashMap::new(),
+            disabled_tags: HashSet::new(),
+            default_roles: Vec::new(),
+            inherited_roles: HashMap::new(),
+            role_sets: HashMap::new(),
+            computed_tags: HashMap::new(),
+            hooks: HashMap::new(),
+            resolver: None,
+            policy: NamePolicy::default(),
+            strict: false,
+        };
+
+        for (tag, spec) in &engine.specs {
+            if !engine.tags.contains(tag) {
+                return Err(Error::MissingTag(Tag::clone(tag)));
+            }
+
+            if spec.tag() != *tag {
+                return Err(Error::Other(
+                    "Specification registered under a different tag",
+                ));
+            }
+
+            for group in &spec.groups {
+                if !engine.tags.contains(group) {
+                    return Err(Error::MissingTag(Tag::clone(group)));
+                }
+
+                if !engine.is_group(group) {
+                    return Err(Error::NotGroup(Tag::clone(group)));
+                }
+            }
+
+            for role in spec.needed_roles.iter() {
+                if !engine.roles.contains(role) {
+                    return Err(Error::MissingRole(Role::clone(role)));
+                }
+            }
+        }
+
+        Ok(engine)
+    }
+
+    /// Decomposes the `Engine` into its specifications, tags and groups, and roles.
+    ///
+    /// Subgroup relationships, group cardinalities, group display names, aliases,
+    /// exclusion sets, synonym groups, templates, disabled tags, default roles,
+    /// role inheritance, role sets, computed tag rules, hooks, and the tag resolver
+    /// are not included.
+    /// See [`from_parts`] for the inverse.
+    ///
+    /// [`from_parts`]: #method.from_parts
+    pub fn into_parts(self) -> (HashMap<Tag, TagSpec>, HashSet<Tag>, HashSet<Role>) {
+        (self.specs, self.tags, self.roles)
+    }
+
+    /// Sets the [`NamePolicy`] used to validate newly-registered tag, group, and role names.
+    ///
+    /// Names which are already registered are not re-checked.
+    ///
+    /// [`NamePolicy`]: ./struct.NamePolicy.html
+    #[inline]
+    pub fn set_name_policy(&mut self, policy: NamePolicy) {
+        self.policy = policy;
+    }
+
+    /// Sets whether the `Engine` is in strict mode, which is off by default.
+    ///
+    /// In strict mode, a specification may only list a group in its `conflicting_tags`
+    /// if the group is exclusive, permitting at most one member to be present through
+    /// its [`Cardinality`]. This makes explicit that conflicting with a group means
+    /// only one of its members may be applied. [`add_tag`] rejects other specifications,
+    /// and [`validate`] reports any already registered.
+    ///
+    /// [`Cardinality`]: ./struct.Cardinality.html
+    /// [`add_tag`]: #method.add_tag
+    /// [`validate`]: #method.validate
+    #[inline]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Determines if the `Engine` is in strict mode. See [`set_strict`].
+    ///
+    /// [`set_strict`]: #method.set_strict
+    #[inline]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets the [`TagResolver`] consulted for tags which are not registered.
+    ///
+    /// Any previous resolver is replaced. Tags it already resolved remain registered.
+    ///
+    /// [`TagResolver`]: ./trait.TagResolver.html
+    pub fn set_resolver<R: TagResolver + 'static>(&mut self, resolver: R) {
+        self.resolver = Some(Resolver(Arc::new(resolver)));
+    }
+
+    /// Removes the [`TagResolver`], if any. Tags it already resolved remain registered.
+    ///
+    /// [`TagResolver`]: ./trait.TagResolver.html
+    #[inline]
+    pub fn clear_resolver(&mut self) {
+        self.resolver = None;
+    }
+
+    #[inline]
+    pub(crate) fn get_resolver(&self) -> Option<Arc<dyn TagResolver>> {
+        self.resolver
+            .as_ref()
+            .map(|resolver| Arc::clone(&resolver.0))
+    }
+
+    /// Gets the [`NamePolicy`] currently in use.
+    ///
+    /// [`NamePolicy`]: ./struct.NamePolicy.html
+    #[inline]
+    pub fn get_name_policy(&self) -> &NamePolicy {
+        &self.policy
+    }
+
+    /// Takes a copy of the `Engine`'s current state, which can be later restored.
+    ///
+    /// Useful for making tentative changes which may need to be rolled back.
+    /// See [`restore`].
+    ///
+    /// [`restore`]: #method.restore
+    pub fn snapshot(&self) -> EngineSnapshot {
+        EngineSnapshot(self.clone())
+    }
+
+    /// Rolls back the `Engine` to the state it was in when the snapshot was taken.
+    ///
+    /// See [`snapshot`].
+    ///
+    /// [`snapshot`]: #method.snapshot
+    pub fn restore(&mut self, snapshot: EngineSnapshot) {
+        *self = snapshot.0;
+    }
+
+    /// Releases excess capacity held by the `Engine`'s internal collections.
+    ///
+    /// Bulk changes, such as applying a configuration, can leave these over-allocated.
+    /// This has no effect on behavior, and is called by [`Configuration::apply`].
+    ///
+    /// [`Configuration::apply`]: ./load/struct.Configuration.html#method.apply
+    pub fn shrink_to_fit(&mut self) {
+        self.specs.shrink_to_fit();
+        self.tags.shrink_to_fit();
+        self.roles.shrink_to_fit();
+        self.parent_groups.shrink_to_fit();
+        self.cardinalities.shrink_to_fit();
+        self.group_display_names.shrink_to_fit();
+        self.aliases.shrink_to_fit();
+        self.exclusion_sets.shrink_to_fit();
+        self.synonym_groups.shrink_to_fit();
+        self.templates.shrink_to_fit();
+        self.disabled_tags.shrink_to_fit();
+        self.default_roles.shrink_to_fit();
+        self.inherited_roles.shrink_to_fit();
+        self.role_sets.shrink_to_fit();
+        self.computed_tags.shrink_to_fit();
+        self.hooks.shrink_to_fit();
+    }
+
+    /// Computes a hash of the tags, groups, roles, and specifications in the `Engine`.
+    ///
+    /// Two engines with the same configuration will produce the same fingerprint,
+    /// regardless of the order in which items were registered. The value is only
+    /// meaningful within the same build of this library, and should not be persisted.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        let mut tags = self.tags.iter().collect::<Vec<_>>();
+        tags.sort_by(|a, b| str::cmp(a, b));
+
+        for tag in tags {
+            tag.hash(&mut hasher);
+
+            if let Some(spec) = self.specs.get(tag) {
+                spec.required_tags.hash(&mut hasher);
+                spec.required_groups.hash(&mut hasher);
+                spec.required_any_of.hash(&mut hasher);
+                spec.publish_required_tags.hash(&mut hasher);
+                spec.conflicting_tags.hash(&mut hasher);
+                spec.implied_tags.hash(&mut hasher);
+                spec.needed_roles.hash(&mut hasher);
+                spec.public.hash(&mut hasher);
+                spec.groups.hash(&mut hasher);
+                spec.is_also_group.hash(&mut hasher);
+                spec.priority.hash(&mut hasher);
+                spec.display_name.hash(&mut hasher);
+
+                #[cfg(feature = "chrono")]
+                spec.expires_at.hash(&mut hasher);
+            }
+
+            self.group_display_names.get(tag).hash(&mut hasher);
+        }
+
+        let mut roles = self.roles.iter().collect::<Vec<_>>();
+        roles.sort_by(|a, b| str::cmp(a, b));
+        roles.hash(&mut hasher);
+
+        let mut subgroups = self
+            .parent_groups
+            .iter()
+            .flat_map(|(child, parents)| parents.iter().map(move |parent| (parent, child)))
+            .collect::<Vec<_>>();
+        subgroups.sort();
+        subgroups.hash(&mut hasher);
+
+        let mut cardinalities = self.cardinalities.iter().collect::<Vec<_>>();
+        cardinalities.sort_by_key(|&(group, _)| group);
+        cardinalities.hash(&mut hasher);
+
+        let mut aliases = self.aliases.iter().collect::<Vec<_>>();
+        aliases.sort();
+        aliases.hash(&mut hasher);
+
+        let mut exclusion_sets = self.exclusion_sets.clone();
+        exclusion_sets.sort();
+        exclusion_sets.hash(&mut hasher);
+
+        let mut synonym_groups = self.synonym_groups.clone();
+        synonym_groups.sort();
+        synonym_groups.hash(&mut hasher);
+
+        let mut templates = self.templates.iter().collect::<Vec<_>>();
+        templates.sort();
+        templates.hash(&mut hasher);
+
+        let mut disabled_tags = self.disabled_tags.iter().collect::<Vec<_>>();
+        disabled_tags.sort();
+        disabled_tags.hash(&mut hasher);
+
+        let mut default_roles = self.default_roles.iter().collect::<Vec<_>>();
+        default_roles.sort();
+        default_roles.hash(&mut hasher);
+
+        let mut inherited_roles = self
+            .inherited_roles
+            .iter()
+            .flat_map(|(role, inherits)| inherits.iter().map(move |other| (role, other)))
+            .collect::<Vec<_>>();
+        inherited_roles.sort();
+        inherited_roles.hash(&mut hasher);
+
+        let mut role_sets = self.role_sets.iter().collect::<Vec<_>>();
+        role_sets.sort();
+        role_sets.hash(&mut hasher);
+
+        // Rules cannot be compared, so only which tags are computed is considered
+        let mut computed_tags = self.computed_tags.keys().collect::<Vec<_>>();
+        computed_tags.sort();
+        computed_tags.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
     /// Registers a tag in the `Engine`, with the given [`TemplateTagSpec`].
     ///
+    /// If the tag is already registered, its specification is replaced.
+    /// Fails if the name is already in use by a tag group or an alias. In [strict mode], also fails
+    /// with [`Error::InvalidConfig`] if it conflicts with a group which is not exclusive.
+    ///
     /// [`TemplateTagSpec`]: ./struct.TemplateTagSpec.html
-    pub fn add_tag<I: Into<String>>(&mut self, name: I, spec: TemplateTagSpec) -> Tag {
+    /// [strict mode]: #method.set_strict
+    /// [`Error::InvalidConfig`]: ./enum.Error.html#variant.InvalidConfig
+    pub fn add_tag<I: Into<String>>(&mut self, name: I, spec: TemplateTagSpec) -> Result<Tag> {
+        let name = name.into();
+        self.policy.check(&name)?;
+
+        let group = self.tags.contains(name.as_str()) && !self.specs.contains_key(name.as_str());
+        if group || self.aliases.contains_key(&name) {
+            return Err(Error::NameCollision(name));
+        }
+
         let tag = Tag::new(name);
+        if self.strict {
+            for group in &spec.conflicting_tags {
+                if self.is_group(group) && !self.is_exclusive_group(group) {
+                    let warning = LintWarning::NonExclusiveConflict(tag, Tag::clone(group));
+                    return Err(Error::InvalidConfig(warning));
+                }
+            }
+        }
+
         let spec = TagSpec::from_template(&tag, spec);
 
-        self.specs.insert(Tag::clone(&tag), spec);
-        self.tags.insert(Tag::clone(&tag));
-        tag
+        self.specs.insert(Tag::clone(&tag), spec);
+        self.tags.insert(Tag::clone(&tag));
+        self.computed_tags.remove(&tag);
+        Ok(tag)
+    }
+
+    /// Registers a computed tag in the `Engine`, which is derived from the rest of a tagset.
+    ///
+    /// Rather than being looked for in a tagset, its presence is determined by calling `rule`.
+    /// It can be referenced by other tags' specifications like any other tag, but cannot
+    /// itself be added or removed by a user, regardless of roles. Attempting to do so fails
+    /// with [`Error::ComputedTag`].
+    ///
+    /// If the tag is already registered, its specification is replaced with an empty one.
+    /// Re-registering it with [`add_tag`] makes it an ordinary tag again.
+    ///
+    /// [`add_tag`]: #method.add_tag
+    /// [`Error::ComputedTag`]: ./enum.Error.html#variant.ComputedTag
+    #[allow(clippy::type_complexity)]
+    pub fn add_computed_tag(
+        &mut self,
+        name: &str,
+        rule: Box<dyn Fn(&[Tag]) -> bool>,
+    ) -> Result<Tag> {
+        let tag = self.add_tag(name, TemplateTagSpec::default())?;
+        let rule = ComputedRule(Arc::from(rule));
+        self.computed_tags.insert(Tag::clone(&tag), rule);
+        Ok(tag)
+    }
+
+    /// Determines if the given tag is a computed tag.
+    ///
+    /// See [`add_computed_tag`].
+    ///
+    /// [`add_computed_tag`]: #method.add_computed_tag
+    #[inline]
+    pub fn is_computed(&self, tag: &Tag) -> bool {
+        self.computed_tags.contains_key(tag)
+    }
+
+    /// Evaluates a computed tag's rule against the given tagset.
+    /// Returns `None` if the tag is not computed.
+    pub(crate) fn evaluate_computed(&self, tag: &Tag, tags: &[Tag]) -> Option<bool> {
+        self.computed_tags.get(tag).map(|rule| (rule.0)(tags))
+    }
+
+    /// Unregisters a tag from the `Engine`. Does nothing if already deleted.
+    ///
+    /// If the tag also acts as a group, it is removed as a group as well,
+    /// including from its members and any subgroups.
+    pub fn delete_tag(&mut self, tag: &Tag) {
+        self.specs.remove(tag);
+        self.tags.remove(tag);
+        self.computed_tags.remove(tag);
+        self.disabled_tags.remove(tag);
+        self.hooks.remove(tag);
+        self.aliases.retain(|_, target| target != tag);
+
+        for spec in self.specs.values_mut() {
+            spec.required_tags.retain(|t| t != tag);
+            spec.required_groups.retain(|t| t != tag);
+            spec.publish_required_tags.retain(|t| t != tag);
+            spec.conflicting_tags.retain(|t| t != tag);
+            spec.implied_tags.retain(|t| t != tag);
+            spec.groups.retain(|t| t != tag);
+
+            for alternatives in &mut spec.required_any_of {
+                alternatives.retain(|t| t != tag);
+            }
+
+            spec.required_any_of
+                .retain(|alternatives| !alternatives.is_empty());
+        }
+
+        self.parent_groups.remove(tag);
+        for parents in self.parent_groups.values_mut() {
+            parents.retain(|t| t != tag);
+        }
+
+        self.cardinalities.remove(tag);
+        self.group_display_names.remove(tag);
+
+        for set in &mut self.exclusion_sets {
+            set.retain(|t| t != tag);
+        }
+
+        self.exclusion_sets.retain(|set| set.len() > 1);
+
+        for group in &mut self.synonym_groups {
+            group.retain(|t| t != tag);
+        }
+
+        self.synonym_groups.retain(|group| group.len() > 1);
+
+        for tags in self.templates.values_mut() {
+            tags.retain(|t| t != tag);
+        }
+    }
+
+    /// Registers a tag group in the `Engine`.
+    ///
+    /// Fails if the name is already in use by a proper tag or an alias. To have a proper tag
+    /// act as a group, set [`is_also_group`] in its specification instead.
+    ///
+    /// [`is_also_group`]: ./struct.TagSpec.html#structfield.is_also_group
+    pub fn add_group<I: Into<String>>(&mut self, name: I) -> Result<Tag> {
+        let name = name.into();
+        self.policy.check(&name)?;
+
+        if self.specs.contains_key(name.as_str()) || self.aliases.contains_key(&name) {
+            return Err(Error::NameCollision(name));
+        }
+
+        let group = Tag::new(name);
+        self.tags.insert(Tag::clone(&group));
+        Ok(group)
+    }
+
+    /// Registers a tag group in the `Engine`, failing if the name is already in use.
+    ///
+    /// Unlike [`add_group`], re-registering an existing group is an error,
+    /// which helps detect duplicate declarations.
+    ///
+    /// [`add_group`]: #method.add_group
+    pub fn try_add_group<I: Into<String>>(&mut self, name: I) -> Result<Tag> {
+        let name = name.into();
+        if self.tags.contains(name.as_str()) {
+            return Err(Error::NameCollision(name));
+        }
+
+        self.add_group(name)
+    }
+
+    /// Unregisters a tag group from the `Engine`. Does nothing if already deleted.
+    ///
+    /// If the group is also a proper tag, the tag itself remains registered,
+    /// but no longer acts as a group.
+    pub fn delete_group(&mut self, group: &Tag) {
+        match self.specs.get_mut(group) {
+            Some(spec) => spec.is_also_group = false,
+            None => {
+                self.tags.remove(group);
+                self.aliases.retain(|_, target| target != group);
+            }
+        }
+
+        for spec in self.specs.values_mut() {
+            spec.groups.retain(|g| g != group);
+            spec.required_groups.retain(|g| g != group);
+
+            for alternatives in &mut spec.required_any_of {
+                alternatives.retain(|g| g != group);
+            }
+
+            spec.required_any_of
+                .retain(|alternatives| !alternatives.is_empty());
+        }
+
+        self.parent_groups.remove(group);
+        for parents in self.parent_groups.values_mut() {
+            parents.retain(|g| g != group);
+        }
+
+        self.cardinalities.remove(group);
+        self.group_display_names.remove(group);
+    }
+
+    /// Limits how many members of the tag group may be present at once.
+    ///
+    /// Enforced when checking with [`CheckFlags::CARDINALITY`].
+    ///
+    /// [`CheckFlags::CARDINALITY`]: ./struct.CheckFlags.html#associatedconstant.CARDINALITY
+    pub fn set_group_cardinality(&mut self, group: &Tag, cardinality: Cardinality) -> Result<()> {
+        if !self.is_group(group) {
+            return Err(Error::NotGroup(Tag::clone(group)));
+        }
+
+        self.cardinalities.insert(Tag::clone(group), cardinality);
+        Ok(())
+    }
+
+    /// Removes any limit on how many members of the tag group may be present.
+    pub fn clear_group_cardinality(&mut self, group: &Tag) {
+        self.cardinalities.remove(group);
+    }
+
+    /// Gets the limit on how many members of the tag group may be present, if any.
+    pub fn get_group_cardinality(&self, group: &Tag) -> Option<Cardinality> {
+        self.cardinalities.get(group).cloned()
+    }
+
+    /// Determines if the tag group permits at most one member to be present.
+    ///
+    /// A group is exclusive if its [`Cardinality`] has a maximum of `1` or less.
+    /// See [`set_group_cardinality`].
+    ///
+    /// [`Cardinality`]: ./struct.Cardinality.html
+    /// [`set_group_cardinality`]: #method.set_group_cardinality
+    pub fn is_exclusive_group(&self, group: &Tag) -> bool {
+        match self.cardinalities.get(group) {
+            Some(Cardinality { max: Some(max), .. }) => *max <= 1,
+            _ => false,
+        }
+    }
+
+    /// Sets whether the tag may be newly applied.
+    ///
+    /// Disabled tags keep their specification, may still be removed, and are tolerated
+    /// in existing tagsets. Only adding them in a change fails, with [`Error::TagDisabled`].
+    ///
+    /// [`Error::TagDisabled`]: ./enum.Error.html#variant.TagDisabled
+    pub fn set_tag_enabled(&mut self, tag: &Tag, enabled: bool) -> Result<()> {
+        self.get_spec(tag)?;
+
+        if enabled {
+            self.disabled_tags.remove(tag);
+        } else {
+            self.disabled_tags.insert(Tag::clone(tag));
+        }
+
+        Ok(())
+    }
+
+    /// Determines whether the tag may be newly applied. See [`set_tag_enabled`].
+    ///
+    /// [`set_tag_enabled`]: #method.set_tag_enabled
+    #[inline]
+    pub fn is_tag_enabled(&self, tag: &Tag) -> bool {
+        !self.disabled_tags.contains(tag)
+    }
+
+    /// Registers a callback to run whenever the tag is added through [`commit_changes`].
+    ///
+    /// The callback is passed the resulting tagset. A tag may have several callbacks,
+    /// which run in the order they were registered. Fails if the tag is not a proper tag.
+    ///
+    /// [`commit_changes`]: #method.commit_changes
+    pub fn on_tag_applied(&mut self, tag: &Tag, hook: Box<Hook>) -> Result<()> {
+        self.get_spec(tag)?;
+
+        let hook = AppliedHook(Arc::from(hook));
+        self.hooks.entry(Tag::clone(tag)).or_default().push(hook);
+        Ok(())
+    }
+
+    /// Sets the label shown in place of the tag or group's canonical name.
+    ///
+    /// Passing `None` reverts to the canonical name. For proper tags this updates
+    /// the specification's `display_name`. See [`display_name`].
+    ///
+    /// [`display_name`]: #method.display_name
+    pub fn set_display_name(&mut self, tag: &Tag, display_name: Option<String>) -> Result<()> {
+        if let Some(spec) = self.specs.get_mut(tag) {
+            spec.display_name = display_name;
+            return Ok(());
+        }
+
+        if !self.tags.contains(tag) {
+            return Err(Error::MissingTag(Tag::clone(tag)));
+        }
+
+        match display_name {
+            Some(display_name) => self
+                .group_display_names
+                .insert(Tag::clone(tag), display_name),
+            None => self.group_display_names.remove(tag),
+        };
+
+        Ok(())
+    }
+
+    /// Gets the label to show for the tag or group, falling back to its canonical name.
+    pub fn display_name<'a>(&'a self, tag: &'a Tag) -> &'a str {
+        let display_name = match self.specs.get(tag) {
+            Some(spec) => spec.display_name.as_ref(),
+            None => self.group_display_names.get(tag),
+        };
+
+        match display_name {
+            Some(display_name) => display_name,
+            None => tag,
+        }
+    }
+
+    /// Requires that the tag only be applied with at least one member of each of the groups.
+    ///
+    /// Replaces any previous set of required groups for the tag.
+    /// Unmet requirements are reported as [`RequiresGroupMember`], naming the first such group.
+    ///
+    /// [`RequiresGroupMember`]: ./enum.Error.html#variant.RequiresGroupMember
+    pub fn add_required_group_set(&mut self, tag: &Tag, groups: Vec<Tag>) -> Result<()> {
+        for group in &groups {
+            if !self.is_group(group) {
+                return Err(Error::NotGroup(Tag::clone(group)));
+            }
+        }
+
+        self.get_spec_mut(tag)?.required_groups = groups;
+        Ok(())
+    }
+
+    /// Requires that at most one of the given tags be present at once.
+    ///
+    /// This expresses mutual exclusion without needing a group for the tags.
+    /// Violations are reported as [`Error::IncompatibleTags`] when checking
+    /// with [`CheckFlags::CONFLICTS`]. Fails if any tag is not a proper tag.
+    ///
+    /// [`Error::IncompatibleTags`]: ./enum.Error.html#variant.IncompatibleTags
+    /// [`CheckFlags::CONFLICTS`]: ./struct.CheckFlags.html#associatedconstant.CONFLICTS
+    pub fn add_exclusion_set(&mut self, mut tags: Vec<Tag>) -> Result<()> {
+        for tag in &tags {
+            self.get_spec(tag)?;
+        }
+
+        tags.sort();
+        tags.dedup();
+
+        if tags.len() > 1 && !self.exclusion_sets.contains(&tags) {
+            self.exclusion_sets.push(tags);
+        }
+
+        Ok(())
+    }
+
+    /// Gets all registered exclusion sets, each sorted.
+    ///
+    /// See [`add_exclusion_set`].
+    ///
+    /// [`add_exclusion_set`]: #method.add_exclusion_set
+    #[inline]
+    pub fn get_exclusion_sets(&self) -> &[Vec<Tag>] {
+        &self.exclusion_sets
+    }
+
+    /// Declares the given tags as synonyms, such as spelling variants of the same tag.
+    ///
+    /// Like an exclusion set, at most one of the synonyms may be present, but each tag
+    /// remains distinct. In addition, [`check_tag`] reports any synonym of the queried tag
+    /// as present. Fails if any tag is not a proper tag.
+    ///
+    /// [`check_tag`]: #method.check_tag
+    pub fn add_synonym_group(&mut self, mut tags: Vec<Tag>) -> Result<()> {
+        for tag in &tags {
+            self.get_spec(tag)?;
+        }
+
+        tags.sort();
+        tags.dedup();
+
+        if tags.len() > 1 && !self.synonym_groups.contains(&tags) {
+            self.synonym_groups.push(tags);
+        }
+
+        Ok(())
+    }
+
+    /// Gets all registered synonym groups, each sorted.
+    ///
+    /// See [`add_synonym_group`].
+    ///
+    /// [`add_synonym_group`]: #method.add_synonym_group
+    #[inline]
+    pub fn get_synonym_groups(&self) -> &[Vec<Tag>] {
+        &self.synonym_groups
+    }
+
+    /// Stores a named tagset, for applications to use as a starting point for new objects.
+    ///
+    /// The tags must form a valid [`TagSet`] which passes [`check_tags`].
+    /// If a template with this name already exists, it is replaced.
+    ///
+    /// [`TagSet`]: ./struct.TagSet.html
+    /// [`check_tags`]: #method.check_tags
+    pub fn register_template(&mut self, name: &str, tags: Vec<Tag>) -> Result<()> {
+        let tags = self.make_tagset(tags)?;
+        self.check_tagset(&tags)?;
+
+        let tags = tags.into_inner();
+        self.templates.insert(str!(name), tags);
+        Ok(())
+    }
+
+    /// Gets the tags in the template with the given name, if registered.
+    ///
+    /// See [`register_template`].
+    ///
+    /// [`register_template`]: #method.register_template
+    #[inline]
+    pub fn template(&self, name: &str) -> Option<&[Tag]> {
+        self.templates.get(name).map(|tags| tags.as_slice())
+    }
+
+    /// Gets all registered templates, keyed by name.
+    ///
+    /// Deleting a tag also removes it from any templates containing it.
+    #[inline]
+    pub fn get_templates(&self) -> &HashMap<String, Vec<Tag>> {
+        &self.templates
+    }
+
+    /// Removes the template with the given name. Does nothing if already deleted.
+    #[inline]
+    pub fn delete_template(&mut self, name: &str) {
+        self.templates.remove(name);
+    }
+
+    /// Makes one tag group a subgroup of another.
+    ///
+    /// Members of the subgroup are then also counted as members of the parent group,
+    /// and of any groups the parent is itself a subgroup of.
+    pub fn add_subgroup(&mut self, parent: &Tag, child: &Tag) -> Result<()> {
+        for group in &[parent, child] {
+            if !self.is_group(group) {
+                return Err(Error::NotGroup(Tag::clone(group)));
+            }
+        }
+
+        if parent == child {
+            return Err(Error::Other("Group cannot be a subgroup of itself"));
+        }
+
+        let parents = self.parent_groups.entry(Tag::clone(child)).or_default();
+        if !parents.contains(parent) {
+            parents.push(Tag::clone(parent));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the groups which the given group is directly a subgroup of.
+    pub fn get_parent_groups(&self, group: &Tag) -> &[Tag] {
+        match self.parent_groups.get(group) {
+            Some(parents) => parents,
+            None => &[],
+        }
+    }
+
+    /// Adds the tag to the group, if it is not already a member.
+    ///
+    /// Fails if the tag is not a proper tag or the group is not a tag group.
+    pub fn add_to_group(&mut self, tag: &Tag, group: &Tag) -> Result<()> {
+        if !self.is_group(group) {
+            return Err(Error::NotGroup(Tag::clone(group)));
+        }
+
+        if tag == group {
+            return Err(Error::Other("Tag cannot be a member of itself"));
+        }
+
+        let spec = self.get_spec_mut(tag)?;
+        if !spec.groups.contains(group) {
+            spec.groups.push(Tag::clone(group));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the tag from the group, leaving both registered.
+    ///
+    /// Does nothing if the tag is not a direct member of the group.
+    /// Fails if the tag is not a proper tag or the group is not a tag group.
+    pub fn remove_from_group(&mut self, tag: &Tag, group: &Tag) -> Result<()> {
+        if !self.is_group(group) {
+            return Err(Error::NotGroup(Tag::clone(group)));
+        }
+
+        self.get_spec_mut(tag)?.groups.retain(|g| g != group);
+        Ok(())
+    }
+
+    /// Makes every current member of the group conflict with every other member.
+    ///
+    /// Each member gains the others in its `conflicting_tags`, skipping any already listed.
+    /// This only affects the members at the time of calling, tags added to the group later
+    /// are unaffected. To have membership itself be exclusive, have members conflict with
+    /// the group instead. Fails if the group is not a tag group.
+    pub fn make_group_mutually_exclusive(&mut self, group: &Tag) -> Result<()> {
+        if !self.is_group(group) {
+            return Err(Error::NotGroup(Tag::clone(group)));
+        }
+
+        let members = self.tags_in_group(group);
+        for member in &members {
+            let spec = self.get_spec_mut(member)?;
+
+            for other in &members {
+                if other != member && !spec.conflicting_tags.contains(other) {
+                    spec.conflicting_tags.push(Tag::clone(other));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes every conflict between proper tags mutual.
+    ///
+    /// For each tag listing another in its `conflicting_tags`, the other gains the tag
+    /// in its own `conflicting_tags`, if not already listed. Conflicts with groups,
+    /// including proper tags which are also groups, are exempt, as conflicting with
+    /// a group is not the same as each of its members conflicting with the tag.
+    /// [`lint`] reports conflicts this would complete.
+    ///
+    /// [`lint`]: #method.lint
+    pub fn symmetrize_conflicts(&mut self) {
+        let mut missing = Vec::new();
+        for (tag, spec) in &self.specs {
+            for other in &spec.conflicting_tags {
+                if self.is_one_sided_conflict(tag, other) {
+                    missing.push((Tag::clone(other), Tag::clone(tag)));
+                }
+            }
+        }
+
+        // Sorted, so the order conflicts are added in does not depend on hashing
+        missing.sort();
+        for (tag, other) in missing {
+            if let Some(spec) = self.specs.get_mut(&tag) {
+                if !spec.conflicting_tags.contains(&other) {
+                    spec.conflicting_tags.push(other);
+                }
+            }
+        }
+    }
+
+    // Whether the tag conflicts with the other proper tag, but not the reverse.
+    pub(crate) fn is_one_sided_conflict(&self, tag: &Tag, other: &Tag) -> bool {
+        if tag == other || self.is_group(tag) || self.is_group(other) {
+            return false;
+        }
+
+        match self.specs.get(other) {
+            Some(spec) => !spec.conflicting_tags.contains(tag),
+            None => false,
+        }
+    }
+
+    /// Renames a tag group, updating every specification which references it.
+    ///
+    /// Proper tags, including those which also act as groups, cannot be renamed this way.
+    pub fn rename_group(&mut self, old: &Tag, new_name: &str) -> Result<Tag> {
+        if self.specs.contains_key(old) || !self.tags.contains(old) {
+            return Err(Error::NotGroup(Tag::clone(old)));
+        }
+
+        self.policy.check(new_name)?;
+
+        if self.tags.contains(new_name) || self.aliases.contains_key(new_name) {
+            return Err(Error::NameCollision(str!(new_name)));
+        }
+
+        let group = Tag::new(new_name);
+        self.tags.remove(old);
+        self.tags.insert(Tag::clone(&group));
+
+        let rename = |tags: &mut Vec<Tag>| {
+            for tag in tags.iter_mut().filter(|tag| *tag == old) {
+                *tag = Tag::clone(&group);
+            }
+        };
+
+        for spec in self.specs.values_mut() {
+            rename(&mut spec.required_tags);
+            rename(&mut spec.required_groups);
+            rename(&mut spec.publish_required_tags);
+            rename(&mut spec.conflicting_tags);
+            rename(&mut spec.groups);
+
+            for alternatives in &mut spec.required_any_of {
+                rename(alternatives);
+            }
+        }
+
+        if let Some(parents) = self.parent_groups.remove(old) {
+            self.parent_groups.insert(Tag::clone(&group), parents);
+        }
+
+        for parents in self.parent_groups.values_mut() {
+            rename(parents);
+        }
+
+        if let Some(cardinality) = self.cardinalities.remove(old) {
+            self.cardinalities.insert(Tag::clone(&group), cardinality);
+        }
+
+        if let Some(display_name) = self.group_display_names.remove(old) {
+            self.group_display_names
+                .insert(Tag::clone(&group), display_name);
+        }
+
+        for target in self.aliases.values_mut().filter(|target| *target == old) {
+            *target = Tag::clone(&group);
+        }
+
+        Ok(group)
+    }
+
+    /// Registers a role in the `Engine`.
+    pub fn add_role<I: Into<String>>(&mut self, name: I) -> Result<Role> {
+        let name = name.into();
+        self.policy.check(&name)?;
+
+        let role = Role::new(name);
+        self.roles.insert(Role::clone(&role));
+        Ok(role)
+    }
+
+    /// Registers a role in the `Engine`, failing if it is already registered.
+    ///
+    /// Unlike [`add_role`], re-registering an existing role is an error,
+    /// which helps detect duplicate declarations.
+    ///
+    /// [`add_role`]: #method.add_role
+    pub fn try_add_role<I: Into<String>>(&mut self, name: I) -> Result<Role> {
+        let name = name.into();
+        if self.roles.contains(name.as_str()) {
+            return Err(Error::NameCollision(name));
+        }
+
+        self.add_role(name)
+    }
+
+    /// Unregisters a role from the `Engine`. Does nothing if already deleted.
+    ///
+    /// The role is also removed from any tags which need it, which may make them
+    /// unrestricted. See [`try_delete_role`] to refuse instead.
+    ///
+    /// [`try_delete_role`]: #method.try_delete_role
+    pub fn delete_role(&mut self, role: &Role) {
+        self.roles.remove(role);
+
+        for spec in self.specs.values_mut() {
+            if spec.needed_roles.contains(role) {
+                spec.needed_roles = spec
+                    .needed_roles
+                    .iter()
+                    .filter(|r| *r != role)
+                    .cloned()
+                    .collect();
+            }
+        }
+
+        self.default_roles.retain(|r| r != role);
+        self.inherited_roles.remove(role);
+        for inherits in self.inherited_roles.values_mut() {
+            inherits.retain(|r| r != role);
+        }
+
+        self.role_sets.remove(role);
+        for members in self.role_sets.values_mut() {
+            members.retain(|r| r != role);
+        }
+    }
+
+    /// Unregisters a role from the `Engine`, unless any tag still needs it.
+    ///
+    /// Fails with [`Error::RoleInUse`] listing those tags, sorted, in which case nothing
+    /// is changed. Use [`delete_role`] to remove the role from them as well.
+    ///
+    /// [`Error::RoleInUse`]: ./enum.Error.html#variant.RoleInUse
+    /// [`delete_role`]: #method.delete_role
+    pub fn try_delete_role(&mut self, role: &Role) -> Result<()> {
+        if !self.roles.contains(role) {
+            return Err(Error::MissingRole(Role::clone(role)));
+        }
+
+        let mut dependents = self
+            .specs
+            .iter()
+            .filter(|(_, spec)| spec.needed_roles.contains(role))
+            .map(|(tag, _)| Tag::clone(tag))
+            .collect::<Vec<_>>();
+
+        if !dependents.is_empty() {
+            dependents.sort();
+            return Err(Error::RoleInUse(Role::clone(role), dependents));
+        }
+
+        self.delete_role(role);
+        Ok(())
+    }
+
+    /// Makes a role count as held by everyone, whether or not it was passed in.
+    ///
+    /// See [`effective_roles`].
+    ///
+    /// [`effective_roles`]: #method.effective_roles
+    pub fn add_default_role(&mut self, role: &Role) -> Result<()> {
+        if !self.roles.contains(role) {
+            return Err(Error::MissingRole(Role::clone(role)));
+        }
+
+        if !self.default_roles.contains(role) {
+            self.default_roles.push(Role::clone(role));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the roles which everyone is considered to hold.
+    #[inline]
+    pub fn get_default_roles(&self) -> &[Role] {
+        &self.default_roles
+    }
+
+    /// Makes holders of one role also hold another, and anything that role inherits.
+    ///
+    /// See [`effective_roles`].
+    ///
+    /// [`effective_roles`]: #method.effective_roles
+    pub fn add_role_inheritance(&mut self, role: &Role, inherits: &Role) -> Result<()> {
+        for role in &[role, inherits] {
+            if !self.roles.contains(*role) {
+                return Err(Error::MissingRole(Role::clone(role)));
+            }
+        }
+
+        let roles = self.inherited_roles.entry(Role::clone(role)).or_default();
+        if role != inherits && !roles.contains(inherits) {
+            roles.push(Role::clone(inherits));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the roles which the given role directly inherits.
+    pub fn get_inherited_roles(&self, role: &Role) -> &[Role] {
+        match self.inherited_roles.get(role) {
+            Some(roles) => roles,
+            None => &[],
+        }
+    }
+
+    /// Defines a named set of roles, which tags can list in `needed_roles` in place of its members.
+    ///
+    /// The name is registered as a role if it is not already, and holding any member of the
+    /// set counts as holding the set itself. If a set with this name already exists,
+    /// its members are replaced. Fails if any member is not a registered role.
+    ///
+    /// See [`effective_roles`].
+    ///
+    /// [`effective_roles`]: #method.effective_roles
+    pub fn add_role_set(&mut self, name: &str, mut roles: Vec<Role>) -> Result<Role> {
+        for role in &roles {
+            if !self.roles.contains(role) {
+                return Err(Error::MissingRole(Role::clone(role)));
+            }
+        }
+
+        let set = self.add_role(name)?;
+        roles.sort();
+        roles.dedup();
+        roles.retain(|role| *role != set);
+
+        self.role_sets.insert(Role::clone(&set), roles);
+        Ok(set)
+    }
+
+    /// Removes the definition of a role set, leaving the role itself registered.
+    /// Does nothing if the role is not a role set.
+    pub fn delete_role_set(&mut self, set: &Role) {
+        self.role_sets.remove(set);
+    }
+
+    /// Gets the members of the given role set, sorted, or `None` if it is not one.
+    pub fn get_role_set(&self, set: &Role) -> Option<&[Role]> {
+        self.role_sets.get(set).map(|roles| roles.as_slice())
+    }
+
+    /// Gets all role sets, and their members.
+    #[inline]
+    pub fn get_role_sets(&self) -> &HashMap<Role, Vec<Role>> {
+        &self.role_sets
+    }
+
+    /// Gets the full set of roles a user is considered to hold when checking tag changes.
+    ///
+    /// This is the held roles together with the default roles,
+    /// plus every role they inherit, directly or indirectly,
+    /// plus every role set with any of those as a member.
+    pub fn effective_roles(&self, held: &[Role]) -> HashSet<Role> {
+        let mut roles = HashSet::new();
+        let mut pending = held.iter().chain(&self.default_roles).collect::<Vec<_>>();
+
+        while !pending.is_empty() {
+            while let Some(role) = pending.pop() {
+                if roles.insert(Role::clone(role)) {
+                    pending.extend(self.get_inherited_roles(role));
+                }
+            }
+
+            // Holding a member of a set grants the set, which may grant further roles
+            pending.extend(
+                self.role_sets
+                    .iter()
+                    .filter(|(set, members)| {
+                        !roles.contains(*set) && members.iter().any(|role| roles.contains(role))
+                    })
+                    .map(|(set, _)| set),
+            );
+        }
+
+        roles
+    }
+
+    /// Gets the roles permitted to add or remove the tag, any one of which suffices.
+    ///
+    /// This is the tag's `needed_roles` along with the members of any role sets, sorted.
+    /// The sets themselves are kept, as holding one directly also suffices. Empty only if
+    /// anyone may change the tag, because it is public or needs no roles. Roles which
+    /// inherit one of these are not listed. Fails if it is not a proper tag.
+    pub fn min_roles_to_apply(&self, tag: &Tag) -> Result<Vec<Role>> {
+        let spec = self.get_spec(tag)?;
+        if spec.public {
+            return Ok(Vec::new());
+        }
+
+        let mut roles = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = spec.needed_roles.iter().collect::<Vec<_>>();
+
+        while let Some(role) = pending.pop() {
+            if !seen.insert(role) {
+                continue;
+            }
+
+            if let Some(members) = self.role_sets.get(role) {
+                pending.extend(members);
+            }
+
+            roles.push(Role::clone(role));
+        }
+
+        roles.sort();
+        Ok(roles)
+    }
+
+    /// Gets a [`HashSet`] of all tags and tag groups in the `Engine`.
+    ///
+    /// [`HashSet`]: https://doc.rust-lang.org/stable/std/collections/struct.HashSet.html
+    #[inline]
+    pub fn get_tags(&self) -> &HashSet<Tag> {
+        &self.tags
+    }
+
+    /// Gets a read-only set of all registered [`TagSpec`]s.
+    /// This will not include specification data for tag groups, only proper tags.
+    ///
+    /// [`TagSpec`]: ./tag/spec.html
+    #[inline]
+    pub fn get_specs(&self) -> &HashMap<Tag, TagSpec> {
+        &self.specs
+    }
+
+    /// Gets all registered [`TagSpec`]s, sorted by tag name.
+    ///
+    /// Unlike [`get_specs`], the order is the same every time, so this should be used
+    /// for anything which is exported or displayed.
+    ///
+    /// [`TagSpec`]: ./tag/spec.html
+    /// [`get_specs`]: #method.get_specs
+    pub fn specs_sorted(&self) -> Vec<(&Tag, &TagSpec)> {
+        let mut specs = self.specs.iter().collect::<Vec<_>>();
+        specs.sort_by(|(a, _), (b, _)| str::cmp(a, b));
+        specs
+    }
+
+    /// Gets a read-only set of all registered [`Role`]s.
+    ///
+    /// [`Role`]: ./tag/role.html
+    #[inline]
+    pub fn get_roles(&self) -> &HashSet<Role> {
+        &self.roles
+    }
+
+    /// Iterates over the names of all proper tags, in no particular order.
+    ///
+    /// Tag groups are not included.
+    pub fn tag_names(&self) -> impl Iterator<Item = &str> {
+        self.specs.keys().map(|tag| tag.as_ref())
+    }
+
+    /// Iterates over the names of all registered roles, in no particular order.
+    pub fn role_names(&self) -> impl Iterator<Item = &str> {
+        self.roles.iter().map(|role| role.as_ref())
+    }
+
+    /// Gets the specification associated with a [`Tag`].
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    pub fn get_spec(&self, tag: &Tag) -> Result<&TagSpec> {
+        match self.specs.get(tag) {
+            Some(spec) => Ok(spec),
+            None => Err(Error::MissingTag(Tag::clone(tag))),
+        }
+    }
+
+    // Looks up the specification of a tag appearing in a tagset.
+    // Groups are not tags themselves, so a bare group is rejected rather than reported missing.
+    pub(crate) fn get_tagset_spec(&self, tag: &Tag) -> Result<&TagSpec> {
+        match self.specs.get(tag) {
+            Some(spec) => Ok(spec),
+            None if self.tags.contains(tag) => Err(Error::GroupAsTag(Tag::clone(tag))),
+            None => Err(Error::MissingTag(Tag::clone(tag))),
+        }
+    }
+
+    /// Gets the specification associated a [`Tag`] as `&mut`.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    pub fn get_spec_mut(&mut self, tag: &Tag) -> Result<&mut TagSpec> {
+        match self.specs.get_mut(tag) {
+            Some(spec) => Ok(spec),
+            None => Err(Error::MissingTag(Tag::clone(tag))),
+        }
+    }
+
+    /// Gets the stored [`Tag`] along with its specification.
+    ///
+    /// Like [`get_spec`], but also borrows the key, avoiding the need to clone it.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    /// [`get_spec`]: #method.get_spec
+    pub fn spec_entry(&self, tag: &Tag) -> Result<(&Tag, &TagSpec)> {
+        match self.specs.get_key_value(tag) {
+            Some(entry) => Ok(entry),
+            None => Err(Error::MissingTag(Tag::clone(tag))),
+        }
+    }
+
+    /// Copies the specification of an existing [`Tag`] as a [`TemplateTagSpec`].
+    ///
+    /// This can be modified and passed to [`add_tag`] to create a similar tag.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    /// [`TemplateTagSpec`]: ./struct.TemplateTagSpec.html
+    /// [`add_tag`]: #method.add_tag
+    pub fn clone_spec(&self, source: &Tag) -> Result<TemplateTagSpec> {
+        self.get_spec(source).map(TemplateTagSpec::from)
+    }
+
+    /// Determines if a [`Tag`] with the given name is registered.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    pub fn has_tag<B: Borrow<str>>(&self, name: B) -> bool {
+        let name = name.borrow();
+
+        self.tags.get(name).is_some()
+    }
+
+    /// Gets the [`Tag`] with the given name.
+    ///
+    /// If no tag or group has this name, but it is an alias, its canonical tag is returned.
+    /// See [`add_alias`].
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    /// [`add_alias`]: #method.add_alias
+    pub fn get_tag<B: Borrow<str>>(&self, name: B) -> Result<Tag> {
+        let name = name.borrow();
+
+        match self.tags.get(name).or_else(|| self.aliases.get(name)) {
+            Some(tag) => Ok(Tag::clone(tag)),
+            None => Err(Error::NoSuchTag(str!(name))),
+        }
+    }
+
+    /// Registers an alternative name for a tag or group, which [`get_tag`] resolves to it.
+    ///
+    /// Unlike synonyms, the alias is not a tag itself, so it can never appear in a tagset.
+    /// If the alias already exists, it is retargeted. Fails if the name is in use by a tag
+    /// or group, or if `canonical` is not registered.
+    ///
+    /// [`get_tag`]: #method.get_tag
+    pub fn add_alias(&mut self, alias: &str, canonical: &Tag) -> Result<()> {
+        self.policy.check(alias)?;
+
+        if self.tags.contains(alias) {
+            return Err(Error::NameCollision(str!(alias)));
+        }
+
+        if !self.tags.contains(canonical) {
+            return Err(Error::MissingTag(Tag::clone(canonical)));
+        }
+
+        self.aliases.insert(str!(alias), Tag::clone(canonical));
+        Ok(())
+    }
+
+    /// Removes an alias. Does nothing if it does not exist.
+    pub fn delete_alias(&mut self, alias: &str) {
+        self.aliases.remove(alias);
+    }
+
+    /// Gets all aliases, and the tags or groups they stand for.
+    #[inline]
+    pub fn get_aliases(&self) -> &HashMap<String, Tag> {
+        &self.aliases
+    }
+
+    /// Parses a list of tags stored as a single string, such as from a database column.
+    ///
+    /// Tags may be separated by whitespace or commas, and empty entries are skipped.
+    /// Aliases are resolved, as per [`get_tag`]. Fails with [`Error::NoSuchTag`]
+    /// for the first name which is not registered. See [`format_tags`] for the inverse.
+    ///
+    /// [`get_tag`]: #method.get_tag
+    /// [`format_tags`]: #method.format_tags
+    /// [`Error::NoSuchTag`]: ./enum.Error.html#variant.NoSuchTag
+    pub fn parse_tags(&self, tags: &str) -> Result<Vec<Tag>> {
+        tags.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|name| !name.is_empty())
+            .map(|name| self.get_tag(name))
+            .collect()
+    }
+
+    /// Formats a list of tags as a single space-separated string.
+    ///
+    /// See [`parse_tags`] for the inverse.
+    ///
+    /// [`parse_tags`]: #method.parse_tags
+    pub fn format_tags(tags: &[Tag]) -> String {
+        let names = tags.iter().map(|tag| &**tag).collect::<Vec<_>>();
+        names.join(" ")
+    }
+
+    /// Borrows the stored [`Tag`] or tag group with the given name, if registered.
+    ///
+    /// Unlike [`get_tag`], this does not produce a new owned handle.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    /// [`get_tag`]: #method.get_tag
+    #[inline]
+    pub fn tag_handle(&self, name: &str) -> Option<&Tag> {
+        self.tags.get(name)
+    }
+
+    /// Searches for registered [`Tag`]s whose names contain the given query, ignoring case.
+    ///
+    /// Display names are searched as well as canonical names.
+    /// Tags starting with the query are listed first, with each portion sorted by name.
+    /// At most `limit` tags are returned. Tag groups are only included if `include_groups`
+    /// is set.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    pub fn tags_matching(&self, query: &str, limit: usize, include_groups: bool) -> Vec<Tag> {
+        let query = query.to_lowercase();
+
+        let mut matches = self
+            .tags
+            .iter()
+            .filter(|tag| include_groups || self.specs.contains_key(*tag))
+            .filter_map(|tag| {
+                let name = tag.to_lowercase();
+                let display_name = self.display_name(tag).to_lowercase();
+                let names = [name, display_name];
+
+                if names.iter().any(|name| name.contains(&query)) {
+                    let prefix = names.iter().any(|name| name.starts_with(&query));
+                    Some((!prefix, tag))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        matches.sort();
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, tag)| Tag::clone(tag))
+            .collect()
+    }
+
+    /// Determines if the given [`Tag`] is present as a group.
+    ///
+    /// This is true for tag groups, and proper tags which are also groups.
+    ///
+    /// [`Tag`]: ./tag/tag.html
+    pub fn is_group(&self, tag: &Tag) -> bool {
+        match self.specs.get(tag) {
+            Some(spec) => spec.is_also_group,
+            None => self.tags.contains(tag),
+        }
+    }
+
+    /// Gets all proper tags which are members of the given group, sorted.
+    ///
+    /// If the group is also a proper tag, it is included as well, as are members of subgroups.
+    pub fn tags_in_group(&self, group: &Tag) -> Vec<Tag> {
+        let mut tags = self
+            .specs
+            .iter()
+            .filter(|(tag, spec)| *tag == group || self.in_group(tag, spec, group))
+            .map(|(tag, _)| Tag::clone(tag))
+            .collect::<Vec<_>>();
+
+        tags.sort();
+        tags
+    }
+
+    /// Determines if a [`Role`] with the given name is registered.
+    ///
+    /// [`Role`]: ./tag/role.html
+    pub fn has_role<B: Borrow<str>>(&self, name: B) -> bool {
+        let name = name.borrow();
+
+        self.roles.get(name).is_some()
+    }
+
+    /// Gets the [`Role`] with the given name.
+    ///
+    /// [`Role`]: ./tag/role.html
+    pub fn get_role<B: Borrow<str>>(&self, name: B) -> Result<Role> {
+        let name = name.borrow();
+
+        match self.roles.get(name) {
+            Some(role) => Ok(Role::clone(role)),
+            None => Err(Error::NoSuchRole(str!(name))),
+        }
+    }
+
+    /// Borrows the stored [`Role`] with the given name, if registered.
+    ///
+    /// Unlike [`get_role`], this does not produce a new owned handle.
+    ///
+    /// [`Role`]: ./tag/role.html
+    /// [`get_role`]: #method.get_role
+    #[inline]
+    pub fn role_handle(&self, name: &str) -> Option<&Role> {
+        self.roles.get(name)
+    }
+
+    /// Count the number of tags in the list that are in the given group.
+    /// For tags this will return 0 or 1.
+    ///
+    /// For tags which are also groups, this counts the tag itself as well as its members.
+    /// Members of subgroups are counted as members of their parent groups.
+    ///
+    /// Groups which are not also proper tags may not appear in the list,
+    /// and produce an [`Error::GroupAsTag`].
+    ///
+    /// [`Error::GroupAsTag`]: ./enum.Error.html#variant.GroupAsTag
+    pub fn count_tag(&self, check: &Tag, tags: &[Tag]) -> Result<usize> {
+        if let Some(present) = self.evaluate_computed(check, tags) {
+            for tag in tags {
+                self.get_tagset_spec(tag)?;
+            }
+
+            return Ok(usize::from(present));
+        }
+
+        let mut count = 0;
+
+        for tag in tags {
+            let spec = self.get_tagset_spec(tag)?;
+            if tag == check || self.in_group(tag, spec, check) {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Like [`count_tag`], but skips any unregistered tags or bare groups in the list instead of failing.
+    ///
+    /// Useful for counting over externally-sourced tag lists which may be out of date.
+    ///
+    /// [`count_tag`]: #method.count_tag
+    pub fn count_tag_lenient(&self, check: &Tag, tags: &[Tag]) -> usize {
+        if let Some(present) = self.evaluate_computed(check, tags) {
+            return usize::from(present);
+        }
+
+        tags.iter()
+            .filter(|tag| match self.specs.get(*tag) {
+                Some(spec) => *tag == check || self.in_group(tag, spec, check),
+                None => false,
+            })
+            .count()
+    }
+
+    fn in_group(&self, tag: &Tag, spec: &TagSpec, group: &Tag) -> bool {
+        if spec.groups.contains(group) {
+            return true;
+        }
+
+        if self.parent_groups.is_empty() {
+            return false;
+        }
+
+        // Walk up through parent groups, guarding against cycles
+        let mut seen = Vec::new();
+        let mut pending = spec.groups.iter().collect::<Vec<_>>();
+        if spec.is_also_group {
+            pending.push(tag);
+        }
+
+        while let Some(current) = pending.pop() {
+            if current == group {
+                return true;
+            }
+
+            if seen.contains(&current) {
+                continue;
+            }
+
+            seen.push(current);
+            pending.extend(self.get_parent_groups(current));
+        }
+
+        false
+    }
+
+    /// Determines if the given tag/group is present in the list.
+    ///
+    /// For computed tags, this evaluates its rule against the list instead.
+    /// For tags with synonyms, any of its synonyms being present counts as well.
+    pub fn check_tag(&self, check: &Tag, tags: &[Tag]) -> Result<bool> {
+        if self.is_group(check) || self.is_computed(check) {
+            return self.count_tag(check, tags).map(|count| count > 0);
+        }
+
+        let mut synonyms = self
+            .synonym_groups
+            .iter()
+            .filter(|group| group.contains(check))
+            .flatten();
+
+        Ok(tags.contains(check) || synonyms.any(|tag| tags.contains(tag)))
     }
 
-    /// Unregisters a tag from the `Engine`. Does nothing if already deleted.
-    pub fn delete_tag(&mut self, tag: &Tag) {
-        self.specs.remove(tag);
-        self.tags.remove(tag);
+    /// Cancels out tags which are both added and removed in the same change.
+    ///
+    /// Such a change is a net no-op, but [`check_tag_changes`] rejects it as contradictory.
+    /// Note that this silently drops those instructions rather than reporting them,
+    /// so the caller should only use it when tolerating malformed changes is acceptable.
+    ///
+    /// Returns the cleaned `(added, removed)` lists, with order otherwise preserved.
+    ///
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    pub fn normalize_change(&self, added: &[Tag], removed: &[Tag]) -> (Vec<Tag>, Vec<Tag>) {
+        let added_tags = added
+            .iter()
+            .filter(|tag| !removed.contains(tag))
+            .cloned()
+            .collect();
 
-        for spec in self.specs.values_mut() {
-            spec.required_tags.retain(|t| t != tag);
-            spec.conflicting_tags.retain(|t| t != tag);
-        }
-    }
+        let removed_tags = removed
+            .iter()
+            .filter(|tag| !added.contains(tag))
+            .cloned()
+            .collect();
 
-    /// Registers a tag group in the `Engine`.
-    pub fn add_group<I: Into<String>>(&mut self, name: I) -> Tag {
-        let group = Tag::new(name);
-        self.tags.insert(Tag::clone(&group));
-        group
+        (added_tags, removed_tags)
     }
 
-    /// Unregisters a tag group from the `Engine`. Does nothing if already deleted.
-    pub fn delete_group(&mut self, group: &Tag) {
-        self.tags.remove(group);
+    /// Determines which [`Role`]s are needed to perform the given tag changes.
+    ///
+    /// Returns the union of the `needed_roles` of every non-public tag being added or removed.
+    /// Note that a user only needs one of the roles listed for each particular tag,
+    /// so holding every returned role is sufficient but not always necessary.
+    ///
+    /// This does not consider which roles the user currently has, see
+    /// [`check_tag_changes`] for that.
+    ///
+    /// [`Role`]: ./tag/role.html
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    pub fn required_roles_for_change(
+        &self,
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+    ) -> Result<HashSet<Role>> {
+        let mut roles = HashSet::new();
 
-        for spec in self.specs.values_mut() {
-            spec.groups.retain(|g| g != group);
+        for tag in added_tags.iter().chain(removed_tags) {
+            let spec = self.get_spec(tag)?;
+            if !spec.public {
+                roles.extend(spec.needed_roles.iter().cloned());
+            }
         }
+
+        Ok(roles)
     }
 
-    /// Registers a role in the `Engine`.
-    pub fn add_role<I: Into<String>>(&mut self, name: I) -> Role {
-        let role = Role::new(name);
-        self.roles.insert(Role::clone(&role));
-        role
+    /// Validates the given list of tags against the engine's tag policies.
+    ///
+    /// Groups which are not also proper tags may not appear in the list,
+    /// and produce an [`Error::GroupAsTag`].
+    ///
+    /// [`Error::GroupAsTag`]: ./enum.Error.html#variant.GroupAsTag
+    #[inline]
+    pub fn check_tags(&self, tags: &[Tag]) -> Result<()> {
+        self.check_tags_with_flags(tags, CheckFlags::all())
     }
 
-    /// Unregisters a role from the `Engine`. Does nothing if already deleted.
-    pub fn delete_role(&mut self, role: &Role) {
-        self.roles.remove(role);
+    /// Validates that the given tags form a proper [`TagSet`], for use with [`check_tagset`]
+    /// and [`check_tagset_changes`].
+    ///
+    /// Every tag must be registered, appear only once, and not be a bare group.
+    /// The order of the tags is preserved.
+    ///
+    /// [`TagSet`]: ./struct.TagSet.html
+    /// [`check_tagset`]: #method.check_tagset
+    /// [`check_tagset_changes`]: #method.check_tagset_changes
+    pub fn make_tagset(&self, tags: Vec<Tag>) -> Result<TagSet<'_>> {
+        let mut seen = HashSet::with_capacity(tags.len());
+        let mut specs = Vec::with_capacity(tags.len());
 
-        for spec in self.specs.values_mut() {
-            spec.needed_roles.retain(|r| r != role);
+        for tag in &tags {
+            specs.push(self.get_tagset_spec(tag)?);
+
+            if !seen.insert(tag) {
+                return Err(Error::DuplicateTag(Tag::clone(tag)));
+            }
         }
+
+        Ok(TagSet::new_unchecked(tags, specs))
     }
 
-    /// Gets a [`HashSet`] of all tags and tag groups in the `Engine`.
+    /// Validates the given [`TagSet`] as [`check_tags`] does.
     ///
-    /// [`HashSet`]: https://doc.rust-lang.org/stable/std/collections/struct.HashSet.html
-    #[inline]
-    pub fn get_tags(&self) -> &HashSet<Tag> {
-        &self.tags
+    /// Since the set was already validated by [`make_tagset`], each tag's
+    /// specification is used as-is rather than looked up again.
+    ///
+    /// [`TagSet`]: ./struct.TagSet.html
+    /// [`check_tags`]: #method.check_tags
+    /// [`make_tagset`]: #method.make_tagset
+    pub fn check_tagset(&self, tags: &TagSet) -> Result<()> {
+        let flags = CheckFlags::all();
+        let result = tags
+            .specs()
+            .iter()
+            .try_for_each(|spec| {
+                spec.check_tag_changes_with_flags(self, tags, &[], &[], &[], flags)
+            })
+            .and_then(|()| self.check_set_rules(tags, flags));
+
+        #[cfg(feature = "log")]
+        log_failure(tags, &result);
+
+        result
     }
 
-    /// Gets a read-only set of all registered [`TagSpec`]s.
-    /// This will not include specification data for tag groups, only proper tags.
+    /// Validates a change to the given [`TagSet`] as [`check_tag_changes`] does.
     ///
-    /// [`TagSpec`]: ./tag/spec.html
-    #[inline]
-    pub fn get_specs(&self) -> &HashMap<Tag, TagSpec> {
-        &self.specs
+    /// The added tags must form a `TagSet` as well. Like [`check_tagset`],
+    /// this uses the sets' specifications rather than looking them up again.
+    ///
+    /// [`TagSet`]: ./struct.TagSet.html
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    /// [`check_tagset`]: #method.check_tagset
+    pub fn check_tagset_changes(
+        &self,
+        tags: &TagSet,
+        added_tags: &TagSet,
+        removed_tags: &[Tag],
+        roles: &[Role],
+    ) -> Result<()> {
+        let flags = CheckFlags::all();
+        let specs = tags
+            .specs()
+            .iter()
+            .chain(added_tags.specs())
+            .map(|spec| Ok(*spec));
+        let result =
+            self.check_tag_changes_inner(tags, added_tags, removed_tags, roles, flags, specs);
+
+        #[cfg(feature = "log")]
+        log_failure(tags, &result);
+
+        result
     }
 
-    /// Gets a read-only set of all registered [`Role`]s.
+    /// Validates the given list of tags as if it were being applied to a new object.
     ///
-    /// [`Role`]: ./tag/role.html
+    /// Unlike [`check_tags`], which treats the tags as already present and so never
+    /// checks roles, every tag is considered newly added. The user with the given
+    /// `roles` must therefore be permitted to apply each of them.
+    ///
+    /// [`check_tags`]: #method.check_tags
     #[inline]
-    pub fn get_roles(&self) -> &HashSet<Role> {
-        &self.roles
+    pub fn check_tags_as_creation(&self, tags: &[Tag], roles: &[Role]) -> Result<()> {
+        self.check_tag_changes(&[], tags, &[], roles)
     }
 
-    /// Gets the specification associated with a [`Tag`].
+    /// Validates the given list of tags for publishing, rather than saving as a draft.
     ///
-    /// [`Tag`]: ./tag/tag.html
-    pub fn get_spec(&self, tag: &Tag) -> Result<&TagSpec> {
-        match self.specs.get(tag) {
-            Some(spec) => Ok(spec),
-            None => Err(Error::MissingTag(Tag::clone(tag))),
+    /// In addition to everything [`check_tags`] enforces, each tag's
+    /// `publish_required_tags` must be present.
+    ///
+    /// [`check_tags`]: #method.check_tags
+    pub fn check_tags_for_publish(&self, tags: &[Tag]) -> Result<()> {
+        self.check_tags(tags)?;
+
+        for tag in tags {
+            self.get_spec(tag)?.check_publish_requirements(self, tags)?;
         }
+
+        Ok(())
     }
 
-    /// Gets the specification associated a [`Tag`] as `&mut`.
+    /// Validates many lists of tags at once, such as during a bulk import.
     ///
-    /// [`Tag`]: ./tag/tag.html
-    pub fn get_spec_mut(&mut self, tag: &Tag) -> Result<&mut TagSpec> {
-        match self.specs.get_mut(tag) {
-            Some(spec) => Ok(spec),
-            None => Err(Error::MissingTag(Tag::clone(tag))),
-        }
+    /// Returns the result for each tagset, along with its index in the input.
+    pub fn check_many<'a, I>(&self, tagsets: I) -> Vec<(usize, Result<()>)>
+    where
+        I: IntoIterator<Item = &'a [Tag]>,
+    {
+        tagsets
+            .into_iter()
+            .enumerate()
+            .map(|(i, tags)| (i, self.check_tags(tags)))
+            .collect()
     }
 
-    /// Determines if a [`Tag`] with the given name is registered.
+    /// Proposes a tag to remove in order to resolve a conflict in the given tagset.
     ///
-    /// [`Tag`]: ./tag/tag.html
-    pub fn has_tag<B: Borrow<str>>(&self, name: B) -> bool {
-        let name = name.borrow();
+    /// Of the tags involved in the first conflict found, the one with the lowest
+    /// `priority` is chosen. Ties go to whichever appears last in `tags`.
+    /// A group with more members present than its [cardinality] allows counts as
+    /// a conflict between those members.
+    ///
+    /// Returns `None` if there are no conflicts. Requirements are not considered,
+    /// nor are groups with too few members present, as removing a tag cannot fix them.
+    ///
+    /// [cardinality]: #method.set_group_cardinality
+    pub fn suggest_removal(&self, tags: &[Tag]) -> Result<Option<Tag>> {
+        let flags = CheckFlags::CONFLICTS | CheckFlags::CARDINALITY;
+        let (tag, other) = match self.check_tags_inner(tags, flags, None) {
+            Ok(()) => return Ok(None),
+            Err(Error::IncompatibleTags(tag, other)) => (tag, other),
+            Err(Error::IncompatibleViaGroup(tag, _, group)) => (tag, group),
+            Err(Error::GroupCardinality(_, cardinality, members)) => {
+                if members.len() < cardinality.min {
+                    return Ok(None);
+                }
 
-        self.tags.get(name).is_some()
+                let candidates = tags.iter().filter(|tag| members.contains(tag));
+                return self.lowest_priority(candidates);
+            }
+            Err(error) => return Err(error),
+        };
+
+        // When conflicting with a group, any of its other members present are involved
+        let mut candidates = Vec::new();
+        for candidate in tags {
+            if *candidate == tag || self.count_tag(&other, slice::from_ref(candidate))? > 0 {
+                candidates.push(candidate);
+            }
+        }
+
+        self.lowest_priority(candidates.into_iter())
     }
 
-    /// Gets the [`Tag`] with the given name.
+    // Picks the tag with the lowest priority, preferring later ones on ties.
+    fn lowest_priority<'a, I>(&self, tags: I) -> Result<Option<Tag>>
+    where
+        I: Iterator<Item = &'a Tag>,
+    {
+        let mut best: Option<(i32, &Tag)> = None;
+        for tag in tags {
+            let priority = self.get_spec(tag)?.priority;
+            match best {
+                Some((lowest, _)) if priority > lowest => (),
+                _ => best = Some((priority, tag)),
+            }
+        }
+
+        Ok(best.map(|(_, tag)| Tag::clone(tag)))
+    }
+
+    /// Expands the given list of tags with everything they imply, then validates it.
     ///
-    /// [`Tag`]: ./tag/tag.html
-    pub fn get_tag<B: Borrow<str>>(&self, name: B) -> Result<Tag> {
-        let name = name.borrow();
+    /// Implications are applied repeatedly, so tags implied by implied tags are added too.
+    /// Returns the expanded tagset, with new tags appended in the order they were implied.
+    pub fn check_tags_closure(&self, tags: &[Tag]) -> Result<Vec<Tag>> {
+        let mut tags = tags.to_vec();
 
-        match self.tags.get(name) {
-            Some(tag) => Ok(Tag::clone(tag)),
-            None => Err(Error::NoSuchTag(str!(name))),
+        // Each pass adds at least one tag, so this bounds the number of passes
+        for _ in 0..=self.specs.len() {
+            let mut implied = Vec::new();
+
+            for tag in &tags {
+                for implied_tag in &self.get_spec(tag)?.implied_tags {
+                    if !tags.contains(implied_tag) && !implied.contains(implied_tag) {
+                        implied.push(Tag::clone(implied_tag));
+                    }
+                }
+            }
+
+            if implied.is_empty() {
+                self.check_tags(&tags)?;
+                return Ok(tags);
+            }
+
+            tags.extend(implied);
         }
+
+        Err(Error::Other("Tag implications did not converge"))
     }
 
-    /// Determines if the given [`Tag`] is present as a group.
+    /// Validates the given list of tags, only enforcing the kinds of rules selected in `flags`.
+    pub fn check_tags_with_flags(&self, tags: &[Tag], flags: CheckFlags) -> Result<()> {
+        let result = self.check_tags_inner(tags, flags, None);
+
+        #[cfg(feature = "log")]
+        log_failure(tags, &result);
+
+        result
+    }
+
+    /// Validates the given list of tags, collecting every error rather than stopping at the first.
     ///
-    /// [`Tag`]: ./tag/tag.html
-    pub fn is_group(&self, tag: &Tag) -> bool {
-        self.tags.contains(tag) && self.specs.get(tag).is_none()
+    /// This finds the same errors as [`check_tags_lazy`], but returns them in the canonical
+    /// order described in [`sort_errors`], so the result does not depend on the tagset's order
+    /// of checking. An empty list means the tagset is valid.
+    ///
+    /// [`check_tags_lazy`]: #method.check_tags_lazy
+    /// [`sort_errors`]: ./fn.sort_errors.html
+    pub fn check_tags_all(&self, tags: &[Tag]) -> Vec<Error> {
+        let mut errors = self.check_tags_lazy(tags).collect::<Vec<_>>();
+        sort_errors(&mut errors);
+        errors
     }
 
-    /// Determines if a [`Role`] with the given name is registered.
+    /// Validates the given list of tags, producing errors lazily as the iterator is advanced.
     ///
-    /// [`Role`]: ./tag/role.html
-    pub fn has_role<B: Borrow<str>>(&self, name: B) -> bool {
-        let name = name.borrow();
+    /// Each tag is checked in order, yielding at most one error per tag, followed by
+    /// any violated exclusion sets or group cardinalities. Unlike [`check_tags`],
+    /// this continues past the first error, but only does as much work as is consumed.
+    ///
+    /// Since most rules inspect the whole tagset, an unregistered tag is reported once
+    /// for nearly every tag. Use [`make_tagset`] to rule these out beforehand.
+    ///
+    /// [`check_tags`]: #method.check_tags
+    /// [`make_tagset`]: #method.make_tagset
+    pub fn check_tags_lazy<'a>(&'a self, tags: &'a [Tag]) -> impl Iterator<Item = Error> + 'a {
+        let flags = CheckFlags::all();
+        let tag_errors = tags.iter().filter_map(move |tag| {
+            let result = self.get_tagset_spec(tag).and_then(|spec| {
+                spec.check_tag_changes_with_flags(self, tags, &[], &[], &[], flags)
+            });
 
-        self.roles.get(name).is_some()
+            result.err()
+        });
+
+        let set_errors = iter::once_with(move || self.check_exclusions(tags.iter()).err())
+            .chain(iter::once_with(move || {
+                self.check_cardinalities(tags.iter()).err()
+            }))
+            .flatten();
+
+        tag_errors.chain(set_errors)
     }
 
-    /// Gets the [`Role`] with the given name.
+    /// Validates the given list of tags, where some requirements may be met by tags managed elsewhere.
     ///
-    /// [`Role`]: ./tag/role.html
-    pub fn get_role<B: Borrow<str>>(&self, name: B) -> Result<Role> {
-        let name = name.borrow();
+    /// A requirement is also satisfied if the required tag or group itself is in `external_present`.
+    /// Such tags are not otherwise considered, so they cannot conflict with anything,
+    /// nor do they count as members of any group here.
+    pub fn check_tags_with_external(
+        &self,
+        tags: &[Tag],
+        external_present: &HashSet<Tag>,
+    ) -> Result<()> {
+        let result = self.check_tags_inner(tags, CheckFlags::all(), Some(external_present));
 
-        match self.roles.get(name) {
-            Some(role) => Ok(Role::clone(role)),
-            None => Err(Error::NoSuchRole(str!(name))),
+        #[cfg(feature = "log")]
+        log_failure(tags, &result);
+
+        result
+    }
+
+    fn check_tags_inner(
+        &self,
+        tags: &[Tag],
+        flags: CheckFlags,
+        external: Option<&HashSet<Tag>>,
+    ) -> Result<()> {
+        for tag in tags {
+            let spec = self.get_tagset_spec(&tag)?;
+            spec.check_tag_changes_external(self, tags, &[], &[], &[], flags, external)?;
         }
+
+        self.check_set_rules(tags, flags)
     }
 
-    /// Count the number of tags in the list that are in the given group.
-    /// For tags this will return 0 or 1.
-    pub fn count_tag(&self, check: &Tag, tags: &[Tag]) -> Result<usize> {
-        let mut count = 0;
+    // Checks the rules which apply to the tagset as a whole, rather than any one tag.
+    pub(crate) fn check_set_rules(&self, tags: &[Tag], flags: CheckFlags) -> Result<()> {
+        if flags.contains(CheckFlags::CONFLICTS) {
+            self.check_exclusions(tags.iter())?;
+        }
 
-        for tag in tags {
-            if tag == check || self.get_spec(tag)?.groups.contains(check) {
-                count += 1;
-            }
+        if flags.contains(CheckFlags::CARDINALITY) {
+            self.check_cardinalities(tags.iter())?;
         }
 
-        Ok(count)
+        Ok(())
     }
 
-    /// Determines if the given tag/group is present in the list.
-    pub fn check_tag(&self, check: &Tag, tags: &[Tag]) -> Result<bool> {
-        if self.is_group(check) {
-            self.count_tag(check, tags).map(|count| count > 0)
-        } else {
-            Ok(tags.contains(check))
+    fn check_exclusions<'a, I>(&self, tags: I) -> Result<()>
+    where
+        I: Iterator<Item = &'a Tag> + Clone,
+    {
+        // Synonyms are mutually exclusive as well
+        for set in self.exclusion_sets.iter().chain(&self.synonym_groups) {
+            let mut present = tags.clone().filter(|tag| set.contains(tag));
+
+            if let (Some(first), Some(second)) = (present.next(), present.next()) {
+                let (first, second) = (Tag::clone(first), Tag::clone(second));
+                return Err(Error::IncompatibleTags(first, second));
+            }
         }
+
+        Ok(())
     }
 
-    /// Validates the given list of tags against the engine's tag policies.
-    pub fn check_tags(&self, tags: &[Tag]) -> Result<()> {
-        for tag in tags {
-            let spec = self.get_spec(&tag)?;
-            spec.check_tags(self, tags)?;
+    fn check_cardinalities<'a, I>(&self, tags: I) -> Result<()>
+    where
+        I: Iterator<Item = &'a Tag> + Clone,
+    {
+        if self.cardinalities.is_empty() {
+            return Ok(());
+        }
+
+        let mut groups = self.cardinalities.iter().collect::<Vec<_>>();
+        groups.sort_by_key(|&(group, _)| group);
+
+        for (group, cardinality) in groups {
+            let mut members = Vec::new();
+            for tag in tags.clone() {
+                if self.count_tag(group, slice::from_ref(tag))? > 0 {
+                    members.push(Tag::clone(tag));
+                }
+            }
+
+            if !cardinality.allows(members.len()) {
+                let group = Tag::clone(group);
+                return Err(Error::GroupCardinality(group, *cardinality, members));
+            }
         }
 
         Ok(())
     }
 
     /// Validates the given list of tag changes against the engine's tag policies.
+    ///
+    /// The passed roles are expanded as per [`effective_roles`].
+    ///
+    /// [`effective_roles`]: #method.effective_roles
+    #[inline]
     pub fn check_tag_changes(
         &self,
         tags: &[Tag],
@@ -221,6 +2031,92 @@ impl Engine {
         removed_tags: &[Tag],
         roles: &[Role],
     ) -> Result<()> {
+        let flags = CheckFlags::all();
+        self.check_tag_changes_with_flags(tags, added_tags, removed_tags, roles, flags)
+    }
+
+    /// Validates the given list of tag changes, only enforcing the kinds of rules selected in `flags`.
+    pub fn check_tag_changes_with_flags(
+        &self,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+        flags: CheckFlags,
+    ) -> Result<()> {
+        let specs = tags
+            .iter()
+            .chain(added_tags)
+            .map(|tag| self.get_tagset_spec(tag));
+        let result =
+            self.check_tag_changes_inner(tags, added_tags, removed_tags, roles, flags, specs);
+
+        #[cfg(feature = "log")]
+        log_failure(tags, &result);
+
+        result
+    }
+
+    /// Validates the given change like [`check_tag_changes`], then applies it.
+    ///
+    /// On success, returns the resulting tagset: `tags` without `removed_tags`, followed by
+    /// any of `added_tags` not already present. Then, each added tag's callbacks registered
+    /// with [`on_tag_applied`] are run, in the order the tags were added.
+    /// If validation fails, no callbacks are run.
+    ///
+    /// Tags listed in both `added_tags` and `removed_tags` cancel out first,
+    /// as with [`normalize_change`], so they are neither added nor removed.
+    ///
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    /// [`on_tag_applied`]: #method.on_tag_applied
+    /// [`normalize_change`]: #method.normalize_change
+    pub fn commit_changes(
+        &self,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+    ) -> Result<Vec<Tag>> {
+        let (added_tags, removed_tags) = self.normalize_change(added_tags, removed_tags);
+        self.check_tag_changes(tags, &added_tags, &removed_tags, roles)?;
+
+        let mut result = tags
+            .iter()
+            .filter(|tag| !removed_tags.contains(tag))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for tag in &added_tags {
+            if !result.contains(tag) {
+                result.push(Tag::clone(tag));
+            }
+        }
+
+        for tag in &added_tags {
+            if let Some(hooks) = self.hooks.get(tag) {
+                for hook in hooks {
+                    (hook.0)(&result);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // The specifications of `tags` followed by `added_tags` are given by `specs`,
+    // so callers with a TagSet don't need to look them up again.
+    fn check_tag_changes_inner<'s, I>(
+        &self,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+        flags: CheckFlags,
+        specs: I,
+    ) -> Result<()>
+    where
+        I: Iterator<Item = Result<&'s TagSpec>>,
+    {
         // Check for unregistered roles
         for role in roles {
             if !self.roles.contains(role) {
@@ -238,11 +2134,120 @@ impl Engine {
             }
         }
 
-        for tag in tags {
-            let spec = self.get_spec(&tag)?;
-            spec.check_tag_changes(self, tags, added_tags, removed_tags, roles)?;
+        // Disabled tags may remain, but cannot be newly applied
+        if !self.disabled_tags.is_empty() {
+            for tag in added_tags {
+                if self.disabled_tags.contains(tag) {
+                    return Err(Error::TagDisabled(Tag::clone(tag)));
+                }
+            }
+        }
+
+        // Only expand roles when needed, to avoid allocating
+        let effective;
+        let unexpanded = self.default_roles.is_empty()
+            && self.inherited_roles.is_empty()
+            && self.role_sets.is_empty();
+
+        let roles = if unexpanded {
+            roles
+        } else {
+            effective = self.effective_roles(roles).into_iter().collect::<Vec<_>>();
+            &effective
+        };
+
+        // Computed tags cannot be changed directly, regardless of roles
+        if flags.contains(CheckFlags::ROLES) && !self.computed_tags.is_empty() {
+            for tag in added_tags.iter().chain(removed_tags) {
+                if self.is_computed(tag) {
+                    return Err(Error::ComputedTag(Tag::clone(tag)));
+                }
+            }
+        }
+
+        // Removed tags only have their roles checked, new tags are checked in full
+        for spec in specs {
+            spec?.check_tag_changes_with_flags(
+                self,
+                tags,
+                added_tags,
+                removed_tags,
+                roles,
+                flags,
+            )?;
+        }
+
+        let present = tags
+            .iter()
+            .filter(|tag| !removed_tags.contains(tag))
+            .chain(added_tags);
+
+        if flags.contains(CheckFlags::CONFLICTS) {
+            self.check_exclusions(present.clone())?;
+        }
+
+        if flags.contains(CheckFlags::CARDINALITY) {
+            self.check_cardinalities(present)?;
         }
 
         Ok(())
     }
 }
+
+#[cfg(feature = "log")]
+fn log_failure(tags: &[Tag], result: &Result<()>) {
+    if let Err(ref error) = *result {
+        warn!(
+            target: "tag_guard",
+            code = error.code(),
+            tag:? = error.tag(),
+            tags:? = tags,
+            error:? = error;
+            "Tag check failed",
+        );
+    }
+}
+
+/// An opaque copy of an [`Engine`]'s state.
+///
+/// Created by [`Engine::snapshot`] and consumed by [`Engine::restore`].
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`Engine::snapshot`]: ./struct.Engine.html#method.snapshot
+/// [`Engine::restore`]: ./struct.Engine.html#method.restore
+#[derive(Debug, Clone)]
+pub struct EngineSnapshot(Engine);
+
+type Rule = dyn Fn(&[Tag]) -> bool;
+
+// The rule for a computed tag, shared between clones of the engine.
+#[derive(Clone)]
+struct ComputedRule(Arc<Rule>);
+
+impl Debug for ComputedRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ComputedRule")
+    }
+}
+
+type Hook = dyn Fn(&[Tag]);
+
+// A callback run when a tag is applied, shared between clones of the engine.
+#[derive(Clone)]
+struct AppliedHook(Arc<Hook>);
+
+impl Debug for AppliedHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AppliedHook")
+    }
+}
+
+// The external source of tags, shared between clones of the engine.
+#[derive(Clone)]
+struct Resolver(Arc<dyn TagResolver>);
+
+impl Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Resolver")
+    }
+}
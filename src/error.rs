@@ -10,25 +10,72 @@
  * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
  */
 
-use super::{Role, Tag};
+use super::{Cardinality, LintWarning, Role, Tag};
 use std::error::Error as StdError;
 use std::fmt::{self, Display};
+use std::io;
+use std::sync::Arc;
 
 /// An enum to represent various tagging errors.
+///
+/// Errors compare equal if they are the same variant with equal data.
+/// [`Io`] errors only compare their [`kind`], as the underlying error has no equality.
+///
+/// [`Io`]: #variant.Io
+/// [`kind`]: https://doc.rust-lang.org/stable/std/io/struct.Error.html#method.kind
 #[must_use = "should handle errors"]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug)]
 pub enum Error {
     /// The tag cannot be applied unless the others are also present.
     RequiresTags(Tag, Vec<Tag>),
 
+    /// The tag requires a member of the given group, but none are present.
+    ///
+    /// Lists the tags in the group, any of which would satisfy the requirement.
+    RequiresOneOf(Tag, Tag, Vec<Tag>),
+
+    /// The tag requires a member from each of several groups, but the given group has none present.
+    RequiresGroupMember(Tag, Tag),
+
+    /// The tag requires at least one of the listed tags or groups, but none are present.
+    RequiresAnyOf(Tag, Vec<Tag>),
+
+    /// The tag group has too many or too few members present for its [`Cardinality`].
+    ///
+    /// Lists the members which were present.
+    ///
+    /// [`Cardinality`]: ./struct.Cardinality.html
+    GroupCardinality(Tag, Cardinality, Vec<Tag>),
+
     /// The two tags cannot be applied together, as they conflict.
     IncompatibleTags(Tag, Tag),
 
+    /// The first two tags cannot be applied together, as the first conflicts
+    /// with a group they are both members of, the third tag.
+    IncompatibleViaGroup(Tag, Tag, Tag),
+
     /// The given tag is not registered in the [`Engine`].
     ///
     /// [`Engine`]: ./struct.Engine.html
     MissingTag(Tag),
 
+    /// The given tag is not a tag group.
+    NotGroup(Tag),
+
+    /// The given tag has been disabled, and cannot be newly applied.
+    TagDisabled(Tag),
+
+    /// The given tag appears more than once in a tagset.
+    DuplicateTag(Tag),
+
+    /// The given group appeared in a tagset, but is not also a proper tag.
+    ///
+    /// Groups are only satisfied by their members, so they cannot be applied directly.
+    GroupAsTag(Tag),
+
+    /// The given tag is computed from the rest of the tagset, so it cannot be added or removed.
+    ComputedTag(Tag),
+
     /// The given tag name could not be found.
     NoSuchTag(String),
 
@@ -38,11 +85,44 @@ pub enum Error {
     MissingRole(Role),
 
     /// Unable to perform this operation due to lacking necessary access role.
-    MissingRoles(Vec<Role>),
+    ///
+    /// The list is shared with the tag's specification, so constructing this error is cheap.
+    MissingRoles(Arc<[Role]>),
 
     /// The given role name could not be found.
     NoSuchRole(String),
 
+    /// The role cannot be deleted, as the listed tags need it.
+    RoleInUse(Role, Vec<Tag>),
+
+    /// The given name does not comply with the [`Engine`]'s [`NamePolicy`].
+    ///
+    /// [`Engine`]: ./struct.Engine.html
+    /// [`NamePolicy`]: ./struct.NamePolicy.html
+    InvalidName(String),
+
+    /// The given name is already in use by a tag or group, and cannot be reused.
+    NameCollision(String),
+
+    /// The configuration has a structural problem, as found by [`Engine::validate`].
+    ///
+    /// [`Engine::validate`]: ./struct.Engine.html#method.validate
+    InvalidConfig(LintWarning),
+
+    /// The named tag's requirement expression, the second field, could not be parsed.
+    ///
+    /// The third field describes the problem.
+    InvalidRequirement(String, String, &'static str),
+
+    /// Unable to read a configuration file.
+    Io(io::Error),
+
+    /// Unable to parse a configuration file, with the parser's message.
+    ///
+    /// The second field is the parser's own error, if there is one, which is
+    /// also available as `source`. It is not compared for equality.
+    Parse(String, Option<Box<dyn StdError>>),
+
     /// For uncommon error cases.
     /// These should not occur assuming a properly-configured [`Engine`].
     ///
@@ -50,24 +130,106 @@ pub enum Error {
     Other(&'static str),
 }
 
+impl Error {
+    /// Returns a short, stable identifier for this kind of error.
+    ///
+    /// Suitable for logging or matching against in other systems.
+    pub fn code(&self) -> &'static str {
+        use self::Error::*;
+
+        match *self {
+            RequiresTags(_, _) => "requires_tags",
+            RequiresOneOf(_, _, _) => "requires_one_of",
+            RequiresGroupMember(_, _) => "requires_group_member",
+            RequiresAnyOf(_, _) => "requires_any_of",
+            GroupCardinality(_, _, _) => "group_cardinality",
+            IncompatibleTags(_, _) => "incompatible_tags",
+            IncompatibleViaGroup(_, _, _) => "incompatible_via_group",
+            MissingTag(_) => "missing_tag",
+            NotGroup(_) => "not_group",
+            TagDisabled(_) => "tag_disabled",
+            DuplicateTag(_) => "duplicate_tag",
+            GroupAsTag(_) => "group_as_tag",
+            ComputedTag(_) => "computed_tag",
+            NoSuchTag(_) => "no_such_tag",
+            MissingRole(_) => "missing_role",
+            MissingRoles(_) => "missing_roles",
+            NoSuchRole(_) => "no_such_role",
+            RoleInUse(_, _) => "role_in_use",
+            InvalidName(_) => "invalid_name",
+            NameCollision(_) => "name_collision",
+            InvalidConfig(_) => "invalid_config",
+            InvalidRequirement(_, _, _) => "invalid_requirement",
+            Io(_) => "io",
+            Parse(_, _) => "parse",
+            Other(_) => "other",
+        }
+    }
+
+    /// Returns the [`Tag`] which caused this error, if there is one.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub fn tag(&self) -> Option<&Tag> {
+        use self::Error::*;
+
+        match *self {
+            RequiresTags(ref tag, _) => Some(tag),
+            RequiresOneOf(ref tag, _, _) => Some(tag),
+            RequiresGroupMember(ref tag, _) => Some(tag),
+            RequiresAnyOf(ref tag, _) => Some(tag),
+            GroupCardinality(ref group, _, _) => Some(group),
+            IncompatibleTags(ref tag, _) => Some(tag),
+            IncompatibleViaGroup(ref tag, _, _) => Some(tag),
+            MissingTag(ref tag) => Some(tag),
+            NotGroup(ref tag) => Some(tag),
+            TagDisabled(ref tag) => Some(tag),
+            DuplicateTag(ref tag) => Some(tag),
+            GroupAsTag(ref tag) => Some(tag),
+            ComputedTag(ref tag) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
 impl StdError for Error {
     fn description(&self) -> &str {
         use self::Error::*;
 
         match *self {
             RequiresTags(_, _) => "Tag missing requirements",
+            RequiresOneOf(_, _, _) => "Tag missing requirements",
+            RequiresGroupMember(_, _) => "Tag missing requirements",
+            RequiresAnyOf(_, _) => "Tag missing requirements",
+            GroupCardinality(_, _, _) => "Wrong number of group members",
             IncompatibleTags(_, _) => "Tags conflict",
+            IncompatibleViaGroup(_, _, _) => "Tags conflict",
             MissingTag(_) => "Tag not found in Engine",
+            NotGroup(_) => "Tag is not a group",
+            TagDisabled(_) => "Tag is disabled",
+            DuplicateTag(_) => "Tag listed more than once",
+            GroupAsTag(_) => "Group cannot be used as a tag",
+            ComputedTag(_) => "Computed tag cannot be changed",
             NoSuchTag(_) => "No tag with that name",
             MissingRole(_) => "Role not found in Engine",
             MissingRoles(_) => "Cannot apply tags without roles",
             NoSuchRole(_) => "No role with that name",
+            RoleInUse(_, _) => "Role still needed by tags",
+            InvalidName(_) => "Name not permitted by policy",
+            NameCollision(_) => "Name already in use",
+            InvalidConfig(_) => "Invalid configuration",
+            InvalidRequirement(_, _, _) => "Invalid requirement expression",
+            Io(_) => "Unable to read configuration",
+            Parse(_, _) => "Unable to parse configuration",
             Other(msg) => msg,
         }
     }
 
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
-        None
+        match *self {
+            Error::Io(ref error) => Some(error),
+            Error::Parse(_, Some(ref error)) => Some(error.as_ref()),
+            _ => None,
+        }
     }
 }
 
@@ -80,28 +242,183 @@ impl Display for Error {
         match *self {
             RequiresTags(ref tag, ref needed) => {
                 write!(f, "{} needs ", tag)?;
-                write_items(f, needed)?;
+                write_items(f, needed, "and")?;
+                Ok(())
+            }
+            RequiresOneOf(ref tag, ref group, ref members) => {
+                write!(f, "{} needs one of ", tag)?;
+                write_items(f, members, "or")?;
+                write!(f, " (from {})", group)?;
+                Ok(())
+            }
+            RequiresGroupMember(ref tag, ref group) => {
+                write!(f, "{} needs a member of {}", tag, group)
+            }
+            RequiresAnyOf(ref tag, ref alternatives) => {
+                write!(f, "{} needs one of ", tag)?;
+                write_items(f, alternatives, "or")?;
                 Ok(())
             }
+            MissingRoles(ref roles) if roles.is_empty() => {
+                write!(f, "this tag cannot be applied by anyone")
+            }
             MissingRoles(ref roles) => {
                 write!(f, "at least one of ")?;
-                write_items(f, roles)?;
+                write_items(f, roles, "or")?;
+                Ok(())
+            }
+            GroupCardinality(ref group, cardinality, ref members) => {
+                write!(f, "group '{}' requires {}", group, cardinality)?;
+                write!(f, " but found {}", members.len())?;
+
+                if !members.is_empty() {
+                    write!(f, " (")?;
+                    write_items(f, members, "and")?;
+                    write!(f, ")")?;
+                }
+
                 Ok(())
             }
             IncompatibleTags(ref first, ref second) => write!(f, "{} and {}", first, second),
+            IncompatibleViaGroup(ref first, ref second, ref group) => write!(
+                f,
+                "{} conflicts with {} because both are in {}",
+                first, second, group,
+            ),
             MissingTag(ref tag) => write!(f, "{}", tag),
+            NotGroup(ref tag) => write!(f, "{}", tag),
+            TagDisabled(ref tag) => write!(f, "{}", tag),
+            DuplicateTag(ref tag) => write!(f, "{}", tag),
+            GroupAsTag(ref tag) => write!(f, "{}", tag),
+            ComputedTag(ref tag) => write!(f, "{}", tag),
             MissingRole(ref role) => write!(f, "{}", role),
             NoSuchTag(ref name) => write!(f, "{}", name),
             NoSuchRole(ref name) => write!(f, "{}", name),
+            RoleInUse(ref role, ref tags) => {
+                write!(f, "{} is needed by ", role)?;
+                write_items(f, tags, "and")?;
+                Ok(())
+            }
+            InvalidName(ref name) => write!(f, "{:?}", name),
+            NameCollision(ref name) => write!(f, "{}", name),
+            InvalidConfig(ref warning) => write!(f, "{}", warning),
+            InvalidRequirement(ref tag, ref expr, reason) => {
+                write!(f, "{:?} for {} ({})", expr, tag, reason)
+            }
+            Io(ref error) => write!(f, "{}", error),
+            Parse(ref message, _) => write!(f, "{}", message),
             Other(_) => Ok(()),
         }
     }
 }
 
-fn write_items<D: Display>(f: &mut fmt::Formatter, items: &[D]) -> fmt::Result {
-    for (i, item) in items.iter().enumerate() {
-        let comma = if i < items.len() - 1 { ", " } else { "" };
-        write!(f, "{}{}", item, comma)?;
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use self::Error::*;
+
+        match (self, other) {
+            (RequiresTags(a, b), RequiresTags(c, d)) => a == c && b == d,
+            (RequiresOneOf(a, b, c), RequiresOneOf(d, e, f)) => a == d && b == e && c == f,
+            (RequiresGroupMember(a, b), RequiresGroupMember(c, d)) => a == c && b == d,
+            (RequiresAnyOf(a, b), RequiresAnyOf(c, d)) => a == c && b == d,
+            (GroupCardinality(a, b, c), GroupCardinality(d, e, f)) => a == d && b == e && c == f,
+            (IncompatibleTags(a, b), IncompatibleTags(c, d)) => a == c && b == d,
+            (IncompatibleViaGroup(a, b, c), IncompatibleViaGroup(d, e, f)) => {
+                a == d && b == e && c == f
+            }
+            (MissingTag(a), MissingTag(b)) => a == b,
+            (NotGroup(a), NotGroup(b)) => a == b,
+            (TagDisabled(a), TagDisabled(b)) => a == b,
+            (DuplicateTag(a), DuplicateTag(b)) => a == b,
+            (GroupAsTag(a), GroupAsTag(b)) => a == b,
+            (ComputedTag(a), ComputedTag(b)) => a == b,
+            (NoSuchTag(a), NoSuchTag(b)) => a == b,
+            (MissingRole(a), MissingRole(b)) => a == b,
+            (MissingRoles(a), MissingRoles(b)) => a == b,
+            (NoSuchRole(a), NoSuchRole(b)) => a == b,
+            (RoleInUse(a, b), RoleInUse(c, d)) => a == c && b == d,
+            (InvalidName(a), InvalidName(b)) => a == b,
+            (NameCollision(a), NameCollision(b)) => a == b,
+            (InvalidConfig(a), InvalidConfig(b)) => a == b,
+            (InvalidRequirement(a, b, c), InvalidRequirement(d, e, f)) => {
+                a == d && b == e && c == f
+            }
+            (Io(a), Io(b)) => a.kind() == b.kind(),
+            (Parse(a, _), Parse(b, _)) => a == b,
+            (Other(a), Other(b)) => a == b,
+            // Listed individually so new variants can't be forgotten
+            (RequiresTags(..), _)
+            | (RequiresOneOf(..), _)
+            | (RequiresGroupMember(..), _)
+            | (RequiresAnyOf(..), _)
+            | (GroupCardinality(..), _)
+            | (IncompatibleTags(..), _)
+            | (IncompatibleViaGroup(..), _)
+            | (MissingTag(..), _)
+            | (NotGroup(..), _)
+            | (TagDisabled(..), _)
+            | (DuplicateTag(..), _)
+            | (GroupAsTag(..), _)
+            | (ComputedTag(..), _)
+            | (NoSuchTag(..), _)
+            | (MissingRole(..), _)
+            | (MissingRoles(..), _)
+            | (NoSuchRole(..), _)
+            | (RoleInUse(..), _)
+            | (InvalidName(..), _)
+            | (NameCollision(..), _)
+            | (InvalidConfig(..), _)
+            | (InvalidRequirement(..), _)
+            | (Io(..), _)
+            | (Parse(..), _)
+            | (Other(..), _) => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// Sorts errors into the canonical order used by every method returning several errors.
+///
+/// Errors are ordered by the name of the [`Tag`] which caused them, with errors not caused
+/// by any tag first, then by [`code`], then by their message. Since this only depends on
+/// the errors themselves, the same errors are always sorted the same way, regardless of
+/// the order they were found in.
+///
+/// [`Tag`]: ./struct.Tag.html
+/// [`code`]: ./enum.Error.html#method.code
+pub fn sort_errors(errors: &mut [Error]) {
+    errors.sort_by_cached_key(|error| (error.tag().cloned(), error.code(), error.to_string()));
+}
+
+// Writes a list of items in prose, such as "a, b, and c" with a conjunction of "and".
+// An empty list is written as "nothing".
+fn write_items<D: Display>(f: &mut fmt::Formatter, items: &[D], conjunction: &str) -> fmt::Result {
+    if items.is_empty() {
+        return write!(f, "nothing");
+    }
+
+    // Only longer lists separate the final item with a comma
+    let last = if items.len() > 2 { ", " } else { " " };
+    let mut items = items.iter().peekable();
+
+    if let Some(item) = items.next() {
+        write!(f, "{}", item)?;
+    }
+
+    while let Some(item) = items.next() {
+        if items.peek().is_some() {
+            write!(f, ", {}", item)?;
+        } else {
+            write!(f, "{}{} {}", last, conjunction, item)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,37 @@
+/*
+ * expiry.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+
+impl Engine {
+    /// Gets every tag whose [`expires_at`] is at or before the given time, sorted.
+    ///
+    /// Expired tags are still checked as usual, this is only intended to help
+    /// find stale tags which should be removed or locked.
+    ///
+    /// [`expires_at`]: ./struct.TagSpec.html#structfield.expires_at
+    pub fn expired_tags(&self, now: DateTime<Utc>) -> Vec<Tag> {
+        let mut tags = self
+            .get_specs()
+            .iter()
+            .filter(|(_, spec)| match spec.expires_at {
+                Some(expires_at) => expires_at <= now,
+                None => false,
+            })
+            .map(|(tag, _)| Tag::clone(tag))
+            .collect::<Vec<_>>();
+
+        tags.sort();
+        tags
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * explore.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::Result;
+use std::collections::{HashSet, VecDeque};
+
+// How many candidate tagsets a search may examine before giving up.
+const SEARCH_LIMIT: usize = 10_000;
+
+impl Engine {
+    /// Finds the smallest valid tagsets which include the given tag, up to `max_results` of them.
+    ///
+    /// Starting from just the tag, each unmet requirement is satisfied in every possible way,
+    /// such as by picking each member of a required group in turn. Tagsets with conflicts
+    /// are discarded, and no returned tagset contains another. Disabled and computed tags
+    /// are never added to satisfy a requirement.
+    ///
+    /// The search is breadth-first, so smaller tagsets are found first. To bound the work
+    /// done on large configurations, it stops after examining a fixed number of candidates,
+    /// so fewer than `max_results` tagsets may be returned even if more exist.
+    /// Each tagset is sorted.
+    pub fn minimal_valid_sets(&self, tag: &Tag, max_results: usize) -> Result<Vec<Vec<Tag>>> {
+        self.get_spec(tag)?;
+
+        let start = vec![Tag::clone(tag)];
+        let mut results: Vec<Vec<Tag>> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(start.clone());
+        queue.push_back(start);
+
+        let mut examined = 0;
+        while let Some(tags) = queue.pop_front() {
+            if results.len() >= max_results || examined >= SEARCH_LIMIT {
+                break;
+            }
+
+            examined += 1;
+
+            // Smaller tagsets are examined first, so any result found is minimal
+            // unless it contains an earlier one.
+            let contains = |result: &Vec<Tag>| result.iter().all(|tag| tags.contains(tag));
+            if results.iter().any(contains) {
+                continue;
+            }
+
+            let error = match self.check_tags(&tags) {
+                Ok(()) => {
+                    results.push(tags);
+                    continue;
+                }
+                Err(error) => error,
+            };
+
+            for candidate in self.satisfying_tags(&error, &tags) {
+                let mut next = tags.clone();
+                next.push(candidate);
+                next.sort();
+
+                if seen.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    // Which tags could each be added to resolve the given error.
+    // Errors which adding a tag cannot fix, such as conflicts, produce none.
+    fn satisfying_tags(&self, error: &Error, tags: &[Tag]) -> Vec<Tag> {
+        let expand = |tag: &Tag| {
+            if self.is_group(tag) {
+                self.tags_in_group(tag)
+            } else {
+                vec![Tag::clone(tag)]
+            }
+        };
+
+        let candidates = match error {
+            // Every missing tag is needed, so adding one at a time suffices
+            Error::RequiresTags(_, required) => required
+                .iter()
+                .filter(|tag| !self.is_group(tag) && !tags.contains(tag))
+                .take(1)
+                .cloned()
+                .collect(),
+            Error::RequiresOneOf(_, _, members) => members.clone(),
+            Error::RequiresGroupMember(_, group) => self.tags_in_group(group),
+            Error::RequiresAnyOf(_, alternatives) => alternatives.iter().flat_map(expand).collect(),
+            Error::GroupCardinality(group, cardinality, present)
+                if present.len() < cardinality.min =>
+            {
+                self.tags_in_group(group)
+            }
+            _ => Vec::new(),
+        };
+
+        candidates
+            .into_iter()
+            .filter(|tag| {
+                self.get_specs().contains_key(tag)
+                    && !tags.contains(tag)
+                    && !self.is_computed(tag)
+                    && self.is_tag_enabled(tag)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+/*
+ * flags.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+bitflags! {
+    /// Selects which kinds of rules are enforced when checking tags.
+    ///
+    /// Used with [`Engine::check_tags_with_flags`] to only enforce part of the
+    /// configured policy, for instance when phasing in new rules.
+    ///
+    /// [`Engine::check_tags_with_flags`]: ./struct.Engine.html#method.check_tags_with_flags
+    #[derive(Default)]
+    pub struct CheckFlags: u8 {
+        /// Tags may not be present alongside the tags or groups they conflict with.
+        /// This includes a group the tag is a member of, in which case it must be
+        /// the only member of that group present.
+        const CONFLICTS = 0b0001;
+
+        /// Tags may not be present without the tags or groups they require.
+        const REQUIREMENTS = 0b0010;
+
+        /// Users may not add or remove tags without one of the needed roles.
+        const ROLES = 0b0100;
+
+        /// Tag groups may not have more or fewer members present than their cardinality allows.
+        const CARDINALITY = 0b1000;
+    }
+}
@@ -0,0 +1,371 @@
+/*
+ * graph.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::{LintWarning, Result};
+use std::collections::HashMap;
+use std::slice;
+
+/// All of the relationships between tags and groups in an [`Engine`], as data.
+///
+/// Each edge is a pair of `(tag, other)`, where `tag` is the proper tag
+/// whose specification declares the relationship. All lists are sorted.
+///
+/// [`Engine`]: ./struct.Engine.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Edges {
+    /// The tag requires the other tag or group to be present.
+    ///
+    /// This includes required groups, and each of the tag's alternative requirements.
+    pub requires: Vec<(Tag, Tag)>,
+
+    /// The tag conflicts with the other tag or group.
+    pub conflicts: Vec<(Tag, Tag)>,
+
+    /// The tag is a member of the group.
+    pub memberships: Vec<(Tag, Tag)>,
+}
+
+impl Engine {
+    /// Gets every requirement, conflict, and group membership as a list of edges.
+    pub fn edges(&self) -> Edges {
+        let mut edges = Edges::default();
+
+        for (tag, spec) in self.specs_sorted() {
+            let pairs = |others: &[Tag]| {
+                others
+                    .iter()
+                    .map(|other| (Tag::clone(tag), Tag::clone(other)))
+                    .collect::<Vec<_>>()
+            };
+
+            edges.requires.extend(pairs(&spec.required_tags));
+            edges.requires.extend(pairs(&spec.required_groups));
+            edges.conflicts.extend(pairs(&spec.conflicting_tags));
+            edges.memberships.extend(pairs(&spec.groups));
+
+            for alternatives in &spec.required_any_of {
+                edges.requires.extend(pairs(alternatives));
+            }
+        }
+
+        edges.requires.sort();
+        edges.requires.dedup();
+        edges.conflicts.sort();
+        edges.memberships.sort();
+        edges
+    }
+
+    /// Finds pairs of tags which each directly require the other.
+    ///
+    /// Such tags can only be applied together, so selecting one should select the other.
+    /// Each pair is listed once, with the lesser tag first, and the list is sorted.
+    pub fn mutual_requirements(&self) -> Vec<(Tag, Tag)> {
+        let mut pairs = Vec::new();
+
+        for (tag, spec) in self.get_specs() {
+            for required in &spec.required_tags {
+                if tag >= required {
+                    continue;
+                }
+
+                if let Some(other) = self.get_specs().get(required) {
+                    if other.required_tags.contains(tag) {
+                        pairs.push((Tag::clone(tag), Tag::clone(required)));
+                    }
+                }
+            }
+        }
+
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Determines whether the tag requires the target, either directly or transitively.
+    ///
+    /// Required groups are expanded to their members, so a tag requiring a group is
+    /// considered to require anything one of its members requires. Cycles are tolerated.
+    pub fn requires_transitively(&self, tag: &Tag, target: &Tag) -> Result<bool> {
+        let spec = self.get_spec(tag)?;
+        if !self.has_tag(&**target) {
+            return Err(Error::MissingTag(Tag::clone(target)));
+        }
+
+        let mut seen = Vec::new();
+        let mut pending = spec
+            .required_tags
+            .iter()
+            .chain(&spec.required_groups)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        while let Some(current) = pending.pop() {
+            if current == *target {
+                return Ok(true);
+            }
+
+            if seen.contains(&current) {
+                continue;
+            }
+
+            if self.is_group(&current) {
+                pending.extend(self.tags_in_group(&current));
+            }
+
+            if let Some(spec) = self.get_specs().get(&current) {
+                pending.extend(spec.required_tags.iter().cloned());
+                pending.extend(spec.required_groups.iter().cloned());
+            }
+
+            seen.push(current);
+        }
+
+        Ok(false)
+    }
+
+    /// Gets the length of the longest requirement chain from the tag to one with no requirements.
+    ///
+    /// Tags without requirements have a depth of `0`. Requiring a group counts as requiring
+    /// each of its members, and alternatives are treated like any other requirement.
+    /// Fails with [`Error::InvalidConfig`] if the chain contains a cycle.
+    ///
+    /// [`Error::InvalidConfig`]: ./enum.Error.html#variant.InvalidConfig
+    pub fn tag_depth(&self, tag: &Tag) -> Result<usize> {
+        let (tag, _) = match self.get_specs().get_key_value(tag) {
+            Some(entry) => entry,
+            None => return Err(Error::MissingTag(Tag::clone(tag))),
+        };
+
+        self.tag_depth_inner(tag, &mut Vec::new(), &mut HashMap::new())
+    }
+
+    fn tag_depth_inner<'a>(
+        &'a self,
+        tag: &'a Tag,
+        path: &mut Vec<&'a Tag>,
+        depths: &mut HashMap<&'a Tag, usize>,
+    ) -> Result<usize> {
+        if let Some(&depth) = depths.get(tag) {
+            return Ok(depth);
+        }
+
+        if let Some(start) = path.iter().position(|other| *other == tag) {
+            let mut cycle = path[start..]
+                .iter()
+                .map(|&tag| Tag::clone(tag))
+                .collect::<Vec<_>>();
+
+            if let Some(lowest) = (0..cycle.len()).min_by_key(|&i| &cycle[i]) {
+                cycle.rotate_left(lowest);
+            }
+
+            return Err(Error::InvalidConfig(LintWarning::RequirementCycle(cycle)));
+        }
+
+        // Unregistered requirements cannot go any deeper
+        let spec = match self.get_specs().get(tag) {
+            Some(spec) => spec,
+            None => return Ok(0),
+        };
+
+        let required = spec
+            .required_tags
+            .iter()
+            .chain(&spec.required_groups)
+            .chain(spec.required_any_of.iter().flatten());
+
+        path.push(tag);
+        let mut depth = 0;
+
+        for required in required {
+            depth = depth.max(1);
+
+            if !self.is_group(required) {
+                depth = depth.max(self.tag_depth_inner(required, path, depths)? + 1);
+                continue;
+            }
+
+            // A tag satisfies a requirement on its own group by itself
+            for member in self.tags_in_group(required) {
+                if let Some((member, _)) = self.get_specs().get_key_value(&member) {
+                    if member != tag {
+                        depth = depth.max(self.tag_depth_inner(member, path, depths)? + 1);
+                    }
+                }
+            }
+        }
+
+        path.pop();
+        depths.insert(tag, depth);
+        Ok(depth)
+    }
+
+    /// Finds pairs of tags which can never coexist, even though neither conflicts with the other.
+    ///
+    /// This happens when one tag requires a group which the other conflicts with,
+    /// since satisfying the requirement would always introduce a conflict.
+    /// Tags which are themselves members of the group are not affected.
+    /// Each pair is listed once, with the lesser tag first, and the list is sorted.
+    ///
+    /// This compares every tag against every other, so it is `O(n²)` in the number
+    /// of tags and should not be called on a hot path.
+    pub fn find_implicit_conflicts(&self) -> Vec<(Tag, Tag)> {
+        let mut pairs = Vec::new();
+
+        for (tag, spec) in self.get_specs() {
+            let required = spec
+                .required_tags
+                .iter()
+                .chain(&spec.required_groups)
+                .filter(|group| self.is_group(group));
+
+            for group in required {
+                for (other, other_spec) in self.get_specs() {
+                    if tag == other || !other_spec.conflicting_tags.contains(group) {
+                        continue;
+                    }
+
+                    let member = self
+                        .count_tag(group, slice::from_ref(other))
+                        .map(|count| count > 0)
+                        .unwrap_or(false);
+
+                    if member || !self.can_coexist(tag, other).unwrap_or(false) {
+                        continue;
+                    }
+
+                    let pair = if tag < other {
+                        (Tag::clone(tag), Tag::clone(other))
+                    } else {
+                        (Tag::clone(other), Tag::clone(tag))
+                    };
+
+                    pairs.push(pair);
+                }
+            }
+        }
+
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Gets every tag which conflicts with the given group, sorted.
+    ///
+    /// If a member of the group is present, none of these may be added, unless
+    /// the tag is itself a member and only conflicts with the other members.
+    pub fn tags_conflicting_with_group(&self, group: &Tag) -> Vec<Tag> {
+        let mut tags = self
+            .get_specs()
+            .iter()
+            .filter(|(_, spec)| spec.conflicting_tags.contains(group))
+            .map(|(tag, _)| Tag::clone(tag))
+            .collect::<Vec<_>>();
+
+        tags.sort();
+        tags
+    }
+
+    /// Gets the groups which both tags are members of, sorted.
+    ///
+    /// Fails if either is not a proper tag.
+    pub fn common_groups(&self, a: &Tag, b: &Tag) -> Result<Vec<Tag>> {
+        let spec_a = self.get_spec(a)?;
+        let spec_b = self.get_spec(b)?;
+
+        let mut groups = spec_a
+            .groups
+            .iter()
+            .filter(|group| spec_b.groups.contains(group))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        groups.sort();
+        groups.dedup();
+        Ok(groups)
+    }
+
+    /// Gets the tags in the tagset which cannot be removed on their own.
+    ///
+    /// A tag is locked if another present tag requires it, either directly, as the
+    /// only present member of a required group, or as the only present tag satisfying
+    /// one of its alternative requirements. Removing it would leave the tagset invalid.
+    pub fn locked_tags(&self, tags: &[Tag]) -> Result<Vec<Tag>> {
+        let mut locked = Vec::new();
+
+        for tag in tags {
+            'dependents: for other in tags.iter().filter(|other| *other != tag) {
+                let spec = self.get_spec(other)?;
+                let requirements = spec
+                    .required_tags
+                    .iter()
+                    .chain(&spec.required_groups)
+                    .map(slice::from_ref)
+                    .chain(spec.required_any_of.iter().map(Vec::as_slice));
+
+                for alternatives in requirements {
+                    let (mut provided, mut total) = (0, 0);
+
+                    for required in alternatives {
+                        provided += self.count_tag(required, slice::from_ref(tag))?;
+                        total += self.count_tag(required, tags)?;
+                    }
+
+                    if provided > 0 && provided == total {
+                        locked.push(Tag::clone(tag));
+                        break 'dependents;
+                    }
+                }
+            }
+        }
+
+        Ok(locked)
+    }
+
+    /// Determines whether two tags may be present on the same object.
+    ///
+    /// Returns `false` if either tag conflicts with the other, or with a group containing it.
+    /// This includes two members of a group which conflicts with its own members,
+    /// and two members of an [exclusion set] or [synonym group].
+    ///
+    /// [exclusion set]: #method.add_exclusion_set
+    /// [synonym group]: #method.add_synonym_group
+    pub fn can_coexist(&self, a: &Tag, b: &Tag) -> Result<bool> {
+        let spec_a = self.get_spec(a)?;
+        let spec_b = self.get_spec(b)?;
+
+        if a == b {
+            return Ok(true);
+        }
+
+        // Synonyms are mutually exclusive as well
+        let mut sets = self
+            .get_exclusion_sets()
+            .iter()
+            .chain(self.get_synonym_groups());
+
+        if sets.any(|set| set.contains(a) && set.contains(b)) {
+            return Ok(false);
+        }
+
+        for (spec, other) in &[(spec_a, b), (spec_b, a)] {
+            for conflicts in &spec.conflicting_tags {
+                if self.count_tag(conflicts, slice::from_ref(other))? > 0 {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
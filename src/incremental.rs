@@ -0,0 +1,134 @@
+/*
+ * incremental.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::{CheckFlags, Result};
+use std::collections::HashMap;
+use std::slice;
+
+/// Validates a tagset as it is edited, only re-checking the tags each edit could affect.
+///
+/// Created by [`Engine::incremental_checker`]. The result of each check is always the same
+/// as calling [`Engine::check_tags`] on the current tagset. Since the checker borrows the
+/// `Engine`, the configuration cannot change while it is in use.
+///
+/// [`Engine::incremental_checker`]: ./struct.Engine.html#method.incremental_checker
+/// [`Engine::check_tags`]: ./struct.Engine.html#method.check_tags
+#[derive(Debug, Clone)]
+pub struct IncrementalChecker<'a> {
+    engine: &'a Engine,
+    tags: Vec<Tag>,
+
+    // Whether each present tag's own rules passed when last evaluated
+    valid: HashMap<Tag, bool>,
+}
+
+impl<'a> IncrementalChecker<'a> {
+    /// Gets the current tagset.
+    #[inline]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Applies an edit to the tagset, then validates the result.
+    ///
+    /// Tags in `removed_tags` are removed, then tags in `added_tags` are appended
+    /// unless already present. Only tags whose rules refer to a changed tag,
+    /// directly or through a group, are re-evaluated.
+    pub fn update(&mut self, added_tags: &[Tag], removed_tags: &[Tag]) -> Result<()> {
+        self.tags.retain(|tag| !removed_tags.contains(tag));
+
+        for tag in added_tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(Tag::clone(tag));
+            }
+        }
+
+        let tags = &self.tags;
+        self.valid.retain(|tag, _| tags.contains(tag));
+
+        // Unregistered tags make the rules of every other tag fail
+        let changed = added_tags.iter().chain(removed_tags).collect::<Vec<_>>();
+        let everything = changed.iter().any(|tag| self.engine.get_spec(tag).is_err());
+
+        for tag in &self.tags {
+            let stale = everything || !self.valid.contains_key(tag) || self.affected(tag, &changed);
+
+            if stale {
+                let valid = self.evaluate(tag).is_ok();
+                self.valid.insert(Tag::clone(tag), valid);
+            }
+        }
+
+        self.check()
+    }
+
+    /// Validates the current tagset, using the cached results.
+    pub fn check(&self) -> Result<()> {
+        for tag in &self.tags {
+            // Only failures need to be re-evaluated, to produce the error
+            if !self.valid[tag] {
+                self.evaluate(tag)?;
+            }
+        }
+
+        self.engine.check_set_rules(&self.tags, CheckFlags::all())
+    }
+
+    fn evaluate(&self, tag: &Tag) -> Result<()> {
+        let spec = self.engine.get_tagset_spec(tag)?;
+        spec.check_tags(self.engine, &self.tags)
+    }
+
+    // Whether a change to any of the given tags could change the result for this tag.
+    fn affected(&self, tag: &Tag, changed: &[&Tag]) -> bool {
+        let spec = match self.engine.get_spec(tag) {
+            Ok(spec) => spec,
+            Err(_) => return true,
+        };
+
+        let mut references = spec
+            .required_tags
+            .iter()
+            .chain(&spec.required_groups)
+            .chain(spec.required_any_of.iter().flatten())
+            .chain(&spec.conflicting_tags);
+
+        references.any(|reference| {
+            // Computed tags depend on the whole tagset
+            self.engine.is_computed(reference)
+                || changed.iter().any(|changed| {
+                    self.engine
+                        .count_tag_lenient(reference, slice::from_ref(changed))
+                        > 0
+                })
+        })
+    }
+}
+
+impl Engine {
+    /// Starts validating the given tagset incrementally, as it is edited.
+    ///
+    /// Duplicate tags are only kept once. See [`IncrementalChecker`].
+    ///
+    /// [`IncrementalChecker`]: ./struct.IncrementalChecker.html
+    pub fn incremental_checker(&self, tags: &[Tag]) -> IncrementalChecker<'_> {
+        let mut checker = IncrementalChecker {
+            engine: self,
+            tags: Vec::new(),
+            valid: HashMap::new(),
+        };
+
+        let _ = checker.update(tags, &[]);
+        checker
+    }
+}
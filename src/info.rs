@@ -0,0 +1,134 @@
+/*
+ * info.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::Result;
+use std::slice;
+
+/// Every rule governing a single tag, from [`Engine::tag_info`].
+///
+/// This is a snapshot, later changes to the `Engine` are not reflected.
+/// All lists of tags are sorted.
+///
+/// [`Engine::tag_info`]: ./struct.Engine.html#method.tag_info
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TagInfo {
+    /// The tag described.
+    pub tag: Tag,
+
+    /// The name to show for the tag.
+    pub display_name: String,
+
+    /// Whether the tag may currently be applied.
+    pub enabled: bool,
+
+    /// Tags and groups which must be present alongside this tag.
+    pub required_tags: Vec<Tag>,
+
+    /// Groups which must have a member present alongside this tag.
+    pub required_groups: Vec<Tag>,
+
+    /// Lists of alternatives, at least one of each must be present alongside this tag.
+    pub required_any_of: Vec<Vec<Tag>>,
+
+    /// Tags and groups which must be present alongside this tag to publish, but not to draft.
+    pub publish_required_tags: Vec<Tag>,
+
+    /// Tags and groups which may not be present alongside this tag.
+    pub conflicting_tags: Vec<Tag>,
+
+    /// Every group this tag is a member of, including through subgroups.
+    pub groups: Vec<Tag>,
+
+    /// Groups this tag is a member of which permit at most one member to be present.
+    ///
+    /// See [`Engine::is_exclusive_group`].
+    ///
+    /// [`Engine::is_exclusive_group`]: ./struct.Engine.html#method.is_exclusive_group
+    pub exclusive_groups: Vec<Tag>,
+
+    /// Roles needed to apply or remove this tag, sorted.
+    pub needed_roles: Vec<Role>,
+
+    /// Proper tags which require this tag, directly or through a group.
+    pub dependents: Vec<Tag>,
+}
+
+impl Engine {
+    /// Gets everything governing the given tag at once, such as for a tooltip.
+    ///
+    /// Fails if it is not a proper tag.
+    pub fn tag_info(&self, tag: &Tag) -> Result<TagInfo> {
+        let spec = self.get_spec(tag)?;
+        let sorted = |tags: &[Tag]| {
+            let mut tags = tags.to_vec();
+            tags.sort();
+            tags
+        };
+
+        let mut groups = self
+            .get_tags()
+            .iter()
+            .filter(|group| *group != tag && self.is_group(group))
+            .filter(|group| self.count_tag_lenient(group, slice::from_ref(tag)) > 0)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        groups.sort();
+
+        let exclusive_groups = groups
+            .iter()
+            .filter(|group| self.is_exclusive_group(group))
+            .cloned()
+            .collect();
+
+        let mut needed_roles = spec.needed_roles.to_vec();
+        needed_roles.sort();
+
+        let dependents = self
+            .specs_sorted()
+            .into_iter()
+            .filter(|(other, _)| *other != tag)
+            .filter(|(_, other)| {
+                other
+                    .required_tags
+                    .iter()
+                    .chain(&other.required_groups)
+                    .chain(other.required_any_of.iter().flatten())
+                    .any(|required| {
+                        !self.is_computed(required)
+                            && self.count_tag_lenient(required, slice::from_ref(tag)) > 0
+                    })
+            })
+            .map(|(other, _)| Tag::clone(other))
+            .collect();
+
+        Ok(TagInfo {
+            tag: Tag::clone(tag),
+            display_name: str!(self.display_name(tag)),
+            enabled: self.is_tag_enabled(tag),
+            required_tags: sorted(&spec.required_tags),
+            required_groups: sorted(&spec.required_groups),
+            required_any_of: spec
+                .required_any_of
+                .iter()
+                .map(|tags| sorted(tags))
+                .collect(),
+            publish_required_tags: sorted(&spec.publish_required_tags),
+            conflicting_tags: sorted(&spec.conflicting_tags),
+            groups,
+            exclusive_groups,
+            needed_roles,
+            dependents,
+        })
+    }
+}
@@ -24,16 +24,61 @@
 //! The actual meaning of the tags, or what objects they are applied
 //! to is up to the consumer of the library.
 
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+
+#[macro_use]
+extern crate bitflags;
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+
 extern crate easy_strings;
 
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+
+#[cfg(feature = "schemars")]
+#[macro_use]
+extern crate schemars;
+
 #[macro_use]
 extern crate serde;
 
+#[cfg(feature = "schemars")]
+extern crate serde_json;
+
 #[macro_use]
 extern crate str_macro;
 
+extern crate toml;
+
+#[cfg(feature = "toml_edit")]
+extern crate toml_edit;
+
+#[macro_use]
+mod macros;
+
+mod cardinality;
+mod diff;
+mod draft;
 mod engine;
 mod error;
+#[cfg(feature = "chrono")]
+mod expiry;
+mod explore;
+mod flags;
+mod graph;
+mod incremental;
+mod info;
+mod lint;
+mod matrix;
+mod policy;
+mod prune;
+mod resolver;
+mod source;
+mod summary;
 mod tag;
 
 #[cfg(test)]
@@ -41,9 +86,22 @@ mod test;
 
 pub mod load;
 
-pub use self::engine::Engine;
-pub use self::error::Error;
-pub use self::tag::{Role, Tag, TagSpec, TemplateTagSpec};
+pub use self::cardinality::Cardinality;
+pub use self::diff::EngineDiff;
+pub use self::draft::DraftReport;
+pub use self::engine::{Engine, EngineSnapshot};
+pub use self::error::{sort_errors, Error};
+pub use self::flags::CheckFlags;
+pub use self::graph::Edges;
+pub use self::incremental::IncrementalChecker;
+pub use self::info::TagInfo;
+pub use self::lint::{ChangeWarning, LintWarning, SchemaRules, SchemaViolation};
+pub use self::matrix::CompatibilityMatrix;
+pub use self::policy::NamePolicy;
+pub use self::prune::PruneReport;
+pub use self::resolver::TagResolver;
+pub use self::summary::ChangeSummary;
+pub use self::tag::{Role, Tag, TagSet, TagSpec, TemplateTagSpec};
 
 /// An alias for the [`Result`] type found in the standard library.
 ///
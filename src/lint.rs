@@ -0,0 +1,441 @@
+/*
+ * lint.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::Result;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// A redundant operation found in a proposed tag change.
+///
+/// Unlike an [`Error`], these do not make the change invalid, they only have no effect.
+///
+/// [`Error`]: ./enum.Error.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeWarning {
+    /// The tag is being added, but is already present.
+    AlreadyPresent(Tag),
+
+    /// The tag is being removed, but is not present.
+    NotPresent(Tag),
+}
+
+/// A likely mistake found in an [`Engine`]'s configuration.
+///
+/// Most do not prevent the engine from working, but probably do not do what was intended.
+/// Those which indicate a broken configuration are [structural].
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [structural]: #method.is_structural
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintWarning {
+    /// The tag requires a group it is a member of, which it always satisfies by itself.
+    RequiresOwnGroup(Tag, Tag),
+
+    /// The tag requires, conflicts with, implies, or is a member of itself.
+    SelfReference(Tag),
+
+    /// The tag's specification refers to another tag or group which is not registered.
+    DanglingReference(Tag, Tag),
+
+    /// The tag's specification needs a [`Role`] which is not registered.
+    ///
+    /// [`Role`]: ./struct.Role.html
+    DanglingRole(Tag, Role),
+
+    /// The tags require each other in a loop, starting from the lowest-sorting tag.
+    RequirementCycle(Vec<Tag>),
+
+    /// The tag conflicts with a group which is not exclusive, only reported in strict mode.
+    ///
+    /// See [`Engine::set_strict`].
+    ///
+    /// [`Engine::set_strict`]: ./struct.Engine.html#method.set_strict
+    NonExclusiveConflict(Tag, Tag),
+
+    /// The first tag conflicts with the second, but not the other way around.
+    ///
+    /// Conflicts with groups are exempt. See [`Engine::symmetrize_conflicts`].
+    ///
+    /// [`Engine::symmetrize_conflicts`]: ./struct.Engine.html#method.symmetrize_conflicts
+    AsymmetricConflict(Tag, Tag),
+}
+
+impl LintWarning {
+    /// Whether this warning indicates a broken configuration, rather than merely a suspicious one.
+    ///
+    /// See [`Engine::validate`].
+    ///
+    /// [`Engine::validate`]: ./struct.Engine.html#method.validate
+    pub fn is_structural(&self) -> bool {
+        !matches!(
+            *self,
+            LintWarning::RequiresOwnGroup(_, _) | LintWarning::AsymmetricConflict(_, _)
+        )
+    }
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::LintWarning::*;
+
+        match *self {
+            RequiresOwnGroup(ref tag, ref group) => {
+                write!(f, "{} requires its own group {}", tag, group)
+            }
+            SelfReference(ref tag) => write!(f, "{} refers to itself", tag),
+            DanglingReference(ref tag, ref other) => {
+                write!(f, "{} refers to unregistered tag {}", tag, other)
+            }
+            DanglingRole(ref tag, ref role) => {
+                write!(f, "{} needs unregistered role {}", tag, role)
+            }
+            RequirementCycle(ref tags) => {
+                write!(f, "requirement cycle ")?;
+                for tag in tags {
+                    write!(f, "{} -> ", tag)?;
+                }
+
+                match tags.first() {
+                    Some(tag) => write!(f, "{}", tag),
+                    None => Ok(()),
+                }
+            }
+            NonExclusiveConflict(ref tag, ref group) => write!(
+                f,
+                "{} conflicts with group {}, which is not exclusive",
+                tag, group,
+            ),
+            AsymmetricConflict(ref tag, ref other) => {
+                write!(f, "{} conflicts with {}, but not the reverse", tag, other)
+            }
+        }
+    }
+}
+
+/// Conventions a deployment requires its configuration to follow, for [`Engine::check_schema`].
+///
+/// Every rule is disabled by default.
+///
+/// [`Engine::check_schema`]: ./struct.Engine.html#method.check_schema
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaRules {
+    /// Every proper tag must be a member of at least one group.
+    pub tags_need_group: bool,
+
+    /// Every tag group must have a display name. See [`Engine::display_name`].
+    ///
+    /// [`Engine::display_name`]: ./struct.Engine.html#method.display_name
+    pub groups_need_display_name: bool,
+
+    /// No tag may list more than this many roles in its `needed_roles`.
+    pub max_roles: Option<usize>,
+}
+
+/// A place where an [`Engine`]'s configuration breaks one of its [`SchemaRules`].
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`SchemaRules`]: ./struct.SchemaRules.html
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaViolation {
+    /// The tag is not a member of any group.
+    Ungrouped(Tag),
+
+    /// The group has no display name.
+    UnnamedGroup(Tag),
+
+    /// The tag needs more roles than allowed, giving how many.
+    TooManyRoles(Tag, usize),
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::SchemaViolation::*;
+
+        match *self {
+            Ungrouped(ref tag) => write!(f, "{} is not in any group", tag),
+            UnnamedGroup(ref group) => write!(f, "group {} has no display name", group),
+            TooManyRoles(ref tag, count) => write!(f, "{} needs {} roles", tag, count),
+        }
+    }
+}
+
+impl Engine {
+    /// Finds likely mistakes in the configured tag specifications.
+    ///
+    /// This includes everything reported by [`validate`]. The returned warnings are sorted.
+    ///
+    /// [`validate`]: #method.validate
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+
+        for (tag, spec) in self.get_specs() {
+            let required = spec.required_tags.iter().chain(&spec.required_groups);
+
+            for group in required {
+                if spec.groups.contains(group) {
+                    let warning = LintWarning::RequiresOwnGroup(Tag::clone(tag), Tag::clone(group));
+                    warnings.push(warning);
+                }
+            }
+
+            for other in &spec.conflicting_tags {
+                if self.is_one_sided_conflict(tag, other) {
+                    let warning =
+                        LintWarning::AsymmetricConflict(Tag::clone(tag), Tag::clone(other));
+                    warnings.push(warning);
+                }
+            }
+        }
+
+        self.lint_structure(&mut warnings);
+        warnings.sort();
+        warnings.dedup();
+        warnings
+    }
+
+    /// Finds problems with the configuration itself, such as self-references,
+    /// references to unregistered tags or roles, and requirement cycles.
+    /// In [strict mode], conflicts with groups which are not exclusive are included.
+    ///
+    /// Only the [structural] warnings from [`lint`] are returned, sorted.
+    ///
+    /// [structural]: ./enum.LintWarning.html#method.is_structural
+    /// [strict mode]: #method.set_strict
+    /// [`lint`]: #method.lint
+    pub fn validate(&self) -> Vec<LintWarning> {
+        let mut warnings = Vec::new();
+        self.lint_structure(&mut warnings);
+        warnings.sort();
+        warnings.dedup();
+        warnings
+    }
+
+    /// Determines whether the configuration is free of structural problems.
+    ///
+    /// This does not consider any particular tagset. See [`validate`] for the problems found.
+    ///
+    /// [`validate`]: #method.validate
+    pub fn is_valid_config(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    /// Confirms every tag and group referenced by a specification is registered.
+    ///
+    /// Walks the requirements, conflicts, and groups of each specification in sorted order,
+    /// failing with [`Error::InvalidConfig`] holding a [`LintWarning::DanglingReference`]
+    /// for the first reference which is not registered. This names both the specification
+    /// and the missing tag up front, rather than leaving [`check_tags`] to fail later with
+    /// a misleading error, such as an unmet requirement. For every structural problem
+    /// at once, see [`validate`].
+    ///
+    /// [`Error::InvalidConfig`]: ./enum.Error.html#variant.InvalidConfig
+    /// [`LintWarning::DanglingReference`]: ./enum.LintWarning.html#variant.DanglingReference
+    /// [`check_tags`]: #method.check_tags
+    /// [`validate`]: #method.validate
+    pub fn validate_references(&self) -> Result<()> {
+        for (tag, spec) in self.specs_sorted() {
+            let references = spec
+                .required_tags
+                .iter()
+                .chain(&spec.required_groups)
+                .chain(spec.required_any_of.iter().flatten())
+                .chain(&spec.publish_required_tags)
+                .chain(&spec.conflicting_tags)
+                .chain(&spec.groups);
+
+            for other in references {
+                if !self.has_tag(&**other) {
+                    let warning =
+                        LintWarning::DanglingReference(Tag::clone(tag), Tag::clone(other));
+                    return Err(Error::InvalidConfig(warning));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds every place the configuration breaks the given conventions.
+    ///
+    /// Unlike [`lint`], which looks for mistakes any configuration could have,
+    /// this enforces rules chosen by the deployment. The returned violations are sorted.
+    ///
+    /// [`lint`]: #method.lint
+    pub fn check_schema(&self, rules: &SchemaRules) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+
+        for (tag, spec) in self.get_specs() {
+            if rules.tags_need_group && spec.groups.is_empty() {
+                violations.push(SchemaViolation::Ungrouped(Tag::clone(tag)));
+            }
+
+            match rules.max_roles {
+                Some(max) if spec.needed_roles.len() > max => {
+                    let count = spec.needed_roles.len();
+                    violations.push(SchemaViolation::TooManyRoles(Tag::clone(tag), count));
+                }
+                _ => (),
+            }
+        }
+
+        if rules.groups_need_display_name {
+            for group in self.get_tags() {
+                if self.is_group(group) && self.display_name(group) == &**group {
+                    violations.push(SchemaViolation::UnnamedGroup(Tag::clone(group)));
+                }
+            }
+        }
+
+        violations.sort();
+        violations
+    }
+
+    fn lint_structure(&self, warnings: &mut Vec<LintWarning>) {
+        for (tag, spec) in self.get_specs() {
+            let references = spec
+                .required_tags
+                .iter()
+                .chain(&spec.required_groups)
+                .chain(spec.required_any_of.iter().flatten())
+                .chain(&spec.publish_required_tags)
+                .chain(&spec.conflicting_tags)
+                .chain(&spec.implied_tags)
+                .chain(&spec.groups);
+
+            for other in references {
+                if other == tag {
+                    warnings.push(LintWarning::SelfReference(Tag::clone(tag)));
+                } else if !self.has_tag(&**other) {
+                    let warning =
+                        LintWarning::DanglingReference(Tag::clone(tag), Tag::clone(other));
+                    warnings.push(warning);
+                }
+            }
+
+            for role in spec.needed_roles.iter() {
+                if !self.has_role(&**role) {
+                    warnings.push(LintWarning::DanglingRole(
+                        Tag::clone(tag),
+                        Role::clone(role),
+                    ));
+                }
+            }
+
+            if self.is_strict() {
+                for group in &spec.conflicting_tags {
+                    if self.is_group(group) && !self.is_exclusive_group(group) {
+                        let warning =
+                            LintWarning::NonExclusiveConflict(Tag::clone(tag), Tag::clone(group));
+                        warnings.push(warning);
+                    }
+                }
+            }
+        }
+
+        let mut visited = HashMap::new();
+        for (tag, _) in self.specs_sorted() {
+            let mut path = Vec::new();
+            self.find_cycles(tag, &mut path, &mut visited, warnings);
+        }
+    }
+
+    // Depth-first search over requirements of every kind between proper tags,
+    // where requiring a group leads to each of its members, as in tag_depth().
+    // Visited tags are marked `false` while on the current path, and `true` once finished.
+    fn find_cycles<'a>(
+        &'a self,
+        tag: &'a Tag,
+        path: &mut Vec<&'a Tag>,
+        visited: &mut HashMap<&'a Tag, bool>,
+        warnings: &mut Vec<LintWarning>,
+    ) {
+        match visited.get(tag) {
+            Some(true) => return,
+            Some(false) => {
+                let start = path.iter().position(|other| *other == tag).unwrap_or(0);
+                let mut cycle = path[start..]
+                    .iter()
+                    .map(|&tag| Tag::clone(tag))
+                    .collect::<Vec<_>>();
+
+                if let Some(lowest) = (0..cycle.len()).min_by_key(|&i| &cycle[i]) {
+                    cycle.rotate_left(lowest);
+                }
+
+                warnings.push(LintWarning::RequirementCycle(cycle));
+                return;
+            }
+            None => (),
+        }
+
+        let spec = match self.get_specs().get(tag) {
+            Some(spec) => spec,
+            None => return,
+        };
+
+        visited.insert(tag, false);
+        path.push(tag);
+
+        let requirements = spec
+            .required_tags
+            .iter()
+            .chain(&spec.required_groups)
+            .chain(spec.required_any_of.iter().flatten());
+
+        for required in requirements {
+            if !self.is_group(required) {
+                if required != tag {
+                    self.find_cycles(required, path, visited, warnings);
+                }
+
+                continue;
+            }
+
+            // A tag satisfies a requirement on its own group by itself
+            for member in self.tags_in_group(required) {
+                if let Some((member, _)) = self.get_specs().get_key_value(&member) {
+                    if member != tag {
+                        self.find_cycles(member, path, visited, warnings);
+                    }
+                }
+            }
+        }
+
+        path.pop();
+        visited.insert(tag, true);
+    }
+
+    /// Finds operations in a tag change which would have no effect.
+    ///
+    /// Warnings for added tags are listed before those for removed tags.
+    /// This does not check whether the change is valid, see [`check_tag_changes`] for that.
+    ///
+    /// [`check_tag_changes`]: ./struct.Engine.html#method.check_tag_changes
+    pub fn lint_change(&self, tags: &[Tag], added: &[Tag], removed: &[Tag]) -> Vec<ChangeWarning> {
+        let mut warnings = Vec::new();
+
+        for tag in added {
+            if tags.contains(tag) {
+                warnings.push(ChangeWarning::AlreadyPresent(Tag::clone(tag)));
+            }
+        }
+
+        for tag in removed {
+            if !tags.contains(tag) {
+                warnings.push(ChangeWarning::NotPresent(Tag::clone(tag)));
+            }
+        }
+
+        warnings
+    }
+}
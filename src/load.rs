@@ -20,63 +20,249 @@
 //! [`Engine`]: ./struct.Engine.html
 
 use crate::prelude::*;
-use crate::Result;
+use crate::{EngineDiff, Error, Result};
 use std::collections::HashSet;
-use std::mem;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::Path;
+use std::result::Result as StdResult;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+#[cfg(feature = "toml_edit")]
+mod preserve;
+
+#[cfg(feature = "toml_edit")]
+pub use self::preserve::to_toml_preserving;
+
+/// The current version of the [`Configuration`] format.
+///
+/// This is only bumped when older configurations need [migrating]. Fields which
+/// are optional, and whose omission keeps the previous behavior, do not change it.
+///
+/// [`Configuration`]: ./struct.Configuration.html
+/// [migrating]: ./struct.Configuration.html#method.migrate
+pub const CONFIG_VERSION: u32 = 2;
 
 /// A serializeable struct that can be applied to an [`Engine`].
 ///
 /// [`Engine`]: ./struct.Engine.html
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct Configuration {
+    /// Which version of the format this configuration uses.
+    ///
+    /// Optional, treated as `1` if omitted. See [`migrate`].
+    ///
+    /// [`migrate`]: #method.migrate
+    #[serde(default = "default_version")]
+    pub version: u32,
+
     /// A declaration of all [`Role`]s.
     ///
     /// [`Role`]: ./struct.Role.html
     pub roles: Vec<String>,
 
+    /// Named sets of roles, which tags may list in place of the roles themselves.
+    ///
+    /// The set names do not need to be declared in `roles`.
+    /// Optional, treated as empty if omitted. See [`Engine::add_role_set`].
+    ///
+    /// [`Engine::add_role_set`]: ../struct.Engine.html#method.add_role_set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub role_sets: Vec<RoleSetConfig>,
+
     /// All tags, and their respective configuration.
     ///
     /// See also [`TemplateTagSpec`].
     ///
     /// [`TemplateTagSpec`]: ./struct.TemplateTagSpec.html
     pub tags: Vec<TagConfig>,
+
+    /// Explicit declarations of tag groups, and their configuration.
+    ///
+    /// Groups referenced by a tag's `groups` are created automatically, so this is only
+    /// needed for groups without members, or to configure them further.
+    /// Optional, treated as empty if omitted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupConfig>,
+
+    /// Named tagsets for applications to start new objects from.
+    ///
+    /// Optional, treated as empty if omitted. See [`Engine::register_template`].
+    ///
+    /// [`Engine::register_template`]: ../struct.Engine.html#method.register_template
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<TemplateConfig>,
+
+    /// Alternative names for tags and groups.
+    ///
+    /// These are only for looking up tags once loaded. The rest of the configuration,
+    /// including the aliases' targets, must use the actual names.
+    /// Optional, treated as empty if omitted. See [`Engine::add_alias`].
+    ///
+    /// [`Engine::add_alias`]: ../struct.Engine.html#method.add_alias
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<AliasConfig>,
 }
 
 impl Configuration {
+    /// Upgrades a configuration written for an older version of the format.
+    ///
+    /// Fields introduced since that version are filled in with their defaults,
+    /// and `version` is set to [`CONFIG_VERSION`]. Current configurations are left as-is.
+    ///
+    /// Fields added since version 2 (`role_sets`, `groups`, `templates`, `aliases`,
+    /// and a tag's `requires_expr`, `publish_requires`, `enabled`, and `expires_at`)
+    /// are left unset, since omitting them already means the version 2 behavior.
+    ///
+    /// [`CONFIG_VERSION`]: ./constant.CONFIG_VERSION.html
+    pub fn migrate(&mut self) {
+        if self.version < 2 {
+            // Version 2 added public, implies, and priority
+            for tag in &mut self.tags {
+                tag.public.get_or_insert(false);
+                tag.implies.get_or_insert_with(Vec::new);
+                tag.priority.get_or_insert(0);
+            }
+        }
+
+        self.version = CONFIG_VERSION;
+    }
+
     /// Parses all of the fields in the config and applies them to the [`Engine`].
     ///
+    /// The configuration is [migrated] to the current format first.
+    /// Fails if the configuration refers to tags or roles it does not declare,
+    /// or if any part of it otherwise cannot be applied. The engine is then left unchanged.
+    ///
+    /// [`Engine`]: ./struct.Engine.html
+    /// [migrated]: #method.migrate
+    pub fn apply(self, engine: &mut Engine) -> Result<()> {
+        let snapshot = engine.snapshot();
+        let result = self.apply_unchecked(engine);
+        if result.is_err() {
+            engine.restore(snapshot);
+        }
+
+        result
+    }
+
+    fn apply_unchecked(mut self, engine: &mut Engine) -> Result<()> {
+        self.migrate();
+        let Configuration {
+            roles,
+            role_sets,
+            tags,
+            groups,
+            templates,
+            aliases,
+            ..
+        } = self;
+
+        Self::apply_roles(&roles, &role_sets, true, engine)?;
+        Self::prune_aliases(&aliases, engine);
+        Self::apply_tags(&tags, engine)?;
+        Self::apply_groups(&groups, engine)?;
+        Self::update_tags(&tags, engine)?;
+        Self::apply_templates(&templates, engine)?;
+        Self::apply_aliases(&aliases, engine)?;
+        engine.shrink_to_fit();
+        Ok(())
+    }
+
+    /// Applies only the roles in the config to the [`Engine`], leaving tags alone.
+    ///
+    /// Useful when reloading a configuration where only the permissions changed.
+    /// Roles and role sets are only added or updated. Those absent from the config
+    /// are kept, as deleting them would change which roles tags need.
+    ///
     /// [`Engine`]: ./struct.Engine.html
-    pub fn apply(self, engine: &mut Engine) {
-        let Configuration { roles, tags } = self;
+    pub fn apply_roles_only(&self, engine: &mut Engine) {
+        Self::apply_roles(&self.roles, &self.role_sets, false, engine)
+            .expect("Unable to add roles");
+    }
 
-        Self::apply_roles(roles, engine);
-        Self::apply_tags(&tags, engine);
-        Self::update_tags(tags, engine).expect("Unable to update tag data");
+    /// Reports how the [`Engine`] differs from what this configuration describes.
+    ///
+    /// Additions are things present in the engine but absent from the configuration.
+    /// Nothing is applied, the engine is only read.
+    /// Fails as [`Engine::from_config`] does if the configuration cannot be loaded.
+    ///
+    /// [`Engine`]: ./struct.Engine.html
+    /// [`Engine::from_config`]: ../struct.Engine.html#method.from_config
+    pub fn drift(&self, engine: &Engine) -> Result<EngineDiff> {
+        let expected = Engine::from_config(self.clone())?;
+        Ok(expected.diff(engine))
     }
 
-    fn apply_roles(roles: Vec<String>, engine: &mut Engine) {
+    fn apply_roles(
+        roles: &[String],
+        sets: &[RoleSetConfig],
+        prune: bool,
+        engine: &mut Engine,
+    ) -> Result<()> {
         let extant_roles = engine
             .get_roles()
             .iter()
             .map(Role::clone)
             .collect::<HashSet<Role>>();
 
+        // Role sets are roles too
+        let roles = roles
+            .iter()
+            .chain(sets.iter().map(|set| &set.name))
+            .collect::<Vec<_>>();
+
         // Remove old roles
-        for extant_role in &extant_roles {
-            if !roles.contains(extant_role.as_ref()) {
-                engine.delete_role(&extant_role);
+        if prune {
+            for extant_role in &extant_roles {
+                if !roles.iter().any(|role| **role == **extant_role) {
+                    engine.delete_role(&extant_role);
+                }
             }
         }
 
         // Add new roles
         for role in roles {
-            if !extant_roles.contains(&role) {
-                engine.add_role(role);
+            if !extant_roles.contains(role.as_str()) {
+                engine.add_role(role.as_str())?;
             }
         }
+
+        // Remove old role sets, keeping the roles
+        if prune {
+            let extant_sets = engine.get_role_sets().keys().cloned().collect::<Vec<_>>();
+            for extant_set in extant_sets {
+                if !sets.iter().any(|set| set.name == *extant_set) {
+                    engine.delete_role_set(&extant_set);
+                }
+            }
+        }
+
+        // Add or replace the rest
+        for set in sets {
+            let mut members = Vec::new();
+            for name in &set.roles {
+                members.push(engine.get_role(name.as_str())?);
+            }
+
+            engine.add_role_set(&set.name, members)?;
+        }
+
+        Ok(())
     }
 
-    fn apply_tags(tags: &[TagConfig], engine: &mut Engine) {
+    fn apply_tags(tags: &[TagConfig], engine: &mut Engine) -> Result<()> {
         let extant_tags = engine
             .get_tags()
             .iter()
@@ -94,92 +280,437 @@ impl Configuration {
         // Add new tags
         for tag in tags {
             if !extant_tags.contains(&tag.name) {
-                engine.add_tag(&tag.name, TemplateTagSpec::default());
+                engine.add_tag(&tag.name, TemplateTagSpec::default())?;
             }
         }
+
+        Ok(())
     }
 
-    fn update_tags(configs: Vec<TagConfig>, engine: &mut Engine) -> Result<()> {
+    fn apply_groups(groups: &[GroupConfig], engine: &mut Engine) -> Result<()> {
+        for config in groups {
+            let group = match find_tag(engine, config.name.as_str()) {
+                Ok(ref group) if engine.is_group(group) => Tag::clone(group),
+                _ => engine.add_group(config.name.as_str())?,
+            };
+
+            engine.set_display_name(&group, config.display_name.clone())?;
+        }
+
+        Ok(())
+    }
+
+    fn update_tags(configs: &[TagConfig], engine: &mut Engine) -> Result<()> {
         for config in configs {
-            let TagConfig {
-                name,
-                groups,
-                roles,
-                requires,
-                conflicts_with,
-            } = config;
-
-            let current_tag = engine.get_tag(name)?;
-
-            // Update required_tags
-            {
-                let requires = requires.unwrap_or_else(Vec::new);
-                let mut required_tags = Vec::new();
-
-                for name in requires {
-                    let tag = engine.get_tag(name)?;
-                    required_tags.push(tag);
-                }
+            engine.register_tag_config(config)?;
+        }
 
-                let spec = engine.get_spec_mut(&current_tag)?;
-                mem::replace(&mut spec.required_tags, required_tags);
+        Ok(())
+    }
+
+    fn apply_templates(templates: &[TemplateConfig], engine: &mut Engine) -> Result<()> {
+        // Remove old templates
+        let extant_names = engine.get_templates().keys().cloned().collect::<Vec<_>>();
+        for name in extant_names {
+            if !templates.iter().any(|template| template.name == name) {
+                engine.delete_template(&name);
             }
+        }
 
-            // Update conflicting_tags
-            {
-                let conflicts_with = conflicts_with.unwrap_or_else(Vec::new);
-                let mut conflicting_tags = Vec::new();
+        // Add or replace the rest
+        for template in templates {
+            let mut tags = Vec::new();
+            for name in &template.tags {
+                tags.push(find_tag(engine, name.as_str())?);
+            }
 
-                for name in conflicts_with {
-                    let tag = engine.get_tag(name)?;
-                    conflicting_tags.push(tag);
-                }
+            engine.register_template(&template.name, tags)?;
+        }
+
+        Ok(())
+    }
 
-                let spec = engine.get_spec_mut(&current_tag)?;
-                mem::replace(&mut spec.conflicting_tags, conflicting_tags);
+    // Done before tags are added, so old aliases cannot collide with new tag names
+    fn prune_aliases(aliases: &[AliasConfig], engine: &mut Engine) {
+        let extant_names = engine.get_aliases().keys().cloned().collect::<Vec<_>>();
+        for name in extant_names {
+            if !aliases.iter().any(|alias| alias.name == name) {
+                engine.delete_alias(&name);
             }
+        }
+    }
 
-            // Update groups
-            {
-                let groups = groups.unwrap_or_else(Vec::new);
-                let mut new_groups = Vec::new();
+    fn apply_aliases(aliases: &[AliasConfig], engine: &mut Engine) -> Result<()> {
+        for alias in aliases {
+            let tag = find_tag(engine, alias.tag.as_str())?;
+            engine.add_alias(&alias.name, &tag)?;
+        }
 
-                for name in groups {
-                    let group = match engine.get_tag(name.as_str()) {
-                        Ok(group) => group,
-                        Err(_) => engine.add_group(name),
-                    };
+        Ok(())
+    }
+}
+
+impl Engine {
+    /// Creates an `Engine` from the given [`Configuration`].
+    ///
+    /// Fails under the same conditions as [`Configuration::apply`].
+    ///
+    /// [`Configuration`]: ./load/struct.Configuration.html
+    /// [`Configuration::apply`]: ./load/struct.Configuration.html#method.apply
+    pub fn from_config(config: Configuration) -> Result<Self> {
+        let mut engine = Engine::default();
+        config.apply_unchecked(&mut engine)?;
+        Ok(engine)
+    }
+
+    /// Creates an `Engine` from the given [`Configuration`], then checks it for structural problems.
+    ///
+    /// This is stricter than [`from_config`], as it also rejects configurations with
+    /// requirement cycles, self-references, or dangling references, returning
+    /// [`Error::InvalidConfig`] with the first problem found by [`validate`].
+    ///
+    /// [`Configuration`]: ./load/struct.Configuration.html
+    /// [`Error::InvalidConfig`]: ./enum.Error.html#variant.InvalidConfig
+    /// [`from_config`]: #method.from_config
+    /// [`validate`]: #method.validate
+    pub fn with_config_validated(config: Configuration) -> Result<Self> {
+        let engine = Self::from_config(config)?;
+
+        match engine.validate().into_iter().next() {
+            Some(warning) => Err(Error::InvalidConfig(warning)),
+            None => Ok(engine),
+        }
+    }
 
-                    new_groups.push(group);
+    /// Registers a single [`TagConfig`], as if it were part of a [`Configuration`].
+    ///
+    /// The tag is created if it does not already exist, otherwise its specification
+    /// is replaced. Any groups it is listed under are created as needed, but all
+    /// other referenced tags and roles must already be registered, and are looked up
+    /// by their exact names rather than through aliases. Fields which
+    /// `TagConfig` does not describe, such as the display name, are kept.
+    ///
+    /// Returns the created or updated [`Tag`]. On failure, the engine is left unchanged.
+    /// As with [`add_tag`], in [strict mode] conflicting with a group which is not
+    /// exclusive fails with [`Error::InvalidConfig`].
+    ///
+    /// [`TagConfig`]: ./load/struct.TagConfig.html
+    /// [`Configuration`]: ./load/struct.Configuration.html
+    /// [`Tag`]: ./struct.Tag.html
+    /// [`add_tag`]: #method.add_tag
+    /// [strict mode]: #method.set_strict
+    /// [`Error::InvalidConfig`]: ./enum.Error.html#variant.InvalidConfig
+    pub fn register_tag_config(&mut self, config: &TagConfig) -> Result<Tag> {
+        let TagConfig {
+            ref name,
+            ref groups,
+            ref roles,
+            public,
+            ref requires,
+            ref requires_expr,
+            ref publish_requires,
+            ref conflicts_with,
+            ref implies,
+            priority,
+            enabled,
+            #[cfg(feature = "chrono")]
+            expires_at,
+        } = *config;
+
+        // Everything is resolved before the engine is modified,
+        // so failures leave no partially-registered tag behind.
+        self.get_name_policy().check(name)?;
+
+        // Aliases are not followed, the tag is always the one with this exact name
+        let mut spec = match self.get_specs().get(name.as_str()) {
+            Some(spec) => TemplateTagSpec::from(spec),
+            None => TemplateTagSpec::default(),
+        };
+
+        // The tag may refer to itself, even if it is not registered yet
+        let get_tag = |engine: &Engine, tag_name: &str| match find_tag(engine, tag_name) {
+            Err(_) if tag_name == name => Ok(Tag::new(tag_name)),
+            result => result,
+        };
+
+        let get_tags = |engine: &Engine, names: &Option<Vec<String>>| -> Result<Vec<Tag>> {
+            let mut tags = Vec::new();
+
+            for name in names.iter().flatten() {
+                let tag = get_tag(engine, name.as_str())?;
+                tags.push(tag);
+            }
+
+            Ok(tags)
+        };
+
+        // Resolve required_tags and required_any_of
+        {
+            let mut required_tags = get_tags(self, requires)?;
+            let mut required_any_of = Vec::new();
+
+            if let Some(expr) = requires_expr {
+                for names in parse_requirement(name, expr)? {
+                    let mut alternatives = get_tags(self, &Some(names))?;
+
+                    if alternatives.len() > 1 {
+                        required_any_of.push(alternatives);
+                    } else if !required_tags.contains(&alternatives[0]) {
+                        required_tags.append(&mut alternatives);
+                    }
                 }
+            }
+
+            spec.required_tags = required_tags;
+            spec.required_any_of = required_any_of;
+        }
+
+        // Resolve the other tag lists
+        spec.publish_required_tags = get_tags(self, publish_requires)?;
+        spec.conflicting_tags = get_tags(self, conflicts_with)?;
+        spec.implied_tags = get_tags(self, implies)?;
 
-                let spec = engine.get_spec_mut(&current_tag)?;
-                mem::replace(&mut spec.groups, new_groups);
+        // Resolve groups, checking the names of those to be created
+        let mut new_groups = Vec::new();
+        spec.groups.clear();
+
+        for group_name in groups.iter().flatten() {
+            match get_tag(self, group_name.as_str()) {
+                Ok(group) => spec.groups.push(group),
+                Err(_) => {
+                    self.get_name_policy().check(group_name)?;
+
+                    if self.get_aliases().contains_key(group_name) {
+                        return Err(Error::NameCollision(str!(group_name)));
+                    }
+
+                    spec.groups.push(Tag::new(group_name.as_str()));
+                    new_groups.push(group_name.as_str());
+                }
             }
+        }
+
+        // Resolve roles
+        spec.needed_roles.clear();
+
+        for name in roles.iter().flatten() {
+            let role = self.get_role(name.as_str())?;
+            spec.needed_roles.push(role);
+        }
 
-            // Update roles
-            {
-                let roles = roles.unwrap_or_else(Vec::new);
-                let mut needed_roles = Vec::new();
+        spec.public = public.unwrap_or(false);
+        spec.priority = priority.unwrap_or(0);
 
-                for name in roles {
-                    let role = engine.get_role(name)?;
-                    needed_roles.push(role);
+        #[cfg(feature = "chrono")]
+        {
+            spec.expires_at = expires_at;
+        }
+
+        // Now register everything
+        let current_tag = self.add_tag(name.as_str(), spec)?;
+
+        for group_name in new_groups {
+            self.add_group(group_name)?;
+        }
+
+        self.set_tag_enabled(&current_tag, enabled.unwrap_or(true))?;
+        Ok(current_tag)
+    }
+}
+
+impl TryFrom<Configuration> for Engine {
+    type Error = Error;
+
+    /// Creates an `Engine` from the given [`Configuration`], as per [`Engine::from_config`].
+    ///
+    /// [`Configuration`]: ./load/struct.Configuration.html
+    /// [`Engine::from_config`]: ./struct.Engine.html#method.from_config
+    #[inline]
+    fn try_from(config: Configuration) -> Result<Self> {
+        Engine::from_config(config)
+    }
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Name(&'a str),
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+// The most clauses a requirement expression may expand to. Distributing | over &
+// multiplies the number of clauses, so this bounds the work done for one expression.
+const MAX_REQUIREMENT_CLAUSES: usize = 256;
+
+// Parses a requirement expression into conjunctive normal form, that is, a list
+// of alternatives where at least one name from each must be present.
+fn parse_requirement(tag: &str, expr: &str) -> Result<Vec<Vec<String>>> {
+    match parse_clauses(expr) {
+        Ok(clauses) => Ok(clauses),
+        Err(reason) => Err(Error::InvalidRequirement(str!(tag), str!(expr), reason)),
+    }
+}
+
+fn parse_clauses(expr: &str) -> StdResult<Vec<Vec<String>>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in expr.char_indices() {
+        let token = match c {
+            '&' => Some(Token::And),
+            '|' => Some(Token::Or),
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            _ if c.is_whitespace() => None,
+            _ => {
+                start.get_or_insert(i);
+                continue;
+            }
+        };
+
+        if let Some(start) = start.take() {
+            tokens.push(Token::Name(&expr[start..i]));
+        }
+
+        tokens.extend(token);
+    }
+
+    if let Some(start) = start {
+        tokens.push(Token::Name(&expr[start..]));
+    }
+
+    let mut parser = RequirementParser {
+        tokens,
+        position: 0,
+    };
+    let clauses = parser.parse_and()?;
+
+    match parser.next() {
+        None => Ok(clauses),
+        Some(Token::Close) => Err("unopened parenthesis"),
+        Some(_) => Err("expected an operator"),
+    }
+}
+
+struct RequirementParser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> RequirementParser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn parse_and(&mut self) -> StdResult<Vec<Vec<String>>, &'static str> {
+        let mut clauses = self.parse_or()?;
+
+        while self.peek() == Some(Token::And) {
+            self.next();
+            clauses.extend(self.parse_or()?);
+
+            if clauses.len() > MAX_REQUIREMENT_CLAUSES {
+                return Err("too many clauses");
+            }
+        }
+
+        Ok(clauses)
+    }
+
+    fn parse_or(&mut self) -> StdResult<Vec<Vec<String>>, &'static str> {
+        let mut clauses = self.parse_atom()?;
+
+        while self.peek() == Some(Token::Or) {
+            self.next();
+            let right = self.parse_atom()?;
+
+            if clauses.len() * right.len() > MAX_REQUIREMENT_CLAUSES {
+                return Err("too many clauses");
+            }
+
+            // (a & b) | (c & d) is equivalent to (a | c) & (a | d) & (b | c) & (b | d)
+            let mut distributed = Vec::new();
+            for left in &clauses {
+                for right in &right {
+                    let mut clause = left.clone();
+                    for name in right {
+                        if !clause.contains(name) {
+                            clause.push(String::clone(name));
+                        }
+                    }
+
+                    distributed.push(clause);
                 }
+            }
+
+            clauses = distributed;
+        }
 
-                let spec = engine.get_spec_mut(&current_tag)?;
-                mem::replace(&mut spec.needed_roles, needed_roles);
+        Ok(clauses)
+    }
+
+    fn parse_atom(&mut self) -> StdResult<Vec<Vec<String>>, &'static str> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(vec![vec![str!(name)]]),
+            Some(Token::Open) => {
+                let clauses = self.parse_and()?;
+
+                match self.next() {
+                    Some(Token::Close) => Ok(clauses),
+                    _ => Err("unclosed parenthesis"),
+                }
             }
+            Some(_) => Err("expected a name"),
+            None => Err("unexpected end"),
         }
+    }
+}
 
-        Ok(())
+// Looks up a tag or group by its exact name, as configurations may not refer to aliases.
+fn find_tag(engine: &Engine, name: &str) -> Result<Tag> {
+    match engine.get_tags().get(name) {
+        Some(tag) => Ok(Tag::clone(tag)),
+        None => Err(Error::NoSuchTag(str!(name))),
     }
 }
 
+// Wraps an error from a parser, keeping it as the source.
+fn parser_error<E: StdError + 'static>(error: E) -> Error {
+    Error::Parse(error.to_string(), Some(Box::new(error)))
+}
+
+/// Reads a [`Configuration`] from the TOML file at the given path.
+///
+/// Fails with [`Error::Io`] if the file cannot be read, or [`Error::Parse`] if it is
+/// not a valid configuration. Either way, the underlying error is available as `source`.
+///
+/// [`Configuration`]: ./struct.Configuration.html
+/// [`Error::Io`]: ../enum.Error.html#variant.Io
+/// [`Error::Parse`]: ../enum.Error.html#variant.Parse
+pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Configuration> {
+    let contents = fs::read_to_string(path)?;
+    let config = toml::from_str(&contents).map_err(parser_error)?;
+    Ok(config)
+}
+
 /// Serializeable sub-structure used as part of [`Configuration`].
 ///
 /// [`Configuration`]: ./struct.Configuration.html
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 pub struct TagConfig {
     /// The name of the [`Tag`].
     ///
@@ -188,22 +719,167 @@ pub struct TagConfig {
 
     /// All groups of which this [`Tag`] is a member.
     ///
+    /// Optional, treated as empty if omitted.
+    ///
     /// [`Tag`]: ./struct.Tag.html
     pub groups: Option<Vec<String>>,
 
     /// Any [`Role`]s needed to apply or remove this [`Tag`].
     ///
+    /// Optional, treated as empty if omitted.
+    ///
     /// [`Role`]: ./struct.Role.html
     /// [`Tag`]: ./struct.Tag.html
     pub roles: Option<Vec<String>>,
 
+    /// Whether anyone may apply or remove this [`Tag`], regardless of `roles`.
+    ///
+    /// Optional, treated as `false` if omitted.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub public: Option<bool>,
+
     /// Which other [`Tag`]s or tag groups this tag requires.
     ///
+    /// Optional, treated as empty if omitted.
+    ///
     /// [`Tag`]: ./struct.Tag.html
     pub requires: Option<Vec<String>>,
 
+    /// A boolean expression of other [`Tag`]s or tag groups this tag requires,
+    /// such as `"scp & (safe | euclid)"`.
+    ///
+    /// Names may be combined with `&` and `|`, and grouped with parentheses.
+    /// This is in addition to `requires`. Optional, treated as empty if omitted.
+    /// Malformed or overly complex expressions fail with [`Error::InvalidRequirement`].
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    /// [`Error::InvalidRequirement`]: ../enum.Error.html#variant.InvalidRequirement
+    pub requires_expr: Option<String>,
+
+    /// Which other [`Tag`]s or tag groups this tag requires before publishing, but not in drafts.
+    ///
+    /// Optional, treated as empty if omitted. See [`Engine::check_tags_for_publish`].
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    /// [`Engine::check_tags_for_publish`]: ../struct.Engine.html#method.check_tags_for_publish
+    pub publish_requires: Option<Vec<String>>,
+
     /// Which other [`Tag`]s or tag groups this tag conflicts with.
     ///
+    /// Optional, treated as empty if omitted.
+    ///
     /// [`Tag`]: ./struct.Tag.html
     pub conflicts_with: Option<Vec<String>>,
+
+    /// Which other [`Tag`]s are implied by this tag.
+    ///
+    /// Optional, treated as empty if omitted.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub implies: Option<Vec<String>>,
+
+    /// How important this [`Tag`] is when resolving conflicts.
+    ///
+    /// Optional, treated as `0` if omitted.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub priority: Option<i32>,
+
+    /// Whether this [`Tag`] may be newly applied.
+    ///
+    /// Optional, treated as `true` if omitted. See [`Engine::set_tag_enabled`].
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    /// [`Engine::set_tag_enabled`]: ../struct.Engine.html#method.set_tag_enabled
+    pub enabled: Option<bool>,
+
+    /// When this [`Tag`] stops being relevant, as an RFC 3339 timestamp.
+    ///
+    /// Optional, never expires if omitted.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Serializeable sub-structure used as part of [`Configuration`].
+///
+/// [`Configuration`]: ./struct.Configuration.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RoleSetConfig {
+    /// The name of the role set.
+    pub name: String,
+
+    /// The names of the [`Role`]s in the set, holding any of which grants the set.
+    ///
+    /// [`Role`]: ./struct.Role.html
+    pub roles: Vec<String>,
+}
+
+/// Serializeable sub-structure used as part of [`Configuration`].
+///
+/// [`Configuration`]: ./struct.Configuration.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct GroupConfig {
+    /// The name of the tag group.
+    pub name: String,
+
+    /// A human-readable label to show in place of the group's name.
+    ///
+    /// Optional, the name is used if omitted. See [`Engine::display_name`].
+    ///
+    /// [`Engine::display_name`]: ../struct.Engine.html#method.display_name
+    pub display_name: Option<String>,
+}
+
+/// Serializeable sub-structure used as part of [`Configuration`].
+///
+/// [`Configuration`]: ./struct.Configuration.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct TemplateConfig {
+    /// The name of the template.
+    pub name: String,
+
+    /// The names of the [`Tag`]s in the template.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub tags: Vec<String>,
+}
+
+/// Serializeable sub-structure used as part of [`Configuration`].
+///
+/// [`Configuration`]: ./struct.Configuration.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct AliasConfig {
+    /// The alternative name.
+    pub name: String,
+
+    /// The name of the [`Tag`] or group it stands for.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub tag: String,
+}
+
+/// Generates a JSON Schema describing the format of a [`Configuration`].
+///
+/// Editors can use this to validate configuration files as they are written.
+/// Optional fields are those absent from each object's `required` list.
+///
+/// [`Configuration`]: ./struct.Configuration.html
+#[cfg(feature = "schemars")]
+pub fn json_schema() -> String {
+    let schema = schema_for!(Configuration);
+    serde_json::to_string_pretty(&schema).expect("Unable to serialize JSON schema")
 }
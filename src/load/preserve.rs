@@ -0,0 +1,388 @@
+/*
+ * load/preserve.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::{find_tag, parse_requirement, parser_error, Configuration, TagConfig};
+use crate::prelude::*;
+use crate::Result;
+use std::mem;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, Value};
+
+/// Rewrites a TOML [`Configuration`] to match the given [`Engine`], preserving its formatting.
+///
+/// Only values which differ from the `Engine` are changed, so comments, key order,
+/// and the order of tags are kept as they were in `original`. Tags which no longer
+/// exist are removed, and new tags are appended at the end. Requirements which differ
+/// are rewritten as `requires`, with any alternatives as `requires_expr`.
+///
+/// The declared roles, aliases, and every tag's fields are updated. Role sets, groups,
+/// and templates are left as they are, as are computed tags, which cannot be configured.
+/// Tags or aliases written as an inline array are rewritten as an array of tables.
+/// Fails with [`Error::Parse`] if `original` is not a valid configuration.
+///
+/// Requires the `toml_edit` feature.
+///
+/// [`Configuration`]: ./struct.Configuration.html
+/// [`Engine`]: ../struct.Engine.html
+/// [`Error::Parse`]: ../enum.Error.html#variant.Parse
+pub fn to_toml_preserving(original: &str, engine: &Engine) -> Result<String> {
+    let config: Configuration = toml::from_str(original).map_err(parser_error)?;
+    let mut document = original.parse::<DocumentMut>().map_err(parser_error)?;
+
+    update_roles(&mut document, &config, engine);
+
+    let tables = tables_mut(&mut document, "tags")?;
+
+    // Update each tag in place, removing those which no longer exist.
+    // This goes from the end, so removals do not shift the tables still to visit.
+    let mut present = Vec::new();
+    for (i, tag_config) in config.tags.iter().enumerate().rev() {
+        match engine.get_specs().get_key_value(tag_config.name.as_str()) {
+            Some((tag, spec)) if !engine.is_computed(tag) => {
+                update_tag(tables.get_mut(i).unwrap(), tag_config, tag, spec, engine)?;
+                present.push(Tag::clone(tag));
+            }
+            Some(_) => (),
+            None => tables.remove(i),
+        }
+    }
+
+    for (tag, spec) in engine.specs_sorted() {
+        if !present.contains(tag) && !engine.is_computed(tag) {
+            let mut table = Table::new();
+            table.insert("name", toml_edit::value(&**tag));
+
+            let tag_config = TagConfig {
+                name: str!(&**tag),
+                ..TagConfig::default()
+            };
+
+            update_tag(&mut table, &tag_config, tag, spec, engine)?;
+            tables.push(table);
+        }
+    }
+
+    update_aliases(&mut document, &config, engine)?;
+    Ok(document.to_string())
+}
+
+fn update_roles(document: &mut DocumentMut, config: &Configuration, engine: &Engine) {
+    // Role set names are registered as roles, but are declared separately
+    let declared = |role: &Role| !engine.get_role_sets().contains_key(role);
+
+    let mut roles = config
+        .roles
+        .iter()
+        .filter(|name| match engine.get_roles().get(name.as_str()) {
+            Some(role) => declared(role),
+            None => false,
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut added = engine
+        .get_roles()
+        .iter()
+        .filter(|role| declared(role) && !config.roles.iter().any(|name| name == &***role))
+        .map(|role| str!(&**role))
+        .collect::<Vec<_>>();
+
+    added.sort();
+    roles.append(&mut added);
+
+    if roles != config.roles {
+        set_list(document.as_table_mut(), "roles", roles);
+    }
+}
+
+fn update_aliases(
+    document: &mut DocumentMut,
+    config: &Configuration,
+    engine: &Engine,
+) -> Result<()> {
+    let aliases = engine.get_aliases();
+    if config.aliases.is_empty() && aliases.is_empty() {
+        return Ok(());
+    }
+
+    let tables = tables_mut(document, "aliases")?;
+
+    // Same as tags, retarget in place and remove from the end
+    for (i, alias_config) in config.aliases.iter().enumerate().rev() {
+        match aliases.get(&alias_config.name) {
+            Some(tag) => {
+                if find_tag(engine, alias_config.tag.as_str()).ok().as_ref() != Some(tag) {
+                    set_value(tables.get_mut(i).unwrap(), "tag", Value::from(&**tag));
+                }
+            }
+            None => tables.remove(i),
+        }
+    }
+
+    let mut added = aliases
+        .iter()
+        .filter(|(name, _)| !config.aliases.iter().any(|alias| alias.name == **name))
+        .collect::<Vec<_>>();
+
+    added.sort();
+
+    for (name, tag) in added {
+        let mut table = Table::new();
+        table.insert("name", toml_edit::value(name.as_str()));
+        table.insert("tag", toml_edit::value(&**tag));
+        tables.push(table);
+    }
+
+    if tables.is_empty() {
+        document.remove("aliases");
+    }
+
+    Ok(())
+}
+
+fn update_tag(
+    table: &mut Table,
+    config: &TagConfig,
+    tag: &Tag,
+    spec: &TagSpec,
+    engine: &Engine,
+) -> Result<()> {
+    let resolve = |names: &Option<Vec<String>>| {
+        names
+            .iter()
+            .flatten()
+            .map(|name| find_tag(engine, name.as_str()).ok())
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let lists = [
+        ("groups", &config.groups, &spec.groups),
+        (
+            "publish_requires",
+            &config.publish_requires,
+            &spec.publish_required_tags,
+        ),
+        (
+            "conflicts_with",
+            &config.conflicts_with,
+            &spec.conflicting_tags,
+        ),
+        ("implies", &config.implies, &spec.implied_tags),
+    ];
+
+    for (key, names, tags) in &lists {
+        if resolve(names).as_ref() != Some(tags) {
+            set_list(table, key, tag_names(tags));
+        }
+    }
+
+    // Requirements are split between requires and requires_expr the same way
+    // as registering the configuration does, so equivalent ones are left alone
+    let mut required_tags = resolve(&config.requires);
+    let mut required_any_of = Some(Vec::new());
+
+    if let Some(ref expr) = config.requires_expr {
+        for names in parse_requirement(&config.name, expr)? {
+            let alternatives = resolve(&Some(names));
+
+            match (alternatives, &mut required_tags, &mut required_any_of) {
+                (Some(alternatives), Some(required_tags), Some(required_any_of)) => {
+                    if alternatives.len() > 1 {
+                        required_any_of.push(alternatives);
+                    } else if !required_tags.contains(&alternatives[0]) {
+                        required_tags.extend(alternatives);
+                    }
+                }
+                _ => required_any_of = None,
+            }
+        }
+    }
+
+    let requires_changed = required_tags.as_ref() != Some(&spec.required_tags)
+        || required_any_of.as_ref() != Some(&spec.required_any_of);
+
+    if requires_changed {
+        set_list(table, "requires", tag_names(&spec.required_tags));
+
+        if spec.required_any_of.is_empty() {
+            table.remove("requires_expr");
+        } else {
+            let expr = spec
+                .required_any_of
+                .iter()
+                .map(|alternatives| format!("({})", tag_names(alternatives).join(" | ")))
+                .collect::<Vec<_>>()
+                .join(" & ");
+
+            set_value(table, "requires_expr", Value::from(expr));
+        }
+    }
+
+    let roles = config
+        .roles
+        .iter()
+        .flatten()
+        .map(|name| engine.get_role(name.as_str()).ok())
+        .collect::<Option<Vec<_>>>();
+
+    if roles.as_deref() != Some(&*spec.needed_roles) {
+        let names = spec.needed_roles.iter().map(|role| str!(&**role)).collect();
+        set_list(table, "roles", names);
+    }
+
+    if config.public.unwrap_or(false) != spec.public {
+        set_value(table, "public", Value::from(spec.public));
+    }
+
+    if config.priority.unwrap_or(0) != spec.priority {
+        set_value(table, "priority", Value::from(i64::from(spec.priority)));
+    }
+
+    let enabled = engine.is_tag_enabled(tag);
+    if config.enabled.unwrap_or(true) != enabled {
+        set_value(table, "enabled", Value::from(enabled));
+    }
+
+    #[cfg(feature = "chrono")]
+    {
+        if config.expires_at != spec.expires_at {
+            match spec.expires_at {
+                Some(expires_at) => {
+                    let expires_at = expires_at.to_rfc3339();
+                    set_value(table, "expires_at", Value::from(expires_at));
+                }
+                None => {
+                    table.remove("expires_at");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Gets an array of tables to edit, creating it if absent.
+// Inline arrays, such as "tags = [{ name = "scp" }]", are converted to tables first.
+fn tables_mut<'a>(document: &'a mut DocumentMut, key: &str) -> Result<&'a mut ArrayOfTables> {
+    let table = document.as_table_mut();
+    let item = table
+        .entry(key)
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()));
+
+    if let Some(array) = item.as_array() {
+        *item = if array.is_empty() {
+            Item::ArrayOfTables(ArrayOfTables::new())
+        } else {
+            match mem::replace(item, Item::None).into_array_of_tables() {
+                Ok(tables) => Item::ArrayOfTables(tables),
+                Err(item) => item,
+            }
+        };
+
+        // Spacing around "tags = [...]" would otherwise end up inside "[[tags]]"
+        if let Some(mut key) = table.key_mut(key) {
+            key.leaf_decor_mut().clear();
+        }
+    }
+
+    match table.get_mut(key).and_then(Item::as_array_of_tables_mut) {
+        Some(tables) => Ok(tables),
+        None => Err(Error::Parse(
+            format!("{} must be an array of tables", key),
+            None,
+        )),
+    }
+}
+
+// Replaces a list, removing it instead if it is empty, as an omitted list is empty.
+fn set_list(table: &mut Table, key: &str, names: Vec<String>) {
+    if names.is_empty() {
+        table.remove(key);
+        return;
+    }
+
+    if let Some(array) = table.get_mut(key).and_then(Item::as_array_mut) {
+        if update_array(array, &names) {
+            return;
+        }
+    }
+
+    set_value(table, key, string_array(&names));
+}
+
+// Updates an array in place if only removals and additions at the end are needed,
+// so its layout is kept. Returns false if it must be replaced instead.
+fn update_array(array: &mut Array, names: &[String]) -> bool {
+    array.retain(|value| match value.as_str() {
+        Some(name) => names.iter().any(|other| other == name),
+        None => false,
+    });
+
+    let kept = array.len();
+    let in_order = array
+        .iter()
+        .zip(names)
+        .all(|(value, name)| value.as_str() == Some(name.as_str()));
+
+    if !in_order {
+        return false;
+    }
+
+    // If the items are on their own lines, new ones are as well
+    let decor = array
+        .iter()
+        .last()
+        .map(|value| value.decor())
+        .filter(
+            |decor| match decor.prefix().and_then(|prefix| prefix.as_str()) {
+                Some(prefix) => prefix.contains('\n'),
+                None => false,
+            },
+        )
+        .cloned();
+
+    for name in &names[kept..] {
+        match decor {
+            Some(ref decor) => {
+                let mut value = Value::from(name.as_str());
+                *value.decor_mut() = decor.clone();
+                array.push_formatted(value);
+            }
+            None => array.push(name.as_str()),
+        }
+    }
+
+    true
+}
+
+// Replaces a value, keeping any whitespace and comments around the old one.
+fn set_value(table: &mut Table, key: &str, mut value: Value) {
+    match table.get_mut(key) {
+        Some(item) => {
+            if let Some(existing) = item.as_value() {
+                *value.decor_mut() = existing.decor().clone();
+            }
+
+            *item = Item::Value(value);
+        }
+        None => {
+            table.insert(key, Item::Value(value));
+        }
+    }
+}
+
+fn string_array(names: &[String]) -> Value {
+    Value::Array(names.iter().collect::<Array>())
+}
+
+fn tag_names(tags: &[Tag]) -> Vec<String> {
+    tags.iter().map(|tag| str!(&**tag)).collect()
+}
@@ -0,0 +1,101 @@
+/*
+ * macros.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+/// Defines an enum for a fixed vocabulary of [`Tag`]s.
+///
+/// Each variant maps to the name of a tag registered in an [`Engine`],
+/// allowing tagsets to be built without stringly-typed names:
+///
+/// ```
+/// # #[macro_use] extern crate tag_guard;
+/// # use tag_guard::prelude::*;
+/// define_tags! {
+///     pub enum Primary {
+///         Scp => "scp",
+///         Tale => "tale",
+///         Hub => "hub",
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut engine = Engine::default();
+/// Primary::register_all(&mut engine).unwrap();
+///
+/// let tags = [Primary::Scp.as_tag()];
+/// engine.check_tags(&tags).unwrap();
+/// # }
+/// ```
+///
+/// The generated type provides `as_tag()`, `name()`, and `register_all()`,
+/// as well as `ALL` listing every variant. It also implements `Into<Tag>`.
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`Tag`]: ./struct.Tag.html
+#[macro_export]
+macro_rules! define_tags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident => $tag:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $(
+                #[allow(missing_docs)]
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// Every variant of this tag vocabulary.
+            #[allow(dead_code)]
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            /// Returns the name of the tag this variant refers to.
+            #[allow(dead_code)]
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $($name::$variant => $tag,)*
+                }
+            }
+
+            /// Returns the tag this variant refers to.
+            #[allow(dead_code)]
+            pub fn as_tag(&self) -> $crate::Tag {
+                $crate::Tag::new(self.name())
+            }
+
+            /// Registers every tag in this vocabulary which is not already present.
+            ///
+            /// Newly registered tags have a default specification.
+            #[allow(dead_code)]
+            pub fn register_all(engine: &mut $crate::Engine) -> $crate::Result<()> {
+                for variant in Self::ALL {
+                    if !engine.has_tag(variant.name()) {
+                        engine.add_tag(variant.name(), $crate::TemplateTagSpec::default())?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        impl From<$name> for $crate::Tag {
+            #[inline]
+            fn from(variant: $name) -> $crate::Tag {
+                variant.as_tag()
+            }
+        }
+    };
+}
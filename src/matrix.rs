@@ -0,0 +1,108 @@
+/*
+ * matrix.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use std::fmt::Write;
+
+/// A table of whether each pair of tags in an [`Engine`] may coexist.
+///
+/// Produced by [`Engine::compatibility_matrix`]. Tags are sorted by name,
+/// so the same configuration always produces the same matrix.
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`Engine::compatibility_matrix`]: ./struct.Engine.html#method.compatibility_matrix
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityMatrix {
+    tags: Vec<Tag>,
+    compatible: Vec<bool>,
+}
+
+impl CompatibilityMatrix {
+    /// Gets the tags making up the rows and columns of the matrix, in order.
+    #[inline]
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Determines whether the two tags may coexist.
+    ///
+    /// Returns `None` if either tag is not part of the matrix.
+    pub fn get(&self, a: &Tag, b: &Tag) -> Option<bool> {
+        let row = self.tags.binary_search(a).ok()?;
+        let column = self.tags.binary_search(b).ok()?;
+        Some(self.compatible[row * self.tags.len() + column])
+    }
+
+    /// Renders the matrix as CSV, with a header row and column of tag names.
+    ///
+    /// Each cell is either `yes` or `no`. Names containing commas or quotes are quoted.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        for tag in &self.tags {
+            csv.push(',');
+            write_field(&mut csv, tag);
+        }
+
+        csv.push('\n');
+
+        for (row, tag) in self.tags.iter().enumerate() {
+            write_field(&mut csv, tag);
+
+            for column in 0..self.tags.len() {
+                let compatible = self.compatible[row * self.tags.len() + column];
+                csv.push_str(if compatible { ",yes" } else { ",no" });
+            }
+
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl Engine {
+    /// Computes whether each pair of proper tags may coexist, as per [`can_coexist`].
+    ///
+    /// Tag groups are not included. This checks every pair of tags,
+    /// so it is `O(n²)` in the number of tags.
+    ///
+    /// [`can_coexist`]: #method.can_coexist
+    pub fn compatibility_matrix(&self) -> CompatibilityMatrix {
+        let tags = self
+            .specs_sorted()
+            .into_iter()
+            .map(|(tag, _)| Tag::clone(tag))
+            .collect::<Vec<_>>();
+
+        let len = tags.len();
+        let mut compatible = vec![true; len * len];
+
+        for (row, a) in tags.iter().enumerate() {
+            for (column, b) in tags.iter().enumerate().skip(row + 1) {
+                let result = self.can_coexist(a, b).unwrap_or(false);
+                compatible[row * len + column] = result;
+                compatible[column * len + row] = result;
+            }
+        }
+
+        CompatibilityMatrix { tags, compatible }
+    }
+}
+
+fn write_field(csv: &mut String, tag: &Tag) {
+    if tag.contains(&[',', '"', '\n'][..]) {
+        write!(csv, "\"{}\"", tag.replace('"', "\"\"")).unwrap();
+    } else {
+        csv.push_str(tag);
+    }
+}
@@ -0,0 +1,53 @@
+/*
+ * policy.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::{Error, Result};
+
+/// Rules which the names of tags, groups, and roles must follow.
+///
+/// Applied by the [`Engine`] whenever a new name is registered. The default
+/// policy only requires that names are non-empty.
+///
+/// [`Engine`]: ./struct.Engine.html
+#[derive(Debug, Copy, Clone)]
+pub struct NamePolicy {
+    /// The maximum length of a name, in bytes. `None` means there is no limit.
+    pub max_length: Option<usize>,
+
+    /// A predicate which every character in a name must satisfy.
+    pub allowed_char: fn(char) -> bool,
+}
+
+impl NamePolicy {
+    /// Checks that the given name complies with this policy.
+    pub fn check(&self, name: &str) -> Result<()> {
+        let too_long = match self.max_length {
+            Some(max_length) => name.len() > max_length,
+            None => false,
+        };
+
+        if name.is_empty() || too_long || !name.chars().all(self.allowed_char) {
+            return Err(Error::InvalidName(str!(name)));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        NamePolicy {
+            max_length: None,
+            allowed_char: |_| true,
+        }
+    }
+}
@@ -0,0 +1,109 @@
+/*
+ * prune.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use std::collections::HashSet;
+
+/// The registrations removed by [`Engine::prune`].
+///
+/// All lists are sorted.
+///
+/// [`Engine::prune`]: ./struct.Engine.html#method.prune
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Roles which no tag needed.
+    pub roles: Vec<Role>,
+
+    /// Tag groups which had no members.
+    pub groups: Vec<Tag>,
+}
+
+impl PruneReport {
+    /// Determines if nothing was removed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.groups.is_empty()
+    }
+}
+
+impl Engine {
+    /// Gets every [`Role`] needed by at least one tag.
+    ///
+    /// Unlike [`get_roles`], this excludes registered roles which do not gate any tag.
+    /// These are the roles [`prune`] keeps.
+    ///
+    /// [`Role`]: ./struct.Role.html
+    /// [`get_roles`]: #method.get_roles
+    /// [`prune`]: #method.prune
+    pub fn all_needed_roles(&self) -> HashSet<Role> {
+        self.get_specs()
+            .values()
+            .flat_map(|spec| spec.needed_roles.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Deletes all roles which no tag needs, and all tag groups which have no members.
+    ///
+    /// Groups which are still referenced as a requirement or conflict are kept, as are
+    /// proper tags which also act as groups. Since empty registrations may be intentional
+    /// scaffolding, this is never done automatically.
+    pub fn prune(&mut self) -> PruneReport {
+        let mut report = PruneReport::default();
+
+        let needed_roles = self.all_needed_roles();
+        for role in self.get_roles() {
+            if !needed_roles.contains(role) {
+                report.roles.push(Role::clone(role));
+            }
+        }
+
+        for group in self.get_tags() {
+            if self.get_specs().contains_key(group) {
+                continue;
+            }
+
+            let used = self.get_specs().values().any(|spec| {
+                spec.groups.contains(group)
+                    || spec.required_tags.contains(group)
+                    || spec.required_groups.contains(group)
+                    || spec.required_any_of.iter().flatten().any(|g| g == group)
+                    || spec.publish_required_tags.contains(group)
+                    || spec.conflicting_tags.contains(group)
+            });
+
+            // Subgroups count as members of their parents
+            let used = used
+                || !self.get_parent_groups(group).is_empty()
+                || self
+                    .get_tags()
+                    .iter()
+                    .any(|child| self.get_parent_groups(child).contains(group));
+
+            if !used {
+                report.groups.push(Tag::clone(group));
+            }
+        }
+
+        for role in &report.roles {
+            self.delete_role(role);
+        }
+
+        for group in &report.groups {
+            self.delete_group(group);
+        }
+
+        report.roles.sort();
+        report.groups.sort();
+        report
+    }
+}
@@ -0,0 +1,122 @@
+/*
+ * resolver.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::{Error, Result};
+
+/// An external source of tags, consulted for tags which are not registered in an [`Engine`].
+///
+/// This allows a large set of tags to be loaded on demand, rather than all up front.
+/// Set one with [`Engine::set_resolver`], and use [`Engine::check_tags_resolving`]
+/// to pull in any tags it knows of.
+///
+/// Any tags or groups referenced by a resolved specification must themselves be
+/// registered or resolvable, otherwise resolution fails.
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`Engine::set_resolver`]: ./struct.Engine.html#method.set_resolver
+/// [`Engine::check_tags_resolving`]: ./struct.Engine.html#method.check_tags_resolving
+pub trait TagResolver {
+    /// Whether a tag or tag group with the given name exists.
+    fn exists(&self, name: &str) -> bool;
+
+    /// Gets the specification of the proper tag with the given name.
+    ///
+    /// Returns `None` for tag groups and names which do not exist.
+    fn spec(&self, name: &str) -> Option<TagSpec>;
+}
+
+impl Engine {
+    /// Registers the tag with the given name, consulting the [`TagResolver`] if needed.
+    ///
+    /// Tags which are already registered are returned as-is. Otherwise, the resolved
+    /// specification is registered, along with any tags, groups, and roles it refers to,
+    /// so later lookups do not consult the resolver again.
+    /// Fails with [`Error::NoSuchTag`] if the name cannot be resolved.
+    ///
+    /// If resolution fails, nothing it registered along the way is kept.
+    ///
+    /// [`TagResolver`]: ./trait.TagResolver.html
+    /// [`Error::NoSuchTag`]: ./enum.Error.html#variant.NoSuchTag
+    pub fn resolve_tag(&mut self, name: &str) -> Result<Tag> {
+        if let Ok(tag) = self.get_tag(name) {
+            return Ok(tag);
+        }
+
+        let snapshot = self.snapshot();
+        let result = self.resolve_tag_inner(name);
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+
+        result
+    }
+
+    fn resolve_tag_inner(&mut self, name: &str) -> Result<Tag> {
+        if let Ok(tag) = self.get_tag(name) {
+            return Ok(tag);
+        }
+
+        let resolver = match self.get_resolver() {
+            Some(resolver) if resolver.exists(name) => resolver,
+            _ => return Err(Error::NoSuchTag(str!(name))),
+        };
+
+        let spec = match resolver.spec(name) {
+            Some(spec) => spec,
+            None => return self.add_group(name),
+        };
+
+        if *spec.tag_ref() != name {
+            return Err(Error::Other("Resolved specification has a different tag"));
+        }
+
+        for role in spec.needed_roles.iter() {
+            if !self.has_role(&**role) {
+                return Err(Error::MissingRole(Role::clone(role)));
+            }
+        }
+
+        // Register first, so references back to this tag are already satisfied
+        let tag = self.add_tag(name, TemplateTagSpec::from(&spec))?;
+
+        let references = spec
+            .required_tags
+            .iter()
+            .chain(&spec.required_groups)
+            .chain(spec.required_any_of.iter().flatten())
+            .chain(&spec.publish_required_tags)
+            .chain(&spec.conflicting_tags)
+            .chain(&spec.implied_tags)
+            .chain(&spec.groups);
+
+        for other in references {
+            self.resolve_tag_inner(other)?;
+        }
+
+        Ok(tag)
+    }
+
+    /// Validates the given list of tags, first resolving any which are not registered.
+    ///
+    /// See [`resolve_tag`] and [`check_tags`].
+    ///
+    /// [`resolve_tag`]: #method.resolve_tag
+    /// [`check_tags`]: #method.check_tags
+    pub fn check_tags_resolving(&mut self, tags: &[Tag]) -> Result<()> {
+        for tag in tags {
+            self.resolve_tag(tag)?;
+        }
+
+        self.check_tags(tags)
+    }
+}
@@ -0,0 +1,314 @@
+/*
+ * source.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use std::fmt::Write;
+
+impl Engine {
+    /// Generates Rust source code which rebuilds this `Engine` through its mutation methods.
+    ///
+    /// The output is a sequence of statements calling methods on a mutable `Engine` named
+    /// `engine`, each propagating errors with `?`, as in a test fixture. Roles are registered
+    /// first, then groups, then tags, followed by the relationships between them and finally
+    /// templates, so each call only refers to things which already exist. Everything is
+    /// emitted in sorted order, so the same configuration always produces the same source.
+    ///
+    /// The [`NamePolicy`], tag resolver, hooks, and computed tag rules cannot be expressed
+    /// this way. Computed tags are noted in comments instead, and must be registered separately.
+    ///
+    /// [`NamePolicy`]: ./struct.NamePolicy.html
+    pub fn to_builder_source(&self) -> String {
+        let mut source = String::new();
+
+        // Roles
+        let mut roles = self.get_roles().iter().collect::<Vec<_>>();
+        roles.sort();
+
+        for role in &roles {
+            line(
+                &mut source,
+                format_args!("engine.add_role({:?})?;", &***role),
+            );
+        }
+
+        for role in &roles {
+            if let Some(members) = self.get_role_set(role) {
+                let members = members.iter().map(role_expr).collect::<Vec<_>>();
+                line(
+                    &mut source,
+                    format_args!(
+                        "engine.add_role_set({:?}, vec![{}])?;",
+                        &***role,
+                        members.join(", "),
+                    ),
+                );
+            }
+        }
+
+        for role in &roles {
+            for inherits in self.get_inherited_roles(role) {
+                line(
+                    &mut source,
+                    format_args!(
+                        "engine.add_role_inheritance(&{}, &{})?;",
+                        role_expr(role),
+                        role_expr(inherits),
+                    ),
+                );
+            }
+        }
+
+        for role in self.get_default_roles() {
+            let role = role_expr(role);
+            line(
+                &mut source,
+                format_args!("engine.add_default_role(&{})?;", role),
+            );
+        }
+
+        // Groups
+        let mut groups = self
+            .get_tags()
+            .iter()
+            .filter(|tag| self.is_group(tag))
+            .collect::<Vec<_>>();
+        groups.sort();
+
+        for group in &groups {
+            if !self.get_specs().contains_key(*group) {
+                line(
+                    &mut source,
+                    format_args!("engine.add_group({:?})?;", &***group),
+                );
+            }
+        }
+
+        // Tags
+        for (tag, spec) in self.specs_sorted() {
+            if self.is_computed(tag) {
+                line(
+                    &mut source,
+                    format_args!("// {:?} is a computed tag, see add_computed_tag()", &**tag),
+                );
+                continue;
+            }
+
+            write_add_tag(&mut source, tag, spec);
+        }
+
+        let mut aliases = self.get_aliases().iter().collect::<Vec<_>>();
+        aliases.sort();
+
+        for (alias, tag) in aliases {
+            let tag = tag_expr(tag);
+            line(
+                &mut source,
+                format_args!("engine.add_alias({:?}, &{})?;", alias, tag),
+            );
+        }
+
+        // Relationships
+        for group in &groups {
+            for parent in self.get_parent_groups(group) {
+                line(
+                    &mut source,
+                    format_args!(
+                        "engine.add_subgroup(&{}, &{})?;",
+                        tag_expr(parent),
+                        tag_expr(group),
+                    ),
+                );
+            }
+
+            if let Some(cardinality) = self.get_group_cardinality(group) {
+                line(
+                    &mut source,
+                    format_args!(
+                        "engine.set_group_cardinality(&{}, Cardinality {{ min: {}, max: {:?} }})?;",
+                        tag_expr(group),
+                        cardinality.min,
+                        cardinality.max,
+                    ),
+                );
+            }
+
+            let display_name = self.display_name(group);
+            if !self.get_specs().contains_key(*group) && display_name != &***group {
+                line(
+                    &mut source,
+                    format_args!(
+                        "engine.set_display_name(&{}, Some(String::from({:?})))?;",
+                        tag_expr(group),
+                        display_name,
+                    ),
+                );
+            }
+        }
+
+        for set in self.get_exclusion_sets() {
+            let tags = tag_list(set);
+            line(
+                &mut source,
+                format_args!("engine.add_exclusion_set({})?;", tags),
+            );
+        }
+
+        for group in self.get_synonym_groups() {
+            let tags = tag_list(group);
+            line(
+                &mut source,
+                format_args!("engine.add_synonym_group({})?;", tags),
+            );
+        }
+
+        for (tag, _) in self.specs_sorted() {
+            if !self.is_tag_enabled(tag) {
+                let tag = tag_expr(tag);
+                line(
+                    &mut source,
+                    format_args!("engine.set_tag_enabled(&{}, false)?;", tag),
+                );
+            }
+        }
+
+        // Templates
+        let mut templates = self.get_templates().iter().collect::<Vec<_>>();
+        templates.sort();
+
+        for (name, tags) in templates {
+            line(
+                &mut source,
+                format_args!("engine.register_template({:?}, {})?;", name, tag_list(tags)),
+            );
+        }
+
+        // Strict mode last, as exclusive groups are only marked after tags are added
+        if self.is_strict() {
+            line(&mut source, format_args!("engine.set_strict(true);"));
+        }
+
+        source
+    }
+}
+
+fn write_add_tag(source: &mut String, tag: &Tag, spec: &TagSpec) {
+    let mut fields = String::new();
+
+    macro_rules! field {
+        ($name:ident, $value:expr) => {
+            let _ = writeln!(fields, "        {}: {},", stringify!($name), $value);
+        };
+    }
+
+    if !spec.required_tags.is_empty() {
+        field!(required_tags, tag_list(&spec.required_tags));
+    }
+
+    if !spec.required_groups.is_empty() {
+        field!(required_groups, tag_list(&spec.required_groups));
+    }
+
+    if !spec.required_any_of.is_empty() {
+        let lists = spec
+            .required_any_of
+            .iter()
+            .map(|alternatives| tag_list(alternatives))
+            .collect::<Vec<_>>();
+
+        field!(required_any_of, format!("vec![{}]", lists.join(", ")));
+    }
+
+    if !spec.publish_required_tags.is_empty() {
+        field!(publish_required_tags, tag_list(&spec.publish_required_tags));
+    }
+
+    if !spec.conflicting_tags.is_empty() {
+        field!(conflicting_tags, tag_list(&spec.conflicting_tags));
+    }
+
+    if !spec.implied_tags.is_empty() {
+        field!(implied_tags, tag_list(&spec.implied_tags));
+    }
+
+    if !spec.needed_roles.is_empty() {
+        let roles = spec.needed_roles.iter().map(role_expr).collect::<Vec<_>>();
+        field!(needed_roles, format!("vec![{}]", roles.join(", ")));
+    }
+
+    if spec.public {
+        field!(public, true);
+    }
+
+    if !spec.groups.is_empty() {
+        field!(groups, tag_list(&spec.groups));
+    }
+
+    if spec.is_also_group {
+        field!(is_also_group, true);
+    }
+
+    if spec.priority != 0 {
+        field!(priority, spec.priority);
+    }
+
+    if let Some(ref display_name) = spec.display_name {
+        field!(
+            display_name,
+            format!("Some(String::from({:?}))", display_name)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    {
+        if let Some(expires_at) = spec.expires_at {
+            let expires_at = expires_at.to_rfc3339();
+            field!(
+                expires_at,
+                format!("Some({:?}.parse().unwrap())", expires_at)
+            );
+        }
+    }
+
+    if fields.is_empty() {
+        let tag = &**tag;
+        line(
+            source,
+            format_args!("engine.add_tag({:?}, TemplateTagSpec::default())?;", tag),
+        );
+        return;
+    }
+
+    let _ = write!(
+        source,
+        "engine.add_tag(\n    {:?},\n    TemplateTagSpec {{\n{}        ..TemplateTagSpec::default()\n    }},\n)?;\n",
+        &**tag,
+        fields,
+    );
+}
+
+#[inline]
+fn line(source: &mut String, args: std::fmt::Arguments) {
+    let _ = writeln!(source, "{}", args);
+}
+
+fn tag_expr(tag: &Tag) -> String {
+    format!("Tag::new({:?})", &**tag)
+}
+
+fn role_expr(role: &Role) -> String {
+    format!("Role::new({:?})", &**role)
+}
+
+fn tag_list(tags: &[Tag]) -> String {
+    let tags = tags.iter().map(tag_expr).collect::<Vec<_>>();
+    format!("vec![{}]", tags.join(", "))
+}
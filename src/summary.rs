@@ -0,0 +1,129 @@
+/*
+ * summary.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::prelude::*;
+use crate::Result;
+
+/// The net effect of a valid tag change, from [`Engine::summarize_change`].
+///
+/// [`Engine::summarize_change`]: ./struct.Engine.html#method.summarize_change
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSummary {
+    /// How many tags were newly added, not counting those already present.
+    pub added: usize,
+
+    /// How many tags were removed, not counting those which were not present.
+    pub removed: usize,
+
+    /// Groups whose number of present members changed, as `(group, before, after)`.
+    ///
+    /// Sorted by group.
+    pub groups: Vec<(Tag, usize, usize)>,
+
+    /// Roles from the changed tags' `needed_roles` which the user held, sorted.
+    ///
+    /// These are the roles which permitted the change. Tags anyone may change
+    /// do not contribute any.
+    pub roles: Vec<Role>,
+}
+
+impl ChangeSummary {
+    /// Determines if the change had no effect on the tagset.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0
+    }
+}
+
+impl Engine {
+    /// Validates a tag change as [`check_tag_changes`] does, then summarizes its effect.
+    ///
+    /// Fails with the same error if the change is not valid. Tags listed in both
+    /// `added_tags` and `removed_tags` cancel out first, as in [`commit_changes`].
+    ///
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    /// [`commit_changes`]: #method.commit_changes
+    pub fn summarize_change(
+        &self,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+    ) -> Result<ChangeSummary> {
+        let (added_tags, removed_tags) = self.normalize_change(added_tags, removed_tags);
+        self.check_tag_changes(tags, &added_tags, &removed_tags, roles)?;
+
+        let mut added = added_tags
+            .iter()
+            .filter(|tag| !tags.contains(tag))
+            .collect::<Vec<_>>();
+
+        let mut removed = removed_tags
+            .iter()
+            .filter(|tag| tags.contains(tag))
+            .collect::<Vec<_>>();
+
+        added.sort();
+        added.dedup();
+        removed.sort();
+        removed.dedup();
+
+        let after = tags
+            .iter()
+            .filter(|tag| !removed.contains(tag))
+            .chain(added.iter().cloned())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut groups = self
+            .get_tags()
+            .iter()
+            .filter(|group| self.is_group(group))
+            .filter_map(|group| {
+                let before = self.count_tag_lenient(group, tags);
+                let count = self.count_tag_lenient(group, &after);
+
+                if before == count {
+                    None
+                } else {
+                    Some((Tag::clone(group), before, count))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        groups.sort();
+
+        let held = self.effective_roles(roles);
+        let mut exercised = Vec::new();
+
+        for tag in added.iter().chain(&removed) {
+            let spec = self.get_spec(tag)?;
+            if spec.public {
+                continue;
+            }
+
+            for role in spec.needed_roles.iter() {
+                if held.contains(role) && !exercised.contains(role) {
+                    exercised.push(Role::clone(role));
+                }
+            }
+        }
+
+        exercised.sort();
+        Ok(ChangeSummary {
+            added: added.len(),
+            removed: removed.len(),
+            groups,
+            roles: exercised,
+        })
+    }
+}
@@ -12,8 +12,10 @@
 
 mod object;
 mod role;
+mod set;
 mod spec;
 
 pub use self::object::Tag;
 pub use self::role::Role;
+pub use self::set::TagSet;
 pub use self::spec::{TagSpec, TemplateTagSpec};
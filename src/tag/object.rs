@@ -11,7 +11,10 @@
  */
 
 use easy_strings::EZString;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt::{self, Debug, Display};
 use std::ops::Deref;
 
@@ -72,6 +75,59 @@ impl Deref for Tag {
     }
 }
 
+impl PartialOrd for Tag {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tag {
+    fn cmp(&self, other: &Self) -> Ordering {
+        str::cmp(self, other)
+    }
+}
+
+impl PartialEq<str> for Tag {
+    fn eq(&self, other: &str) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<&str> for Tag {
+    fn eq(&self, other: &&str) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<Tag> for str {
+    fn eq(&self, other: &Tag) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<Tag> for &str {
+    fn eq(&self, other: &Tag) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name.is_empty() {
+            return Err(D::Error::custom("Empty tag names are not permitted"));
+        }
+
+        Ok(Tag::new(name))
+    }
+}
+
 impl Debug for Tag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Tag({:?})", *self.0)
@@ -80,6 +136,6 @@ impl Debug for Tag {
 
 impl Display for Tag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &self)
+        f.write_str(self)
     }
 }
@@ -11,7 +11,10 @@
  */
 
 use easy_strings::EZString;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt::{self, Debug, Display};
 use std::ops::Deref;
 
@@ -72,6 +75,59 @@ impl Deref for Role {
     }
 }
 
+impl PartialOrd for Role {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Role {
+    fn cmp(&self, other: &Self) -> Ordering {
+        str::cmp(self, other)
+    }
+}
+
+impl PartialEq<str> for Role {
+    fn eq(&self, other: &str) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<&str> for Role {
+    fn eq(&self, other: &&str) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<Role> for str {
+    fn eq(&self, other: &Role) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl PartialEq<Role> for &str {
+    fn eq(&self, other: &Role) -> bool {
+        <str as PartialEq>::eq(self, other)
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name.is_empty() {
+            return Err(D::Error::custom("Empty role names are not permitted"));
+        }
+
+        Ok(Role::new(name))
+    }
+}
+
 impl Debug for Role {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Role({:?})", *self.0)
@@ -80,6 +136,6 @@ impl Debug for Role {
 
 impl Display for Role {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &self)
+        f.write_str(self)
     }
 }
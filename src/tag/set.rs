@@ -0,0 +1,84 @@
+/*
+ * tag/set.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::{Tag, TagSpec};
+use std::ops::Deref;
+
+/// A list of tags which has been validated against an [`Engine`].
+///
+/// Created by [`Engine::make_tagset`], which ensures every tag is registered,
+/// none are duplicated, and no bare groups are present. The set keeps each tag's
+/// specification, borrowing the engine, so these invariants hold for as long as it exists.
+///
+/// [`check_tagset`] and [`check_tagset_changes`] use those specifications directly,
+/// skipping the lookups [`check_tags`] and [`check_tag_changes`] make for every tag.
+/// This also dereferences to `[Tag]`, so it can be passed to any method accepting a tag slice.
+///
+/// [`Engine`]: ./struct.Engine.html
+/// [`Engine::make_tagset`]: ./struct.Engine.html#method.make_tagset
+/// [`check_tagset`]: ./struct.Engine.html#method.check_tagset
+/// [`check_tagset_changes`]: ./struct.Engine.html#method.check_tagset_changes
+/// [`check_tags`]: ./struct.Engine.html#method.check_tags
+/// [`check_tag_changes`]: ./struct.Engine.html#method.check_tag_changes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagSet<'e> {
+    tags: Vec<Tag>,
+    specs: Vec<&'e TagSpec>,
+}
+
+impl<'e> TagSet<'e> {
+    #[inline]
+    pub(crate) fn new_unchecked(tags: Vec<Tag>, specs: Vec<&'e TagSpec>) -> Self {
+        TagSet { tags, specs }
+    }
+
+    /// Borrows the tags in this set, in their original order.
+    #[inline]
+    pub fn as_slice(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Borrows the specifications of the tags in this set, in the same order.
+    #[inline]
+    pub fn specs(&self) -> &[&'e TagSpec] {
+        &self.specs
+    }
+
+    /// Consumes the set, returning the underlying list of tags.
+    #[inline]
+    pub fn into_inner(self) -> Vec<Tag> {
+        self.tags
+    }
+}
+
+impl AsRef<[Tag]> for TagSet<'_> {
+    #[inline]
+    fn as_ref(&self) -> &[Tag] {
+        &self.tags
+    }
+}
+
+impl Deref for TagSet<'_> {
+    type Target = [Tag];
+
+    #[inline]
+    fn deref(&self) -> &[Tag] {
+        &self.tags
+    }
+}
+
+impl From<TagSet<'_>> for Vec<Tag> {
+    #[inline]
+    fn from(tagset: TagSet) -> Self {
+        tagset.tags
+    }
+}
@@ -11,7 +11,13 @@
  */
 
 use crate::prelude::*;
-use crate::{Error, Result};
+use crate::{CheckFlags, Error, Result};
+use std::collections::HashSet;
+use std::slice;
+use std::sync::Arc;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
 
 /// Input specification of a tag's requirements.
 ///
@@ -19,30 +25,81 @@ use crate::{Error, Result};
 ///
 /// [`Engine`]: ./struct.Engine.html
 /// [`TagSpec`]: ./struct.TagSpec.html
-#[derive(Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
 pub struct TemplateTagSpec {
     /// Which [`Tag`]s or tag groups must also be present for before this one may be applied.
     ///
+    /// Requiring a group this tag is a member of has no effect, as the tag satisfies
+    /// the requirement by itself. [`Engine::lint`] reports this as a likely mistake.
+    ///
+    /// [`Engine::lint`]: ./struct.Engine.html#method.lint
     /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub required_tags: Vec<Tag>,
 
+    /// Tag groups which must each have at least one member present before this one may be applied.
+    ///
+    /// See [`Engine::add_required_group_set`].
+    ///
+    /// [`Engine::add_required_group_set`]: ./struct.Engine.html#method.add_required_group_set
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_groups: Vec<Tag>,
+
+    /// Alternatives of [`Tag`]s or tag groups, where at least one of each list must be present.
+    ///
+    /// This expresses requirements which can be met in several ways, without needing a group.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_any_of: Vec<Vec<Tag>>,
+
+    /// Which [`Tag`]s or tag groups must also be present before an object with this tag
+    /// may be published, but not to save it as a draft.
+    ///
+    /// Only enforced by [`Engine::check_tags_for_publish`].
+    ///
+    /// [`Engine::check_tags_for_publish`]: ./struct.Engine.html#method.check_tags_for_publish
+    /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub publish_required_tags: Vec<Tag>,
+
     /// Which [`Tag`]s or tag groups may not be present if this one is to be applied.
     ///
     /// Note that specifying a tag group that this tag is a member of is not contradictory,
     /// as the [`Engine`] will instead ensure this tag is the only one of its group present.
+    /// This tag itself is never counted, so any other member of the group is a conflict,
+    /// even if it also satisfies one of this tag's requirements.
     ///
     /// [`Engine`]: ./struct.Engine.html
     /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub conflicting_tags: Vec<Tag>,
 
+    /// Which [`Tag`]s are implied by this one, and should be added alongside it.
+    ///
+    /// Implications are only applied by [`Engine::check_tags_closure`].
+    ///
+    /// [`Engine::check_tags_closure`]: ./struct.Engine.html#method.check_tags_closure
+    /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub implied_tags: Vec<Tag>,
+
     /// A list of [`Role`]s which may add or remove this tag.
     ///
     /// To "lock" a tag, you can set this to either moderator-only, or create a specific role that
     /// nobody has access to.
     ///
     /// [`Role`]: ./struct.Role.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub needed_roles: Vec<Role>,
 
+    /// Whether anyone may add or remove this tag, regardless of `needed_roles`.
+    ///
+    /// Makes the absence of role restrictions explicit, rather than relying on
+    /// `needed_roles` being empty.
+    pub public: bool,
+
     /// A list of [`Tag`] groups this tag is a member of.
     ///
     /// If a tag group is checked for membership, then the presence of this tag will cause it to
@@ -50,7 +107,36 @@ pub struct TemplateTagSpec {
     /// tag rules.
     ///
     /// [`Tag`]: ./struct.Tag.html
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub groups: Vec<Tag>,
+
+    /// Whether this tag also acts as a tag group.
+    ///
+    /// If set, other tags may list this one in their `groups`, and checking for this tag
+    /// will count both its own presence and that of its members.
+    pub is_also_group: bool,
+
+    /// How important this tag is relative to others, defaulting to `0`.
+    ///
+    /// When two tags conflict, the one with the lower priority is proposed for removal.
+    pub priority: i32,
+
+    /// A human-readable label to show in place of the tag's canonical name.
+    ///
+    /// See [`Engine::display_name`].
+    ///
+    /// [`Engine::display_name`]: ./struct.Engine.html#method.display_name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// When this tag stops being relevant, if ever.
+    ///
+    /// This does not affect checks, see [`Engine::expired_tags`].
+    ///
+    /// [`Engine::expired_tags`]: ./struct.Engine.html#method.expired_tags
+    #[cfg(feature = "chrono")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// A [`TemplateTagSpec`] that has been associated with a particular [`Tag`].
@@ -60,31 +146,76 @@ pub struct TemplateTagSpec {
 /// [`Engine`]: ./struct.Engine.html
 /// [`Tag`]: ./struct.Tag.html
 /// [`TemplateTagSpec`]: ./struct.TemplateTagSpec.html
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TagSpec {
     tag: Tag,
 
     /// Which [`Tag`]s or tag groups must also be present for before this one may be applied.
     ///
+    /// Requiring a group this tag is a member of has no effect, as the tag satisfies
+    /// the requirement by itself. [`Engine::lint`] reports this as a likely mistake.
+    ///
+    /// [`Engine::lint`]: ./struct.Engine.html#method.lint
     /// [`Tag`]: ./struct.Tag.html
     pub required_tags: Vec<Tag>,
 
+    /// Tag groups which must each have at least one member present before this one may be applied.
+    ///
+    /// See [`Engine::add_required_group_set`].
+    ///
+    /// [`Engine::add_required_group_set`]: ./struct.Engine.html#method.add_required_group_set
+    pub required_groups: Vec<Tag>,
+
+    /// Alternatives of [`Tag`]s or tag groups, where at least one of each list must be present.
+    ///
+    /// This expresses requirements which can be met in several ways, without needing a group.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    pub required_any_of: Vec<Vec<Tag>>,
+
+    /// Which [`Tag`]s or tag groups must also be present before an object with this tag
+    /// may be published, but not to save it as a draft.
+    ///
+    /// Only enforced by [`Engine::check_tags_for_publish`].
+    ///
+    /// [`Engine::check_tags_for_publish`]: ./struct.Engine.html#method.check_tags_for_publish
+    /// [`Tag`]: ./struct.Tag.html
+    pub publish_required_tags: Vec<Tag>,
+
     /// Which [`Tag`]s or tag groups may not be present if this one is to be applied.
     ///
     /// Note that specifying a tag group that this tag is a member of is not contradictory,
     /// as the [`Engine`] will instead ensure this tag is the only one of its group present.
+    /// This tag itself is never counted, so any other member of the group is a conflict,
+    /// even if it also satisfies one of this tag's requirements.
     ///
     /// [`Engine`]: ./struct.Engine.html
     /// [`Tag`]: ./struct.Tag.html
     pub conflicting_tags: Vec<Tag>,
 
+    /// Which [`Tag`]s are implied by this one, and should be added alongside it.
+    ///
+    /// Implications are only applied by [`Engine::check_tags_closure`].
+    ///
+    /// [`Engine::check_tags_closure`]: ./struct.Engine.html#method.check_tags_closure
+    /// [`Tag`]: ./struct.Tag.html
+    pub implied_tags: Vec<Tag>,
+
     /// A list of [`Role`]s which may add or remove this tag.
     ///
     /// To "lock" a tag, you can set this to either moderator-only, or create a specific role that
     /// nobody has access to.
     ///
+    /// Shared so that errors referencing this list do not need to copy it.
+    ///
     /// [`Role`]: ./struct.Role.html
-    pub needed_roles: Vec<Role>,
+    pub needed_roles: Arc<[Role]>,
+
+    /// Whether anyone may add or remove this tag, regardless of `needed_roles`.
+    ///
+    /// Makes the absence of role restrictions explicit, rather than relying on
+    /// `needed_roles` being empty.
+    pub public: bool,
 
     /// A list of [`Tag`] groups this tag is a member of.
     ///
@@ -94,6 +225,32 @@ pub struct TagSpec {
     ///
     /// [`Tag`]: ./struct.Tag.html
     pub groups: Vec<Tag>,
+
+    /// Whether this tag also acts as a tag group.
+    ///
+    /// If set, other tags may list this one in their `groups`, and checking for this tag
+    /// will count both its own presence and that of its members.
+    pub is_also_group: bool,
+
+    /// How important this tag is relative to others, defaulting to `0`.
+    ///
+    /// When two tags conflict, the one with the lower priority is proposed for removal.
+    pub priority: i32,
+
+    /// A human-readable label to show in place of the tag's canonical name.
+    ///
+    /// See [`Engine::display_name`].
+    ///
+    /// [`Engine::display_name`]: ./struct.Engine.html#method.display_name
+    pub display_name: Option<String>,
+
+    /// When this tag stops being relevant, if ever.
+    ///
+    /// This does not affect checks, see [`Engine::expired_tags`].
+    ///
+    /// [`Engine::expired_tags`]: ./struct.Engine.html#method.expired_tags
+    #[cfg(feature = "chrono")]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl TagSpec {
@@ -105,6 +262,17 @@ impl TagSpec {
         Tag::clone(&self.tag)
     }
 
+    /// Borrows the [`Tag`] associated with this specification.
+    ///
+    /// Unlike [`tag`], this does not produce a new owned handle.
+    ///
+    /// [`Tag`]: ./struct.Tag.html
+    /// [`tag`]: #method.tag
+    #[inline]
+    pub fn tag_ref(&self) -> &Tag {
+        &self.tag
+    }
+
     /// Creates a new instance using the given [`Tag`] and [`TemplateTagSpec`].
     ///
     /// [`Tag`]: ./struct.Tag.html
@@ -114,23 +282,43 @@ impl TagSpec {
         let tag = Tag::clone(tag);
         let TemplateTagSpec {
             required_tags,
+            required_groups,
+            required_any_of,
+            publish_required_tags,
             conflicting_tags,
+            implied_tags,
             needed_roles,
+            public,
             groups,
+            is_also_group,
+            priority,
+            display_name,
+            #[cfg(feature = "chrono")]
+            expires_at,
         } = spec;
 
         TagSpec {
             tag,
             required_tags,
+            required_groups,
+            required_any_of,
+            publish_required_tags,
             conflicting_tags,
-            needed_roles,
+            implied_tags,
+            needed_roles: Arc::from(needed_roles),
+            public,
             groups,
+            is_also_group,
+            priority,
+            display_name,
+            #[cfg(feature = "chrono")]
+            expires_at,
         }
     }
 
     fn check_roles(&self, roles: &[Role]) -> Result<()> {
         // No role requirements
-        if self.needed_roles.is_empty() {
+        if self.public || self.needed_roles.is_empty() {
             return Ok(());
         }
 
@@ -141,7 +329,7 @@ impl TagSpec {
             }
         }
 
-        Err(Error::MissingRoles(self.needed_roles.clone()))
+        Err(Error::MissingRoles(Arc::clone(&self.needed_roles)))
     }
 
     /// Checks that the given [`Tag`]s comply with the policy described in the [`Engine`].
@@ -153,6 +341,25 @@ impl TagSpec {
         self.check_tag_changes(engine, tags, &[], &[], &[])
     }
 
+    // Checks the publish-only requirements, which check_tags does not enforce.
+    pub(crate) fn check_publish_requirements(&self, engine: &Engine, tags: &[Tag]) -> Result<()> {
+        for required in &self.publish_required_tags {
+            if engine.count_tag(required, tags)? == 0 {
+                // Groups can't be added directly, so list what would satisfy them
+                if engine.is_group(required) {
+                    let members = engine.tags_in_group(required);
+                    let group = Tag::clone(required);
+                    return Err(Error::RequiresOneOf(self.tag(), group, members));
+                }
+
+                let required_tags = self.publish_required_tags.clone();
+                return Err(Error::RequiresTags(self.tag(), required_tags));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Checks that the given [`Tag`]s changes with the policy described in the [`Engine`].
     ///
     /// Will consider the action of adding `added_tags` and removing `removed_tags` from the
@@ -163,6 +370,7 @@ impl TagSpec {
     ///
     /// [`Engine`]: ./struct.Engine.html
     /// [`Tag`]: ./struct.Tag.html
+    #[inline]
     pub fn check_tag_changes(
         &self,
         engine: &Engine,
@@ -170,53 +378,187 @@ impl TagSpec {
         added_tags: &[Tag],
         removed_tags: &[Tag],
         roles: &[Role],
+    ) -> Result<()> {
+        let flags = CheckFlags::all();
+        self.check_tag_changes_with_flags(engine, tags, added_tags, removed_tags, roles, flags)
+    }
+
+    /// Like [`check_tag_changes`], but only enforces the kinds of rules selected in `flags`.
+    ///
+    /// [`check_tag_changes`]: #method.check_tag_changes
+    #[inline]
+    pub fn check_tag_changes_with_flags(
+        &self,
+        engine: &Engine,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+        flags: CheckFlags,
+    ) -> Result<()> {
+        self.check_tag_changes_external(engine, tags, added_tags, removed_tags, roles, flags, None)
+    }
+
+    // Like check_tag_changes_with_flags, but requirements may also be satisfied by
+    // tags in `external`, which are otherwise not considered.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn check_tag_changes_external(
+        &self,
+        engine: &Engine,
+        tags: &[Tag],
+        added_tags: &[Tag],
+        removed_tags: &[Tag],
+        roles: &[Role],
+        flags: CheckFlags,
+        external: Option<&HashSet<Tag>>,
     ) -> Result<()> {
         // Check if this tag was changed
-        if added_tags.contains(&self.tag) || removed_tags.contains(&self.tag) {
+        let removed = removed_tags.contains(&self.tag);
+        let changed = added_tags.contains(&self.tag) || removed;
+        if changed && flags.contains(CheckFlags::ROLES) {
             // If so, ensure user has permission to change this tag
             self.check_roles(roles)?;
         }
 
+        // A removed tag's rules no longer apply
+        if removed {
+            return Ok(());
+        }
+
+        // The tagset after the changes have been made
+        let present = || {
+            tags.iter()
+                .filter(move |tag| !removed_tags.contains(tag))
+                .chain(added_tags)
+        };
+
         // Local helper function
-        let count_tags = |tag| -> Result<usize> {
-            // Tag isn't present
-            if removed_tags.contains(tag) {
-                return Ok(0);
+        let count_tags = |check| -> Result<usize> {
+            // Computed tags depend on the whole tagset, not on any one tag
+            if engine.is_computed(check) {
+                let tags = present().cloned().collect::<Vec<_>>();
+                return engine.count_tag(check, &tags);
+            }
+
+            let mut count = 0;
+            for tag in present() {
+                count += engine.count_tag(check, slice::from_ref(tag))?;
             }
 
-            // Check current and new tags
-            let result = engine.count_tag(tag, tags)? + engine.count_tag(tag, added_tags)?;
-            Ok(result)
+            Ok(count)
+        };
+
+        // Whether an unmanaged tag satisfies the requirement instead
+        let external_present = |required| match external {
+            Some(external) => external.contains(required),
+            None => false,
         };
 
         // Ensure all requirements are met
-        for required in &self.required_tags {
-            if count_tags(required)? == 0 {
-                let required_tags = self.required_tags.clone();
-                return Err(Error::RequiresTags(self.tag(), required_tags));
+        if flags.contains(CheckFlags::REQUIREMENTS) {
+            for required in &self.required_tags {
+                if count_tags(required)? == 0 && !external_present(required) {
+                    // Groups can't be added directly, so list what would satisfy them
+                    if engine.is_group(required) {
+                        let members = engine.tags_in_group(required);
+                        let group = Tag::clone(required);
+                        return Err(Error::RequiresOneOf(self.tag(), group, members));
+                    }
+
+                    let required_tags = self.required_tags.clone();
+                    return Err(Error::RequiresTags(self.tag(), required_tags));
+                }
             }
+
+            for group in &self.required_groups {
+                if count_tags(group)? == 0 && !external_present(group) {
+                    let group = Tag::clone(group);
+                    return Err(Error::RequiresGroupMember(self.tag(), group));
+                }
+            }
+
+            'alternatives: for alternatives in &self.required_any_of {
+                for required in alternatives {
+                    if count_tags(required)? > 0 || external_present(required) {
+                        continue 'alternatives;
+                    }
+                }
+
+                let alternatives = alternatives.clone();
+                return Err(Error::RequiresAnyOf(self.tag(), alternatives));
+            }
+        }
+
+        if !flags.contains(CheckFlags::CONFLICTS) {
+            return Ok(());
         }
 
         // Ensure no conflicts are present
         for conflicts in &self.conflicting_tags {
-            // Sees if the current tag matches the conflict requirement,
-            // to avoid getting a false-positive on ourselves.
+            // Conflicting with a group we are a member of limits how many of the group
+            // can be present, rather than forbidding it outright.
+            let member = engine.is_group(conflicts)
+                && engine.count_tag(conflicts, slice::from_ref(&self.tag))? > 0;
 
-            let limit = if engine.is_group(conflicts) {
-                let self_matches = engine.check_tag(&self.tag, tags)?
-                    || engine.check_tag(&self.tag, added_tags)?;
-
-                usize::from(self_matches)
+            // This tag itself never counts towards a conflict, so any *other* present tag
+            // matching the conflict is an error, even one which also satisfies a requirement.
+            // Computed tags depend on the whole tagset, so they are evaluated as-is.
+            let conflict = if engine.is_computed(conflicts) {
+                if count_tags(conflicts)? > 0 {
+                    Some(Tag::clone(conflicts))
+                } else {
+                    None
+                }
             } else {
-                0
+                let others = present().filter(|tag| **tag != self.tag);
+                find_member(engine, conflicts, others)?
             };
 
-            if count_tags(conflicts)? > limit {
-                let conflicts = Tag::clone(conflicts);
-                return Err(Error::IncompatibleTags(self.tag(), conflicts));
+            if let Some(other) = conflict {
+                // For groups we are in, name the group as well
+                if member {
+                    let group = Tag::clone(conflicts);
+                    return Err(Error::IncompatibleViaGroup(self.tag(), other, group));
+                }
+
+                return Err(Error::IncompatibleTags(self.tag(), other));
             }
         }
 
         Ok(())
     }
 }
+
+impl<'a> From<&'a TagSpec> for TemplateTagSpec {
+    fn from(spec: &'a TagSpec) -> Self {
+        TemplateTagSpec {
+            required_tags: spec.required_tags.clone(),
+            required_groups: spec.required_groups.clone(),
+            required_any_of: spec.required_any_of.clone(),
+            publish_required_tags: spec.publish_required_tags.clone(),
+            conflicting_tags: spec.conflicting_tags.clone(),
+            implied_tags: spec.implied_tags.clone(),
+            needed_roles: spec.needed_roles.to_vec(),
+            public: spec.public,
+            groups: spec.groups.clone(),
+            is_also_group: spec.is_also_group,
+            priority: spec.priority,
+            display_name: spec.display_name.clone(),
+            #[cfg(feature = "chrono")]
+            expires_at: spec.expires_at,
+        }
+    }
+}
+
+fn find_member<'a, I>(engine: &Engine, group: &Tag, tags: I) -> Result<Option<Tag>>
+where
+    I: IntoIterator<Item = &'a Tag>,
+{
+    for tag in tags {
+        if engine.count_tag(group, slice::from_ref(tag))? > 0 {
+            return Ok(Some(Tag::clone(tag)));
+        }
+    }
+
+    Ok(None)
+}
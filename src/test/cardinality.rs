@@ -0,0 +1,108 @@
+/*
+ * test/cardinality.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::{Cardinality, CheckFlags};
+
+#[test]
+fn test_group_cardinality() {
+    let mut engine = setup();
+    let object_class = Tag::new("object-class");
+    engine
+        .set_group_cardinality(&object_class, Cardinality::at_most(1))
+        .unwrap();
+
+    macro_rules! check {
+        ($tags:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            engine.check_tags(&tags).unwrap();
+        };
+        ($tags:expr, $members:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            match engine.check_tags(&tags) {
+                Err(Error::GroupCardinality(group, _, members)) => {
+                    let expected = $members
+                        .iter()
+                        .map(|name| Tag::new(*name))
+                        .collect::<Vec<_>>();
+                    assert_eq!(group, object_class);
+                    assert_eq!(members, expected);
+                }
+                result => panic!("Expected Error::GroupCardinality, got {:?}", result),
+            }
+        };
+    }
+
+    check!(["scp"]);
+    check!(["scp", "keter"]);
+    check!(["scp", "safe", "euclid"], ["safe", "euclid"]);
+
+    engine
+        .set_group_cardinality(&object_class, Cardinality::exactly(1))
+        .unwrap();
+    check!(["scp"], [] as [&str; 0]);
+    check!(["scp", "keter"]);
+
+    // Only enforced with the cardinality flag
+    let tags = [Tag::new("scp")];
+    assert!(engine
+        .check_tags_with_flags(&tags, CheckFlags::CONFLICTS | CheckFlags::REQUIREMENTS)
+        .is_ok());
+
+    // Changes are checked against the final tagset
+    let tags = [Tag::new("scp"), Tag::new("safe")];
+    let result = engine.check_tag_changes(&tags, &[Tag::new("euclid")], &[Tag::new("safe")], &[]);
+    assert_eq!(result, Ok(()));
+    let result = engine.check_tag_changes(&tags, &[Tag::new("euclid")], &[], &[]);
+    assert!(result.is_err());
+
+    engine.clear_group_cardinality(&object_class);
+    check!(["scp", "safe", "euclid"]);
+
+    assert_eq!(
+        engine.set_group_cardinality(&Tag::new("scp"), Cardinality::at_least(1)),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+}
+
+#[test]
+fn test_cardinality_message() {
+    let mut engine = setup();
+    let object_class = Tag::new("object-class");
+    engine
+        .set_group_cardinality(&object_class, Cardinality::exactly(1))
+        .unwrap();
+
+    let tags = [Tag::new("scp"), Tag::new("safe"), Tag::new("euclid")];
+    let message = engine.check_tags(&tags).unwrap_err().to_string();
+    assert!(
+        message.ends_with(
+            "group 'object-class' requires exactly 1 member but found 2 (safe and euclid)"
+        ),
+        "Unexpected message: {}",
+        message,
+    );
+
+    let message = engine
+        .check_tags(&[Tag::new("scp")])
+        .unwrap_err()
+        .to_string();
+    assert!(message.ends_with("requires exactly 1 member but found 0"));
+
+    assert_eq!(Cardinality::at_most(2).to_string(), "at most 2 members");
+    assert_eq!(Cardinality::at_least(1).to_string(), "at least 1 member");
+    let between = Cardinality {
+        min: 1,
+        max: Some(3),
+    };
+    assert_eq!(between.to_string(), "between 1 and 3 members");
+}
@@ -11,6 +11,10 @@
  */
 
 use super::prelude::*;
+use crate::{ChangeSummary, ChangeWarning};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
 
 #[test]
 fn test_good_changes() {
@@ -109,7 +113,7 @@ fn test_good_changes() {
         &[Tag::new("tale"), Tag::new("_image")],
         &[Tag::new("_cc")],
         &[Tag::new("_image")],
-        &[]
+        &[Role::new("licensing")]
     );
 }
 
@@ -130,7 +134,7 @@ fn test_bad_changes() {
         &[Tag::new("tale")],
         &[],
         &[],
-        Error::IncompatibleTags(Tag::new("scp"), Tag::new("primary"))
+        Error::IncompatibleViaGroup(Tag::new("scp"), Tag::new("tale"), Tag::new("primary"))
     );
 
     check!(
@@ -147,7 +151,11 @@ fn test_bad_changes() {
         &[],
         &[Tag::new("scp")],
         &[],
-        Error::RequiresTags(Tag::new("electronic"), vec![Tag::new("primary")])
+        Error::RequiresOneOf(
+            Tag::new("electronic"),
+            Tag::new("primary"),
+            vec![Tag::new("hub"), Tag::new("scp"), Tag::new("tale")],
+        )
     );
 
     // Missing roles
@@ -156,7 +164,7 @@ fn test_bad_changes() {
         &[Tag::new("doomsday2018")],
         &[],
         &[Role::new("member")],
-        Error::MissingRoles(vec![Role::new("locked")])
+        Error::MissingRoles(vec![Role::new("locked")].into())
     );
 
     check!(
@@ -164,7 +172,7 @@ fn test_bad_changes() {
         &[Tag::new("admin"), Tag::new("hub")],
         &[],
         &[Role::new("member")],
-        Error::MissingRoles(vec![Role::new("admin")])
+        Error::MissingRoles(vec![Role::new("admin")].into())
     );
 }
 
@@ -214,3 +222,558 @@ fn test_misc() {
 
     check!(&[], &[Tag::new("tale")], &[Tag::new("tale")], &[]);
 }
+
+#[test]
+fn test_required_roles() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($added_tags:expr, $removed_tags:expr, $roles:expr) => {
+            let roles = engine
+                .required_roles_for_change($added_tags, $removed_tags)
+                .unwrap();
+            let expected = $roles.iter().cloned().collect::<HashSet<Role>>();
+            assert_eq!(roles, expected);
+        };
+    }
+
+    check!(&[Tag::new("_cc")], &[], [Role::new("licensing")]);
+    check!(&[Tag::new("scp")], &[Tag::new("tale")], [] as [Role; 0]);
+    check!(
+        &[Tag::new("admin")],
+        &[Tag::new("doomsday2018")],
+        [Role::new("admin"), Role::new("locked")]
+    );
+}
+
+#[test]
+fn test_normalize_change() {
+    let engine = setup();
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+    let added = [Tag::new("_cc"), Tag::new("euclid")];
+    let removed = [Tag::new("keter"), Tag::new("_cc")];
+
+    // Contradictory changes are rejected as-is
+    assert!(engine
+        .check_tag_changes(&tags, &added, &removed, &[Role::new("licensing")])
+        .is_err());
+
+    let (added, removed) = engine.normalize_change(&added, &removed);
+    assert_eq!(added, [Tag::new("euclid")]);
+    assert_eq!(removed, [Tag::new("keter")]);
+
+    // Changes without overlap are left alone
+    let (same_added, same_removed) = engine.normalize_change(&added, &removed);
+    assert_eq!(same_added, added);
+    assert_eq!(same_removed, removed);
+}
+
+#[test]
+fn test_removed_dependencies() {
+    let mut engine = setup();
+
+    engine
+        .add_tag(
+            "anomalous",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("attribute")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let tags = [Tag::new("scp"), Tag::new("anomalous"), Tag::new("humanoid")];
+    engine.check_tags(&tags).unwrap();
+
+    // Removing the only member of a required group
+    let result = engine.check_tag_changes(&tags, &[], &[Tag::new("humanoid")], &[]);
+    match result {
+        Err(Error::RequiresOneOf(tag, group, _)) => {
+            assert_eq!(tag, Tag::new("anomalous"));
+            assert_eq!(group, Tag::new("attribute"));
+        }
+        _ => panic!("Expected Error::RequiresOneOf, got {:#?}", result),
+    }
+
+    // Replacing it with another member is fine
+    engine
+        .check_tag_changes(
+            &tags,
+            &[Tag::new("amorphous")],
+            &[Tag::new("humanoid")],
+            &[],
+        )
+        .unwrap();
+
+    // As is removing the dependent tag along with it
+    engine
+        .check_tag_changes(
+            &tags,
+            &[],
+            &[Tag::new("anomalous"), Tag::new("humanoid")],
+            &[],
+        )
+        .unwrap();
+
+    // Rules of newly added tags are enforced
+    let result = engine.check_tag_changes(&[Tag::new("scp")], &[Tag::new("anomalous")], &[], &[]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_public_tags() {
+    let mut engine = setup();
+
+    let open = engine
+        .add_tag(
+            "open",
+            TemplateTagSpec {
+                needed_roles: vec![Role::new("admin")],
+                public: true,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let restricted = engine
+        .add_tag(
+            "restricted",
+            TemplateTagSpec {
+                needed_roles: vec![Role::new("admin")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let unrestricted = engine
+        .add_tag("unrestricted", TemplateTagSpec::default())
+        .unwrap();
+
+    macro_rules! check {
+        ($tag:expr, $roles:expr, $valid:expr) => {
+            let added = [Tag::clone(&$tag)];
+            let result = engine.check_tag_changes(&[], &added, &[], $roles);
+            assert_eq!(result.is_ok(), $valid, "Unexpected result: {:?}", result);
+
+            let result = engine.check_tag_changes(&added, &[], &added, $roles);
+            assert_eq!(result.is_ok(), $valid, "Unexpected result: {:?}", result);
+        };
+    }
+
+    // Public tags ignore their listed roles
+    check!(open, &[], true);
+    check!(open, &[Role::new("member")], true);
+    check!(restricted, &[], false);
+    check!(restricted, &[Role::new("member")], false);
+    check!(restricted, &[Role::new("admin")], true);
+
+    // No listed roles means anyone may apply the tag
+    check!(unrestricted, &[], true);
+    check!(unrestricted, &[Role::new("member")], true);
+
+    let roles = engine.required_roles_for_change(&[open], &[]).unwrap();
+    assert!(roles.is_empty());
+    let roles = engine
+        .required_roles_for_change(&[restricted], &[])
+        .unwrap();
+    assert!(roles.contains(&Role::new("admin")));
+}
+
+#[test]
+fn test_lint_change() {
+    let engine = setup();
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+
+    macro_rules! check {
+        ($added_tags:expr, $removed_tags:expr, $warnings:expr) => {
+            let warnings = engine.lint_change(&tags, $added_tags, $removed_tags);
+            assert_eq!(warnings, $warnings);
+        };
+    }
+
+    check!(&[Tag::new("humanoid")], &[Tag::new("keter")], []);
+    check!(
+        &[Tag::new("scp")],
+        &[],
+        [ChangeWarning::AlreadyPresent(Tag::new("scp"))]
+    );
+    check!(
+        &[],
+        &[Tag::new("euclid")],
+        [ChangeWarning::NotPresent(Tag::new("euclid"))]
+    );
+    check!(
+        &[Tag::new("keter"), Tag::new("humanoid")],
+        &[Tag::new("safe"), Tag::new("scp")],
+        [
+            ChangeWarning::AlreadyPresent(Tag::new("keter")),
+            ChangeWarning::NotPresent(Tag::new("safe")),
+        ]
+    );
+}
+
+#[test]
+fn test_effective_roles() {
+    let mut engine = setup();
+    let (admin, moderator, member) = (
+        Role::new("admin"),
+        Role::new("moderator"),
+        Role::new("member"),
+    );
+    let licensing = Role::new("licensing");
+
+    engine.add_role_inheritance(&admin, &moderator).unwrap();
+    engine.add_role_inheritance(&moderator, &member).unwrap();
+    engine.add_default_role(&licensing).unwrap();
+
+    let roles = engine.effective_roles(&[Role::clone(&admin)]);
+    let expected = [
+        admin,
+        moderator,
+        Role::clone(&member),
+        Role::clone(&licensing),
+    ]
+    .iter()
+    .cloned()
+    .collect::<HashSet<Role>>();
+    assert_eq!(roles, expected);
+
+    let roles = engine.effective_roles(&[]);
+    assert_eq!(roles.len(), 1);
+    assert!(roles.contains(&licensing));
+
+    // Used when checking changes
+    let tags = [Tag::new("tale")];
+    engine
+        .check_tag_changes(&tags, &[Tag::new("_cc")], &[], &[Role::clone(&member)])
+        .unwrap();
+
+    // Unregistered roles are rejected
+    assert_eq!(
+        engine.add_default_role(&Role::new("nobody")),
+        Err(Error::MissingRole(Role::new("nobody"))),
+    );
+
+    // Inheritance cycles are harmless
+    engine
+        .add_role_inheritance(&member, &Role::new("admin"))
+        .unwrap();
+    assert_eq!(engine.effective_roles(&[member]).len(), 4);
+
+    engine.delete_role(&licensing);
+    assert!(engine.get_default_roles().is_empty());
+    assert!(engine.effective_roles(&[]).is_empty());
+}
+
+#[test]
+fn test_role_sets() {
+    let mut engine = setup();
+    let (admin, moderator, member) = (
+        Role::new("admin"),
+        Role::new("moderator"),
+        Role::new("member"),
+    );
+
+    let staff = engine
+        .add_role_set("staff", vec![Role::clone(&admin), Role::clone(&moderator)])
+        .unwrap();
+    assert!(engine.has_role("staff"));
+    assert_eq!(
+        engine.get_role_set(&staff),
+        Some(&[Role::clone(&admin), Role::clone(&moderator)][..]),
+    );
+
+    let featured = engine
+        .add_tag(
+            "featured",
+            TemplateTagSpec {
+                needed_roles: vec![Role::clone(&staff)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    // Any member of the set may apply the tag
+    let tags = [Tag::new("scp")];
+    let added = [Tag::clone(&featured)];
+    for role in &[&admin, &moderator, &staff] {
+        engine
+            .check_tag_changes(&tags, &added, &[], &[Role::clone(role)])
+            .unwrap();
+    }
+
+    assert_eq!(
+        engine.check_tag_changes(&tags, &added, &[], &[Role::clone(&member)]),
+        Err(Error::MissingRoles(vec![Role::clone(&staff)].into())),
+    );
+
+    // Sets may contain other sets
+    let everyone = engine
+        .add_role_set("everyone", vec![Role::clone(&staff), Role::clone(&member)])
+        .unwrap();
+    assert!(engine.effective_roles(&[admin]).contains(&everyone));
+
+    // Unregistered members are rejected
+    assert_eq!(
+        engine.add_role_set("nobody", vec![Role::new("xyz")]),
+        Err(Error::MissingRole(Role::new("xyz"))),
+    );
+
+    engine.delete_role(&moderator);
+    assert_eq!(engine.get_role_set(&staff), Some(&[Role::new("admin")][..]));
+    engine.delete_role_set(&staff);
+    assert_eq!(engine.get_role_set(&staff), None);
+    assert!(engine.has_role("staff"));
+}
+
+#[test]
+fn test_missing_roles_display() {
+    let error = Error::MissingRoles(vec![Role::new("admin"), Role::new("moderator")].into());
+    assert_eq!(
+        error.to_string(),
+        "Cannot apply tags without roles: at least one of admin or moderator",
+    );
+
+    let error = Error::MissingRoles(Vec::new().into());
+    assert_eq!(
+        error.to_string(),
+        "Cannot apply tags without roles: this tag cannot be applied by anyone",
+    );
+}
+
+#[test]
+fn test_list_display() {
+    macro_rules! check {
+        ($needed:expr, $message:expr) => {
+            let needed = $needed.iter().map(|name| Tag::new(*name)).collect();
+            let error = Error::RequiresTags(Tag::new("x"), needed);
+            assert_eq!(
+                error.to_string(),
+                concat!("Tag missing requirements: ", $message)
+            );
+        };
+    }
+
+    check!(Vec::<&str>::new(), "x needs nothing");
+    check!(["a"], "x needs a");
+    check!(["a", "b"], "x needs a and b");
+    check!(["a", "b", "c"], "x needs a, b, and c");
+
+    let error = Error::RequiresAnyOf(Tag::new("x"), vec![Tag::new("a"), Tag::new("b")]);
+    assert_eq!(
+        error.to_string(),
+        "Tag missing requirements: x needs one of a or b"
+    );
+}
+
+#[test]
+fn test_disabled_tags() {
+    let mut engine = setup();
+    let keter = Tag::new("keter");
+    let tags = [Tag::new("scp"), Tag::clone(&keter)];
+    let roles = [Role::new("member")];
+
+    assert!(engine.is_tag_enabled(&keter));
+    engine.set_tag_enabled(&keter, false).unwrap();
+    assert!(!engine.is_tag_enabled(&keter));
+
+    // Cannot be newly applied
+    assert_eq!(
+        engine.check_tag_changes(&tags[..1], &tags[1..], &[], &roles),
+        Err(Error::TagDisabled(Tag::clone(&keter))),
+    );
+    assert_eq!(
+        engine.check_tags_as_creation(&tags, &roles),
+        Err(Error::TagDisabled(Tag::clone(&keter))),
+    );
+
+    // But existing usages are unaffected
+    engine.check_tags(&tags).unwrap();
+    engine
+        .check_tag_changes(&tags, &[Tag::new("humanoid")], &[], &roles)
+        .unwrap();
+    engine
+        .check_tag_changes(&tags, &[Tag::new("euclid")], &tags[1..], &roles)
+        .unwrap();
+    assert!(engine.get_spec(&keter).is_ok());
+
+    engine.set_tag_enabled(&keter, true).unwrap();
+    engine
+        .check_tag_changes(&tags[..1], &tags[1..], &[], &roles)
+        .unwrap();
+
+    assert_eq!(
+        engine.set_tag_enabled(&Tag::new("primary"), false),
+        Err(Error::MissingTag(Tag::new("primary"))),
+    );
+}
+
+#[test]
+fn test_commit_changes() {
+    let mut engine = setup();
+    let admin = Tag::new("admin");
+    let applied = Rc::new(Cell::new(0));
+
+    {
+        let applied = Rc::clone(&applied);
+        let hook = move |tags: &[Tag]| {
+            assert!(tags.contains(&Tag::new("admin")));
+            applied.set(applied.get() + 1);
+        };
+
+        engine.on_tag_applied(&admin, Box::new(hook)).unwrap();
+    }
+
+    // Rejected changes do not fire hooks
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+    let added = [Tag::clone(&admin)];
+    assert_eq!(
+        engine.commit_changes(&tags, &added, &[], &[Role::new("member")]),
+        Err(Error::MissingRoles(vec![Role::new("admin")].into())),
+    );
+    assert_eq!(applied.get(), 0);
+
+    // Successful ones fire once per added tag
+    let tags = engine
+        .commit_changes(&tags, &added, &[Tag::new("keter")], &[Role::new("admin")])
+        .unwrap();
+    assert_eq!(tags, [Tag::new("scp"), Tag::clone(&admin)]);
+    assert_eq!(applied.get(), 1);
+
+    // Only added tags fire hooks, not those already present
+    engine
+        .commit_changes(&tags, &[Tag::new("euclid")], &[], &[Role::new("member")])
+        .unwrap();
+    assert_eq!(applied.get(), 1);
+
+    // Nor those which are also removed, as the two cancel out
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+    assert_eq!(
+        engine.commit_changes(&tags, &added, &added, &[Role::new("admin")]),
+        Ok(tags.to_vec()),
+    );
+    assert_eq!(applied.get(), 1);
+
+    assert_eq!(
+        engine.on_tag_applied(&Tag::new("xyz"), Box::new(|_: &[Tag]| ())),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_min_roles_to_apply() {
+    let mut engine = setup();
+    assert_eq!(
+        engine.min_roles_to_apply(&Tag::new("_cc")),
+        Ok(vec![Role::new("licensing")]),
+    );
+    assert_eq!(
+        engine.min_roles_to_apply(&Tag::new("co-authored")),
+        Ok(vec![]),
+    );
+
+    // Role sets are expanded, including nested ones
+    let (admin, moderator) = (Role::new("admin"), Role::new("moderator"));
+    let staff = engine
+        .add_role_set("staff", vec![Role::clone(&moderator)])
+        .unwrap();
+    let trusted = engine
+        .add_role_set("trusted", vec![Role::clone(&staff), Role::clone(&admin)])
+        .unwrap();
+    engine
+        .add_tag(
+            "featured",
+            TemplateTagSpec {
+                needed_roles: vec![
+                    Role::clone(&trusted),
+                    Role::clone(&staff),
+                    Role::new("licensing"),
+                ],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.min_roles_to_apply(&Tag::new("featured")),
+        Ok(vec![
+            Role::clone(&admin),
+            Role::new("licensing"),
+            Role::clone(&moderator),
+            Role::clone(&staff),
+            Role::clone(&trusted),
+        ]),
+    );
+
+    // An empty set still restricts the tag to those holding it
+    engine.delete_role(&moderator);
+    engine.delete_role(&admin);
+    assert_eq!(engine.get_role_set(&staff).map(|set| set.len()), Some(0));
+    engine
+        .get_spec_mut(&Tag::new("featured"))
+        .unwrap()
+        .needed_roles = vec![Role::clone(&staff)].into();
+    assert_eq!(
+        engine.min_roles_to_apply(&Tag::new("featured")),
+        Ok(vec![Role::clone(&staff)]),
+    );
+    assert!(engine
+        .check_tag_changes(&[], &[Tag::new("featured")], &[], &[])
+        .is_err());
+
+    // Public tags need no roles at all
+    engine.get_spec_mut(&Tag::new("featured")).unwrap().public = true;
+    assert_eq!(engine.min_roles_to_apply(&Tag::new("featured")), Ok(vec![]));
+
+    assert_eq!(
+        engine.min_roles_to_apply(&Tag::new("object-class")),
+        Err(Error::MissingTag(Tag::new("object-class"))),
+    );
+}
+
+#[test]
+fn test_summarize_change() {
+    let engine = setup();
+    let tags = [Tag::new("scp"), Tag::new("_cc")];
+    let added = [Tag::new("humanoid"), Tag::new("_image")];
+    let removed = [Tag::new("_cc")];
+    let roles = [Role::new("licensing"), Role::new("member")];
+
+    let summary = engine
+        .summarize_change(&tags, &added, &removed, &roles)
+        .unwrap();
+    assert_eq!(
+        summary,
+        ChangeSummary {
+            added: 2,
+            removed: 1,
+            groups: vec![(Tag::new("attribute"), 0, 1)],
+            roles: vec![Role::new("licensing")],
+        },
+    );
+    assert!(!summary.is_empty());
+
+    // Already present and absent tags have no effect
+    let summary = engine
+        .summarize_change(&tags, &[Tag::new("scp")], &[Tag::new("tale")], &roles)
+        .unwrap();
+    assert_eq!(summary, ChangeSummary::default());
+    assert!(summary.is_empty());
+
+    // Tags both added and removed cancel out, matching commit_changes
+    let both = [Tag::new("_cc"), Tag::new("tale")];
+    let summary = engine
+        .summarize_change(&tags, &both, &both, &roles)
+        .unwrap();
+    assert!(summary.is_empty());
+    assert_eq!(
+        engine.commit_changes(&tags, &both, &both, &roles),
+        Ok(tags.to_vec())
+    );
+
+    // Invalid changes fail as they would when checked
+    assert_eq!(
+        engine.summarize_change(&tags, &added, &removed, &[]),
+        Err(Error::MissingRoles(vec![Role::new("licensing")].into())),
+    );
+}
@@ -11,6 +11,8 @@
  */
 
 use super::prelude::*;
+use crate::{sort_errors, Cardinality, CheckFlags};
+use std::collections::HashSet;
 
 #[test]
 fn test_good_tags() {
@@ -67,15 +69,54 @@ fn test_requires() {
         };
     }
 
+    check!(
+        [Tag::new("creepypasta"), Tag::new("co-authored")],
+        [Tag::new("tale")]
+    );
+}
+
+#[test]
+fn test_requires_group() {
+    let mut engine = setup();
+
+    engine
+        .add_tag(
+            "classified",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("object-class")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    macro_rules! check {
+        ($check_tags:expr, $err_group:expr, $err_members:expr) => {
+            match engine.check_tags(&$check_tags).unwrap_err() {
+                Error::RequiresOneOf(_, group, members) => {
+                    let expected: Vec<Tag> = $err_members.iter().map(|t| Tag::new(*t)).collect();
+                    assert_eq!(group, Tag::new($err_group));
+                    assert_eq!(members, expected);
+                }
+                error => panic!("Error wasn't RequiresOneOf: {:?}", error),
+            }
+        };
+    }
+
     check!(
         [Tag::new("ontokinetic"), Tag::new("humanoid")],
-        [Tag::new("primary")]
+        "primary",
+        ["hub", "scp", "tale"]
     );
 
     check!(
-        [Tag::new("creepypasta"), Tag::new("co-authored")],
-        [Tag::new("tale")]
+        [Tag::new("scp"), Tag::new("classified")],
+        "object-class",
+        ["esoteric-class", "euclid", "keter", "safe", "thaumiel"]
     );
+
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("keter"), Tag::new("classified")])
+        .unwrap();
 }
 
 #[test]
@@ -94,19 +135,1098 @@ fn test_conflicts() {
         };
     }
 
-    check!(
-        [Tag::new("scp"), Tag::new("tale"), Tag::new("keter")],
-        Tag::new("scp"),
-        Tag::new("primary")
-    );
     check!(
         [Tag::new("cliche2019"), Tag::new("_image"), Tag::new("_cc")],
         Tag::new("_image"),
         Tag::new("_cc")
     );
+}
+
+#[test]
+fn test_conflicts_via_group() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($check_tags:expr, $first:expr, $second:expr, $group:expr) => {
+            let tags = $check_tags
+                .iter()
+                .map(|name| Tag::new(*name))
+                .collect::<Vec<_>>();
+            let error =
+                Error::IncompatibleViaGroup(Tag::new($first), Tag::new($second), Tag::new($group));
+            assert_eq!(engine.check_tags(&tags), Err(error));
+        };
+    }
+
+    check!(["scp", "tale", "keter"], "scp", "tale", "primary");
+    check!(["keter", "tale", "scp"], "tale", "scp", "primary");
+    check!(
+        ["cliche2019", "doomsday2018"],
+        "cliche2019",
+        "doomsday2018",
+        "contests"
+    );
+
+    let tags = [Tag::new("scp"), Tag::new("hub")];
+    assert_eq!(
+        engine.check_tags(&tags).unwrap_err().to_string(),
+        "Tags conflict: scp conflicts with hub because both are in primary",
+    );
+}
+
+#[test]
+fn test_group_conflicts() {
+    let mut engine = setup();
+
+    engine
+        .add_tag(
+            "no-attributes",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("attribute")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    macro_rules! check {
+        ($check_tags:expr) => {
+            engine.check_tags(&$check_tags).unwrap()
+        };
+        ($check_tags:expr, $err_tag:expr) => {
+            match engine.check_tags(&$check_tags).unwrap_err() {
+                Error::IncompatibleTags(first_tag, second_tag) => {
+                    assert_eq!(first_tag, Tag::new("no-attributes"));
+                    assert_eq!(second_tag, Tag::new($err_tag));
+                }
+                error => panic!("Error wasn't IncompatibleTags: {:?}", error),
+            }
+        };
+    }
+
+    check!([Tag::new("scp"), Tag::new("no-attributes")]);
+    check!(
+        [
+            Tag::new("scp"),
+            Tag::new("no-attributes"),
+            Tag::new("humanoid")
+        ],
+        "humanoid"
+    );
+    check!(
+        [
+            Tag::new("scp"),
+            Tag::new("electronic"),
+            Tag::new("no-attributes"),
+            Tag::new("humanoid")
+        ],
+        "electronic"
+    );
+}
+
+#[test]
+fn test_check_many() {
+    let engine = setup();
+
+    let tagsets: &[&[Tag]] = &[
+        &[Tag::new("scp"), Tag::new("keter")],
+        &[Tag::new("scp"), Tag::new("tale")],
+        &[Tag::new("tale"), Tag::new("creepypasta")],
+        &[],
+        &[Tag::new("creepypasta")],
+        &[Tag::new("_image"), Tag::new("_cc")],
+    ];
+
+    let results = engine.check_many(tagsets.iter().cloned());
+    assert_eq!(results.len(), tagsets.len());
+
+    let failed = results
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(i, _)| *i)
+        .collect::<Vec<_>>();
+
+    assert_eq!(failed, [1, 4, 5]);
+
+    for (i, result) in results {
+        assert_eq!(result, engine.check_tags(tagsets[i]));
+    }
+}
+
+#[test]
+fn test_suggest_removal() {
+    let mut engine = setup();
+
+    macro_rules! check {
+        ($tags:expr, $suggested:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            let suggested = engine.suggest_removal(&tags).unwrap();
+            assert_eq!(suggested, $suggested.map(Tag::new));
+        };
+    }
+
+    // Ties go to the last tag
+    check!(["scp", "tale"], Some("tale"));
+    check!(["tale", "scp"], Some("scp"));
+    check!(["_image", "_cc"], Some("_cc"));
+    check!(["scp", "keter"], None::<&str>);
+
+    engine.get_spec_mut(&Tag::new("scp")).unwrap().priority = 10;
+    engine.get_spec_mut(&Tag::new("_cc")).unwrap().priority = 5;
+
+    check!(["scp", "tale"], Some("tale"));
+    check!(["tale", "scp"], Some("tale"));
+    check!(["tale", "scp", "hub"], Some("hub"));
+    check!(["_cc", "_image"], Some("_image"));
+
+    // Too many members of a group
+    let cardinality = Cardinality {
+        min: 1,
+        max: Some(2),
+    };
+    engine
+        .set_group_cardinality(&Tag::new("attribute"), cardinality)
+        .unwrap();
+
+    check!(["scp", "humanoid", "electronic"], None::<&str>);
     check!(
-        [Tag::new("cliche2019"), Tag::new("doomsday2018")],
-        Tag::new("cliche2019"),
-        Tag::new("contests")
+        ["scp", "humanoid", "electronic", "amorphous"],
+        Some("amorphous")
+    );
+    engine.get_spec_mut(&Tag::new("humanoid")).unwrap().priority = -1;
+    check!(
+        ["scp", "humanoid", "electronic", "amorphous"],
+        Some("humanoid")
+    );
+
+    // Too few can't be fixed by removing anything
+    check!(["scp"], None::<&str>);
+}
+
+#[test]
+fn test_check_closure() {
+    let mut engine = setup();
+
+    engine
+        .add_tag(
+            "sapient",
+            TemplateTagSpec {
+                implied_tags: vec![Tag::new("humanoid")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    engine
+        .add_tag(
+            "person-of-interest",
+            TemplateTagSpec {
+                implied_tags: vec![Tag::new("sapient")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    macro_rules! check {
+        ($tags:expr, $expanded:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            let expanded = $expanded
+                .iter()
+                .map(|name| Tag::new(*name))
+                .collect::<Vec<_>>();
+            assert_eq!(engine.check_tags_closure(&tags), Ok(expanded));
+        };
+    }
+
+    check!(["scp", "keter"], ["scp", "keter"]);
+    check!(["scp", "sapient"], ["scp", "sapient", "humanoid"]);
+    check!(
+        ["scp", "person-of-interest"],
+        ["scp", "person-of-interest", "sapient", "humanoid"]
+    );
+    check!(
+        ["tale", "sapient", "humanoid"],
+        ["tale", "sapient", "humanoid"]
+    );
+
+    // The implied tag has an unmet requirement of its own
+    match engine.check_tags_closure(&[Tag::new("person-of-interest")]) {
+        Err(Error::RequiresOneOf(tag, group, _)) => {
+            assert_eq!(tag, Tag::new("humanoid"));
+            assert_eq!(group, Tag::new("primary"));
+        }
+        result => panic!("Expected Error::RequiresOneOf, got {:?}", result),
+    }
+
+    // Without implications, the tagset is considered valid
+    engine
+        .check_tags(&[Tag::new("person-of-interest")])
+        .unwrap();
+}
+
+#[test]
+fn test_subgroups() {
+    let mut engine = setup();
+    let classification = engine.add_group("classification").unwrap();
+    let object_class = Tag::new("object-class");
+    engine.add_subgroup(&classification, &object_class).unwrap();
+
+    engine
+        .add_tag(
+            "classified",
+            TemplateTagSpec {
+                required_tags: vec![Tag::clone(&classification)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let tags = [Tag::new("scp"), Tag::new("keter"), Tag::new("classified")];
+    engine.check_tags(&tags).unwrap();
+    assert_eq!(engine.count_tag(&classification, &tags), Ok(1));
+
+    match engine.check_tags(&[Tag::new("scp"), Tag::new("classified")]) {
+        Err(Error::RequiresOneOf(_, group, members)) => {
+            assert_eq!(group, classification);
+            assert!(members.contains(&Tag::new("keter")));
+        }
+        result => panic!("Expected Error::RequiresOneOf, got {:?}", result),
+    }
+
+    // Nested more deeply, and with a cycle
+    let metadata = engine.add_group("metadata").unwrap();
+    engine.add_subgroup(&metadata, &classification).unwrap();
+    engine.add_subgroup(&object_class, &metadata).unwrap();
+    assert_eq!(engine.count_tag(&metadata, &tags), Ok(1));
+    assert_eq!(engine.count_tag(&object_class, &tags), Ok(1));
+    assert_eq!(engine.count_tag(&Tag::new("primary"), &tags), Ok(1));
+
+    // Invalid subgroups
+    assert_eq!(
+        engine.add_subgroup(&classification, &Tag::new("scp")),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+    assert!(engine.add_subgroup(&metadata, &metadata).is_err());
+}
+
+#[test]
+fn test_subgroup_conflicts() {
+    let mut engine = setup();
+    let ruling = engine.add_group("ruling").unwrap();
+    engine.add_subgroup(&ruling, &Tag::new("primary")).unwrap();
+
+    engine
+        .add_tag(
+            "canon",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::clone(&ruling)],
+                groups: vec![Tag::clone(&ruling)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    engine.check_tags(&[Tag::new("canon")]).unwrap();
+    engine.check_tags(&[Tag::new("scp")]).unwrap();
+    assert!(engine
+        .check_tags(&[Tag::new("canon"), Tag::new("scp")])
+        .is_err());
+}
+
+#[test]
+fn test_overlapping_group_conflicts() {
+    let mut engine = setup();
+    let medium = engine.add_group("medium").unwrap();
+    let style = engine.add_group("style").unwrap();
+
+    macro_rules! add {
+        ($name:expr, $groups:expr) => {
+            engine
+                .add_tag(
+                    $name,
+                    TemplateTagSpec {
+                        groups: $groups,
+                        ..TemplateTagSpec::default()
+                    },
+                )
+                .unwrap()
+        };
+    }
+
+    let audio = add!("audio", vec![Tag::clone(&medium), Tag::clone(&style)]);
+    let video = add!("video", vec![Tag::clone(&medium)]);
+    let comic = add!("comic", vec![Tag::clone(&style)]);
+
+    // Requires a member of one group, but conflicts with the other, overlapping group
+    let podcast = engine
+        .add_tag(
+            "podcast",
+            TemplateTagSpec {
+                required_groups: vec![Tag::clone(&style)],
+                conflicting_tags: vec![Tag::clone(&medium)],
+                groups: vec![Tag::clone(&medium)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    macro_rules! check {
+        ($tags:expr) => {
+            engine.check_tags(&$tags).unwrap()
+        };
+        ($tags:expr, $error:expr) => {
+            assert_eq!(engine.check_tags(&$tags), Err($error))
+        };
+    }
+
+    check!([Tag::clone(&podcast), Tag::clone(&comic)]);
+    check!(
+        [Tag::clone(&podcast)],
+        Error::RequiresGroupMember(Tag::clone(&podcast), Tag::clone(&style))
+    );
+
+    // Satisfying the requirement doesn't excuse the conflict
+    check!(
+        [Tag::clone(&podcast), Tag::clone(&audio)],
+        Error::IncompatibleViaGroup(
+            Tag::clone(&podcast),
+            Tag::clone(&audio),
+            Tag::clone(&medium)
+        )
+    );
+    check!(
+        [Tag::clone(&podcast), Tag::clone(&comic), Tag::clone(&video)],
+        Error::IncompatibleViaGroup(
+            Tag::clone(&podcast),
+            Tag::clone(&video),
+            Tag::clone(&medium)
+        )
+    );
+
+    // The tag itself is never counted, however many times it appears
+    check!([
+        Tag::clone(&podcast),
+        Tag::clone(&comic),
+        Tag::clone(&podcast)
+    ]);
+
+    // Nor does its absence hide a conflict with another member
+    let spec = engine.get_spec(&podcast).unwrap();
+    assert_eq!(
+        spec.check_tags(&engine, &[Tag::clone(&audio)]),
+        Err(Error::IncompatibleViaGroup(
+            Tag::clone(&podcast),
+            Tag::clone(&audio),
+            Tag::clone(&medium)
+        )),
+    );
+}
+
+#[test]
+fn test_required_group_set() {
+    let mut engine = setup();
+    let groups = vec![Tag::new("primary"), Tag::new("object-class")];
+    let classified = engine
+        .add_tag("classified", TemplateTagSpec::default())
+        .unwrap();
+    engine.add_required_group_set(&classified, groups).unwrap();
+
+    macro_rules! check {
+        ($tags:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            engine.check_tags(&tags).unwrap();
+        };
+        ($tags:expr, $group:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            assert_eq!(
+                engine.check_tags(&tags),
+                Err(Error::RequiresGroupMember(
+                    Tag::clone(&classified),
+                    Tag::new($group),
+                )),
+            );
+        };
+    }
+
+    check!(["scp", "keter", "classified"]);
+    check!(["classified"], "primary");
+    check!(["classified", "keter"], "primary");
+    check!(["scp", "classified"], "object-class");
+
+    // Only groups may be required this way
+    assert_eq!(
+        engine.add_required_group_set(&classified, vec![Tag::new("scp")]),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+    assert_eq!(
+        engine.add_required_group_set(&Tag::new("xyz"), vec![]),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+
+    // Deleting a tag which is also a group drops it from the set
+    let featured = engine
+        .add_tag(
+            "featured",
+            TemplateTagSpec {
+                is_also_group: true,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    let groups = vec![Tag::new("primary"), Tag::clone(&featured)];
+    engine.add_required_group_set(&classified, groups).unwrap();
+    check!(["scp", "classified"], "featured");
+
+    engine.delete_tag(&featured);
+    assert_eq!(
+        engine.get_spec(&classified).unwrap().required_groups,
+        [Tag::new("primary")],
+    );
+    check!(["scp", "classified"]);
+}
+
+#[test]
+fn test_check_as_creation() {
+    let engine = setup();
+    let tags = [Tag::new("tale"), Tag::new("_cc")];
+
+    engine.check_tags(&tags).unwrap();
+    engine
+        .check_tags_as_creation(&tags, &[Role::new("licensing")])
+        .unwrap();
+
+    assert_eq!(
+        engine.check_tags_as_creation(&tags, &[Role::new("member")]),
+        Err(Error::MissingRoles(vec![Role::new("licensing")].into())),
+    );
+
+    // Other rules are still enforced
+    let tags = [Tag::new("tale"), Tag::new("_cc"), Tag::new("_image")];
+    assert!(engine
+        .check_tags_as_creation(&tags, &[Role::new("licensing")])
+        .is_err());
+}
+
+#[test]
+fn test_membership_changes() {
+    let mut engine = setup();
+    let format = engine.add_group("format").unwrap();
+    let essay = engine.add_tag("essay", TemplateTagSpec::default()).unwrap();
+    let reviewed = engine
+        .add_tag(
+            "reviewed",
+            TemplateTagSpec {
+                required_tags: vec![Tag::clone(&format)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let tags = [Tag::clone(&reviewed), Tag::clone(&essay)];
+
+    // The group starts out empty
+    assert_eq!(
+        engine.check_tags(&tags),
+        Err(Error::RequiresOneOf(
+            Tag::clone(&reviewed),
+            Tag::clone(&format),
+            vec![],
+        )),
+    );
+
+    // Membership changes take effect immediately
+    engine.add_to_group(&essay, &format).unwrap();
+    assert_eq!(engine.count_tag(&format, &tags), Ok(1));
+    engine.check_tags(&tags).unwrap();
+    engine
+        .check_tag_changes(&[Tag::clone(&reviewed)], &[Tag::clone(&essay)], &[], &[])
+        .unwrap();
+
+    engine.remove_from_group(&essay, &format).unwrap();
+    assert_eq!(engine.count_tag(&format, &tags), Ok(0));
+    assert!(engine.check_tags(&tags).is_err());
+
+    // Including those made through subgroups
+    let prose = engine.add_group("prose").unwrap();
+    engine.add_to_group(&essay, &prose).unwrap();
+    assert!(engine.check_tags(&tags).is_err());
+
+    engine.add_subgroup(&format, &prose).unwrap();
+    engine.check_tags(&tags).unwrap();
+
+    // And through the specification directly
+    engine.get_spec_mut(&essay).unwrap().groups.clear();
+    assert!(engine.check_tags(&tags).is_err());
+}
+
+#[test]
+fn test_exclusion_sets() {
+    let mut engine = setup();
+    let exclusive = vec![
+        Tag::new("humanoid"),
+        Tag::new("electronic"),
+        Tag::new("co-authored"),
+    ];
+    engine.add_exclusion_set(exclusive).unwrap();
+
+    macro_rules! check {
+        ($tags:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            engine.check_tags(&tags).unwrap();
+        };
+        ($tags:expr, $first:expr, $second:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            assert_eq!(
+                engine.check_tags(&tags),
+                Err(Error::IncompatibleTags(Tag::new($first), Tag::new($second))),
+            );
+        };
+    }
+
+    check!(["scp", "keter"]);
+    check!(["scp", "keter", "humanoid"]);
+    check!(["scp", "keter", "co-authored"]);
+    check!(
+        ["scp", "electronic", "keter", "humanoid"],
+        "electronic",
+        "humanoid"
+    );
+    check!(
+        ["scp", "co-authored", "humanoid", "electronic"],
+        "co-authored",
+        "humanoid"
+    );
+
+    // Changes are checked against the final tagset
+    let tags = [Tag::new("scp"), Tag::new("humanoid")];
+    let added = [Tag::new("electronic")];
+    assert!(engine.check_tag_changes(&tags, &added, &[], &[]).is_err());
+    engine
+        .check_tag_changes(&tags, &added, &[Tag::new("humanoid")], &[])
+        .unwrap();
+
+    // Only enforced with the conflicts flag
+    let tags = [
+        Tag::new("scp"),
+        Tag::new("humanoid"),
+        Tag::new("electronic"),
+    ];
+    engine
+        .check_tags_with_flags(&tags, CheckFlags::REQUIREMENTS)
+        .unwrap();
+
+    // Deleted tags are removed from the set
+    engine.delete_tag(&Tag::new("electronic"));
+    assert_eq!(
+        engine.get_exclusion_sets(),
+        [vec![Tag::new("co-authored"), Tag::new("humanoid")]],
+    );
+
+    assert_eq!(
+        engine.add_exclusion_set(vec![Tag::new("scp"), Tag::new("xyz")]),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_synonym_groups() {
+    let mut engine = setup();
+    let coauthored = engine
+        .add_tag("coauthored", TemplateTagSpec::default())
+        .unwrap();
+    let co_authored = Tag::new("co-authored");
+    engine
+        .add_synonym_group(vec![Tag::clone(&co_authored), Tag::clone(&coauthored)])
+        .unwrap();
+
+    // Either spelling is fine alone
+    engine
+        .check_tags(&[Tag::new("tale"), Tag::clone(&coauthored)])
+        .unwrap();
+    engine
+        .check_tags(&[Tag::new("tale"), Tag::clone(&co_authored)])
+        .unwrap();
+
+    // But not both together
+    let tags = [
+        Tag::new("tale"),
+        Tag::clone(&coauthored),
+        Tag::clone(&co_authored),
+    ];
+    assert_eq!(
+        engine.check_tags(&tags),
+        Err(Error::IncompatibleTags(
+            Tag::clone(&coauthored),
+            Tag::clone(&co_authored)
+        )),
+    );
+    engine
+        .check_tags_with_flags(&tags, CheckFlags::REQUIREMENTS)
+        .unwrap();
+
+    // Which the graph queries agree with
+    assert_eq!(engine.can_coexist(&coauthored, &co_authored), Ok(false));
+    assert_eq!(
+        engine.compatibility_matrix().get(&co_authored, &coauthored),
+        Some(false),
+    );
+
+    // Each synonym matches the other
+    let tags = [Tag::new("tale"), Tag::clone(&coauthored)];
+    assert!(engine.check_tag(&co_authored, &tags).unwrap());
+    assert!(engine.check_tag(&coauthored, &tags).unwrap());
+    assert!(!engine.check_tag(&Tag::new("scp"), &tags).unwrap());
+
+    engine.delete_tag(&coauthored);
+    assert!(engine.get_synonym_groups().is_empty());
+    assert!(!engine.check_tag(&co_authored, &tags).unwrap());
+}
+
+#[test]
+fn test_group_as_tag() {
+    let engine = setup();
+    let primary = Tag::new("primary");
+    let tags = [Tag::new("scp"), Tag::clone(&primary), Tag::new("euclid")];
+
+    assert_eq!(
+        engine.check_tags(&tags),
+        Err(Error::GroupAsTag(Tag::clone(&primary))),
+    );
+    assert_eq!(
+        engine.count_tag(&primary, &tags),
+        Err(Error::GroupAsTag(Tag::clone(&primary))),
+    );
+    assert_eq!(
+        engine.check_tag_changes(&tags[..1], &[Tag::clone(&primary)], &[], &[]),
+        Err(Error::GroupAsTag(Tag::clone(&primary))),
+    );
+
+    // Skipped when counting leniently
+    assert_eq!(engine.count_tag_lenient(&primary, &tags), 1);
+
+    // Unknown names are still missing, rather than groups
+    let tags = [Tag::new("scp"), Tag::new("xyz")];
+    assert_eq!(
+        engine.check_tags(&tags),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_tagsets() {
+    let engine = setup();
+    let tags = vec![Tag::new("scp"), Tag::new("keter"), Tag::new("ontokinetic")];
+
+    let tagset = engine.make_tagset(tags.clone()).unwrap();
+    assert_eq!(tagset.as_slice(), &tags[..]);
+    assert_eq!(tagset.len(), 3);
+    assert_eq!(
+        tagset.specs()[1],
+        engine.get_spec(&Tag::new("keter")).unwrap()
+    );
+    engine.check_tags(&tagset).unwrap();
+    engine.check_tagset(&tagset).unwrap();
+
+    let added = engine.make_tagset(vec![Tag::new("humanoid")]).unwrap();
+    engine.check_tag_changes(&tagset, &added, &[], &[]).unwrap();
+    engine
+        .check_tagset_changes(&tagset, &added, &[], &[])
+        .unwrap();
+
+    // Rules are still enforced, with the same errors as for slices
+    let added = engine.make_tagset(vec![Tag::new("tale")]).unwrap();
+    assert_eq!(
+        engine.check_tagset_changes(&tagset, &added, &[], &[Role::new("member")]),
+        engine.check_tag_changes(&tagset, &added, &[], &[Role::new("member")]),
+    );
+    assert!(engine
+        .check_tagset_changes(&tagset, &added, &[], &[Role::new("member")])
+        .is_err());
+
+    let invalid = engine
+        .make_tagset(vec![Tag::new("scp"), Tag::new("tale")])
+        .unwrap();
+    assert_eq!(engine.check_tagset(&invalid), engine.check_tags(&invalid),);
+    assert!(engine.check_tagset(&invalid).is_err());
+    assert_eq!(tagset.into_inner(), tags);
+
+    macro_rules! check_err {
+        ($tags:expr, $error:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect();
+            assert_eq!(engine.make_tagset(tags), Err($error));
+        };
+    }
+
+    check_err!(
+        ["scp", "keter", "scp"],
+        Error::DuplicateTag(Tag::new("scp"))
+    );
+    check_err!(["scp", "primary"], Error::GroupAsTag(Tag::new("primary")));
+    check_err!(["scp", "xyz"], Error::MissingTag(Tag::new("xyz")));
+}
+
+#[test]
+fn test_computed_tags() {
+    let mut engine = setup();
+    let has_image = engine
+        .add_computed_tag(
+            "has-image",
+            Box::new(|tags| tags.iter().any(|tag| tag == "_image")),
+        )
+        .unwrap();
+
+    assert!(engine.is_computed(&has_image));
+    assert!(!engine.is_computed(&Tag::new("_image")));
+
+    let with_image = [Tag::new("tale"), Tag::new("_image")];
+    let without_image = [Tag::new("tale"), Tag::new("_cc")];
+    assert_eq!(engine.check_tag(&has_image, &with_image), Ok(true));
+    assert_eq!(engine.check_tag(&has_image, &without_image), Ok(false));
+    assert_eq!(engine.count_tag(&has_image, &with_image), Ok(1));
+    assert_eq!(engine.count_tag_lenient(&has_image, &without_image), 0);
+
+    // Other tags can depend on it
+    let illustrated = engine
+        .add_tag(
+            "illustrated",
+            TemplateTagSpec {
+                required_tags: vec![Tag::clone(&has_image)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let mut tags = with_image.to_vec();
+    tags.push(Tag::clone(&illustrated));
+    engine.check_tags(&tags).unwrap();
+    assert_eq!(
+        engine.check_tag_changes(&tags, &[], &[Tag::new("_image")], &[]),
+        Err(Error::RequiresTags(
+            Tag::clone(&illustrated),
+            vec![Tag::clone(&has_image)],
+        )),
+    );
+
+    // It cannot be applied directly, even by admins
+    assert_eq!(
+        engine.check_tag_changes(
+            &with_image,
+            &[Tag::clone(&has_image)],
+            &[],
+            &[Role::new("admin")]
+        ),
+        Err(Error::ComputedTag(Tag::clone(&has_image))),
+    );
+    assert_eq!(
+        engine.check_tag_changes(&with_image, &[], &[Tag::clone(&has_image)], &[]),
+        Err(Error::ComputedTag(Tag::clone(&has_image))),
+    );
+
+    // Re-registering makes it an ordinary tag
+    engine
+        .add_tag("has-image", TemplateTagSpec::default())
+        .unwrap();
+    assert!(!engine.is_computed(&has_image));
+    assert_eq!(engine.check_tag(&has_image, &with_image), Ok(false));
+}
+
+#[test]
+fn test_external_tags() {
+    let mut engine = setup();
+    let indexed = Tag::new("system-indexed");
+    let featured = engine
+        .add_tag(
+            "featured",
+            TemplateTagSpec {
+                required_tags: vec![Tag::clone(&indexed)],
+                conflicting_tags: vec![Tag::new("_cc")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let tags = [Tag::new("tale"), Tag::clone(&featured)];
+    let mut external = HashSet::new();
+
+    assert_eq!(
+        engine.check_tags_with_external(&tags, &external),
+        Err(Error::RequiresTags(
+            Tag::clone(&featured),
+            vec![Tag::clone(&indexed)],
+        )),
+    );
+
+    // Satisfied solely by the external tag
+    external.insert(Tag::clone(&indexed));
+    engine.check_tags_with_external(&tags, &external).unwrap();
+    assert!(engine.check_tags(&tags).is_err());
+
+    // External tags do not affect conflicts
+    external.insert(Tag::new("_cc"));
+    engine.check_tags_with_external(&tags, &external).unwrap();
+
+    let tags = [Tag::new("tale"), Tag::new("_cc"), Tag::clone(&featured)];
+    assert_eq!(
+        engine.check_tags_with_external(&tags, &external),
+        Err(Error::IncompatibleTags(
+            Tag::clone(&featured),
+            Tag::new("_cc")
+        )),
+    );
+}
+
+#[test]
+fn test_check_lazy() {
+    let engine = setup();
+
+    // Valid tagsets produce nothing
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+    assert_eq!(engine.check_tags_lazy(&tags).count(), 0);
+
+    let tags = [
+        Tag::new("scp"),
+        Tag::new("tale"),
+        Tag::new("ontokinetic"),
+        Tag::new("_cc"),
+        Tag::new("_image"),
+    ];
+
+    let errors = engine.check_tags_lazy(&tags).take(2).collect::<Vec<_>>();
+    assert_eq!(Some(&errors[0]), engine.check_tags(&tags).err().as_ref());
+    assert_eq!(
+        errors,
+        [
+            Error::IncompatibleViaGroup(Tag::new("scp"), Tag::new("tale"), Tag::new("primary")),
+            Error::IncompatibleViaGroup(Tag::new("tale"), Tag::new("scp"), Tag::new("primary")),
+        ],
+    );
+
+    // Continues past the first errors
+    let errors = engine.check_tags_lazy(&tags).skip(2).collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        [
+            Error::IncompatibleTags(Tag::new("_cc"), Tag::new("_image")),
+            Error::IncompatibleTags(Tag::new("_image"), Tag::new("_cc")),
+        ],
+    );
+
+    // Set-wide rules are checked last
+    let mut engine = engine;
+    let object_class = Tag::new("object-class");
+    engine
+        .set_group_cardinality(&object_class, Cardinality::exactly(1))
+        .unwrap();
+    let tags = [Tag::new("scp"), Tag::new("tale")];
+    let last = engine.check_tags_lazy(&tags).last();
+    assert_eq!(
+        last,
+        Some(Error::GroupCardinality(
+            object_class,
+            Cardinality::exactly(1),
+            vec![]
+        )),
+    );
+}
+
+#[test]
+fn test_check_draft() {
+    let engine = setup();
+
+    // Unmet requirements are only warnings
+    let tags = [Tag::new("euclid")];
+    let report = engine.check_tags_draft(&tags);
+    assert!(report.is_ok());
+    assert!(!report.is_complete());
+    assert_eq!(
+        report.warnings,
+        [Error::RequiresTags(
+            Tag::new("euclid"),
+            vec![Tag::new("scp")]
+        )],
+    );
+    assert_eq!(
+        engine.check_tags(&tags),
+        Err(Error::RequiresTags(
+            Tag::new("euclid"),
+            vec![Tag::new("scp")]
+        )),
+    );
+
+    let tags = [Tag::new("creepypasta"), Tag::new("euclid")];
+    assert_eq!(engine.check_tags_draft(&tags).warnings.len(), 2);
+
+    // Complete tagsets produce no warnings
+    let report = engine.check_tags_draft(&[Tag::new("scp"), Tag::new("euclid")]);
+    assert!(report.is_complete());
+    assert_eq!(report.into_result(), Ok(vec![]));
+
+    // Conflicts are still errors
+    let tags = [Tag::new("euclid"), Tag::new("_cc"), Tag::new("_image")];
+    let report = engine.check_tags_draft(&tags);
+    assert!(!report.is_ok());
+    assert!(report.warnings.is_empty());
+    assert_eq!(
+        report.into_result(),
+        Err(Error::IncompatibleTags(Tag::new("_cc"), Tag::new("_image"))),
+    );
+
+    assert_eq!(
+        engine.check_tags_draft(&[Tag::new("xyz")]).error,
+        Some(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_check_all() {
+    let engine = setup();
+    assert!(engine.check_tags_all(&[Tag::new("scp")]).is_empty());
+
+    let tags = [
+        Tag::new("tale"),
+        Tag::new("_image"),
+        Tag::new("scp"),
+        Tag::new("_cc"),
+        Tag::new("euclid"),
+        Tag::new("creepypasta"),
+    ];
+
+    // Repeated runs agree, and are sorted by tag
+    let errors = engine.check_tags_all(&tags);
+    assert_eq!(errors, engine.check_tags_all(&tags));
+    assert_eq!(
+        errors,
+        [
+            Error::IncompatibleTags(Tag::new("_cc"), Tag::new("_image")),
+            Error::IncompatibleTags(Tag::new("_image"), Tag::new("_cc")),
+            Error::IncompatibleViaGroup(Tag::new("scp"), Tag::new("tale"), Tag::new("primary")),
+            Error::IncompatibleViaGroup(Tag::new("tale"), Tag::new("scp"), Tag::new("primary")),
+        ],
+    );
+
+    // Errors without a tag come first, then by code
+    let mut errors = vec![
+        Error::RequiresTags(Tag::new("b"), vec![]),
+        Error::MissingTag(Tag::new("b")),
+        Error::NoSuchRole(str!("z")),
+        Error::RequiresTags(Tag::new("a"), vec![]),
+    ];
+    sort_errors(&mut errors);
+    assert_eq!(
+        errors,
+        [
+            Error::NoSuchRole(str!("z")),
+            Error::RequiresTags(Tag::new("a"), vec![]),
+            Error::MissingTag(Tag::new("b")),
+            Error::RequiresTags(Tag::new("b"), vec![]),
+        ],
+    );
+}
+
+#[test]
+fn test_incremental() {
+    let mut engine = setup();
+    engine
+        .set_group_cardinality(&Tag::new("object-class"), Cardinality::at_most(1))
+        .unwrap();
+    engine
+        .add_exclusion_set(vec![Tag::new("humanoid"), Tag::new("amorphous")])
+        .unwrap();
+
+    let mut checker = engine.incremental_checker(&[Tag::new("scp")]);
+    assert_eq!(checker.check(), Ok(()));
+
+    let edits: &[(&[&str], &[&str])] = &[
+        (&["keter"], &[]),
+        (&["euclid"], &[]),
+        (&[], &["keter"]),
+        (&["tale"], &[]),
+        (&[], &["scp"]),
+        (&["scp"], &["tale"]),
+        (&["humanoid"], &[]),
+        (&["amorphous"], &[]),
+        (&[], &["humanoid"]),
+        (&["xyz"], &[]),
+        (&["_cc"], &["xyz"]),
+        (&["_image"], &[]),
+        (&[], &["_cc"]),
+        (&[], &["scp"]),
+        (&["scp"], &[]),
+    ];
+
+    for (added, removed) in edits {
+        let added = added.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+        let removed = removed
+            .iter()
+            .map(|name| Tag::new(*name))
+            .collect::<Vec<_>>();
+
+        let result = checker.update(&added, &removed);
+        assert_eq!(
+            result,
+            engine.check_tags(checker.tags()),
+            "Mismatch after adding {:?} and removing {:?}",
+            added,
+            removed,
+        );
+        assert_eq!(checker.check(), result);
+    }
+
+    assert_eq!(
+        checker.tags(),
+        [
+            Tag::new("euclid"),
+            Tag::new("amorphous"),
+            Tag::new("_image"),
+            Tag::new("scp"),
+        ],
+    );
+}
+
+#[test]
+fn test_check_for_publish() {
+    let mut engine = setup();
+    engine
+        .add_tag(
+            "contest-entry",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("scp")],
+                publish_required_tags: vec![Tag::new("licensing"), Tag::new("_image")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let draft = vec![Tag::new("scp"), Tag::new("contest-entry")];
+    assert_eq!(engine.check_tags(&draft), Ok(()));
+    assert_eq!(
+        engine.check_tags_for_publish(&draft),
+        Err(Error::RequiresOneOf(
+            Tag::new("contest-entry"),
+            Tag::new("licensing"),
+            engine.tags_in_group(&Tag::new("licensing")),
+        )),
+    );
+
+    let mut complete = draft.clone();
+    complete.push(Tag::new("_image"));
+    assert_eq!(engine.check_tags_for_publish(&complete), Ok(()));
+
+    let mut licensed = draft.clone();
+    licensed.push(Tag::new("_cc"));
+    assert_eq!(
+        engine.check_tags_for_publish(&licensed),
+        Err(Error::RequiresTags(
+            Tag::new("contest-entry"),
+            vec![Tag::new("licensing"), Tag::new("_image")],
+        )),
+    );
+
+    // Base requirements are still enforced first
+    assert_eq!(
+        engine.check_tags_for_publish(&[Tag::new("contest-entry"), Tag::new("_image")]),
+        Err(Error::RequiresTags(
+            Tag::new("contest-entry"),
+            vec![Tag::new("scp")],
+        )),
     );
 }
@@ -0,0 +1,148 @@
+/*
+ * test/diff.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::load::config;
+use super::prelude::*;
+
+#[test]
+fn test_diff() {
+    let engine = setup();
+    assert!(engine.diff(&engine).is_empty());
+    assert!(engine.diff(&engine.clone()).is_empty());
+
+    let mut other = engine.clone();
+    other.add_tag("rating", TemplateTagSpec::default()).unwrap();
+    other.delete_tag(&Tag::new("tale"));
+    other.get_spec_mut(&Tag::new("scp")).unwrap().priority = 5;
+    other.add_group("format").unwrap();
+    other.delete_role(&Role::new("member"));
+
+    let diff = engine.diff(&other);
+    assert_eq!(diff.added_tags, [Tag::new("rating")]);
+    assert_eq!(diff.removed_tags, [Tag::new("tale")]);
+    assert_eq!(
+        diff.changed_tags,
+        [Tag::new("creepypasta"), Tag::new("scp")]
+    );
+    assert_eq!(diff.added_groups, [Tag::new("format")]);
+    assert!(diff.removed_groups.is_empty());
+    assert!(diff.added_roles.is_empty());
+    assert_eq!(diff.removed_roles, [Role::new("member")]);
+
+    // Reversed comparison swaps additions and removals
+    let reverse = other.diff(&engine);
+    assert_eq!(reverse.added_tags, diff.removed_tags);
+    assert_eq!(reverse.removed_tags, diff.added_tags);
+    assert_eq!(reverse.changed_tags, diff.changed_tags);
+    assert_eq!(reverse.removed_groups, diff.added_groups);
+}
+
+#[test]
+fn test_drift() {
+    let config = config();
+    let mut engine = Engine::default();
+    config.clone().apply(&mut engine).unwrap();
+    assert!(config.drift(&engine).unwrap().is_empty());
+
+    let before = engine.fingerprint();
+    engine.add_tag("hub", TemplateTagSpec::default()).unwrap();
+
+    let drift = config.drift(&engine).unwrap();
+    assert_eq!(drift.added_tags, [Tag::new("hub")]);
+    assert!(drift.removed_tags.is_empty());
+    assert!(drift.changed_tags.is_empty());
+
+    // Checking drift doesn't modify anything
+    engine.delete_tag(&Tag::new("hub"));
+    assert_eq!(engine.fingerprint(), before);
+    assert_eq!(config, self::config());
+
+    // Configurations which cannot be loaded fail rather than panicking
+    let mut broken = config;
+    broken.tags[0].requires = Some(vec![str!("xyz")]);
+    assert_eq!(
+        broken.drift(&engine).err(),
+        Some(Error::NoSuchTag(str!("xyz"))),
+    );
+}
+
+#[test]
+fn test_apply_diff() {
+    let mut engine = setup();
+    let mut other = engine.clone();
+    other.add_tag("rating", TemplateTagSpec::default()).unwrap();
+    other.delete_tag(&Tag::new("tale"));
+    other.get_spec_mut(&Tag::new("scp")).unwrap().priority = 5;
+    other.add_group("format").unwrap();
+    other.delete_group(&Tag::new("contests"));
+    other.add_role("staff").unwrap();
+    other.delete_role(&Role::new("member"));
+
+    let diff = engine.diff(&other);
+    engine.apply_diff(&diff).unwrap();
+    assert!(engine.diff(&other).is_empty());
+    assert_eq!(engine.fingerprint(), other.fingerprint());
+
+    // Applying again fails, as the preconditions no longer hold
+    assert_eq!(
+        engine.apply_diff(&diff),
+        Err(Error::NameCollision(str!("rating"))),
+    );
+
+    let mut diff = diff;
+    diff.added_tags.clear();
+    diff.added_groups.clear();
+    assert_eq!(
+        engine.apply_diff(&diff),
+        Err(Error::MissingTag(Tag::new("tale"))),
+    );
+
+    // Failures leave the engine untouched
+    assert_eq!(engine.fingerprint(), other.fingerprint());
+}
+
+#[test]
+fn test_apply_diff_kind_change() {
+    let mut engine = setup();
+    let mut other = engine.clone();
+    let (goi, contests) = (Tag::new("goi"), Tag::new("contests"));
+
+    // "goi" goes from a tag acting as a group to a plain group
+    engine
+        .add_tag(
+            "goi",
+            TemplateTagSpec {
+                is_also_group: true,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    other.add_group("goi").unwrap();
+
+    // "contests" goes from a group to a tag
+    other.delete_group(&contests);
+    other
+        .add_tag("contests", TemplateTagSpec::default())
+        .unwrap();
+
+    let diff = engine.diff(&other);
+    assert_eq!(diff.removed_tags, [Tag::clone(&goi)]);
+    assert_eq!(diff.added_groups, [Tag::clone(&goi)]);
+    assert_eq!(diff.removed_groups, [Tag::clone(&contests)]);
+    assert_eq!(diff.added_tags, [Tag::clone(&contests)]);
+
+    engine.apply_diff(&diff).unwrap();
+    assert!(engine.diff(&other).is_empty());
+    assert_eq!(engine.fingerprint(), other.fingerprint());
+    assert_eq!(engine.tags_in_group(&goi).len(), 3);
+    assert!(!engine.is_group(&contests));
+}
@@ -11,13 +11,15 @@
  */
 
 use super::prelude::*;
+use crate::CheckFlags;
+use std::collections::{HashMap, HashSet};
 
 #[test]
 fn add_remove_tags() {
     let mut engine = Engine::default();
 
     assert_eq!(engine.has_tag("test"), false);
-    let tag = engine.add_tag("test", TemplateTagSpec::default());
+    let tag = engine.add_tag("test", TemplateTagSpec::default()).unwrap();
     assert_eq!(engine.has_tag("test"), true);
     assert_eq!(engine.get_tag("test").unwrap(), tag);
 
@@ -37,7 +39,7 @@ fn add_remove_specs() {
 
     {
         assert_eq!(engine.has_tag("apple"), false);
-        let tag = engine.add_tag("apple", apple);
+        let tag = engine.add_tag("apple", apple).unwrap();
         assert_eq!(engine.has_tag("apple"), true);
 
         let spec = engine.get_spec(&tag).unwrap();
@@ -46,7 +48,7 @@ fn add_remove_specs() {
 
     {
         assert_eq!(engine.has_tag("banana"), false);
-        let tag = engine.add_tag("banana", banana);
+        let tag = engine.add_tag("banana", banana).unwrap();
         assert_eq!(engine.has_tag("apple"), true);
         assert_eq!(engine.has_tag("banana"), true);
 
@@ -55,12 +57,35 @@ fn add_remove_specs() {
     }
 }
 
+#[test]
+fn clone_specs() {
+    let mut engine = setup();
+    let euclid = Tag::new("euclid");
+
+    let mut template = engine.clone_spec(&euclid).unwrap();
+    template.priority = 1;
+    let new_class = engine.add_tag("new-class", template).unwrap();
+
+    let original = engine.get_spec(&euclid).unwrap();
+    let copy = engine.get_spec(&new_class).unwrap();
+    assert_eq!(copy.tag(), new_class);
+    assert_eq!(copy.required_tags, original.required_tags);
+    assert_eq!(copy.conflicting_tags, original.conflicting_tags);
+    assert_eq!(copy.groups, original.groups);
+    assert_eq!(copy.priority, 1);
+
+    assert_eq!(
+        engine.clone_spec(&Tag::new("primary")),
+        Err(Error::MissingTag(Tag::new("primary"))),
+    );
+}
+
 #[test]
 fn add_remove_groups() {
     let mut engine = Engine::default();
 
     assert_eq!(engine.has_tag("fruit"), false);
-    let tag = engine.add_group("fruit");
+    let tag = engine.add_group("fruit").unwrap();
     assert_eq!(engine.has_tag("fruit"), true);
     assert_eq!(engine.get_tag("fruit").unwrap(), tag);
     assert_eq!(engine.get_spec(&tag).is_err(), true);
@@ -70,15 +95,443 @@ fn add_remove_groups() {
     assert_eq!(engine.has_tag("fruit"), false);
 }
 
+#[test]
+fn mutually_exclusive_groups() {
+    let mut engine = setup();
+    let primary = Tag::new("primary");
+    let members = engine.tags_in_group(&primary);
+    assert!(members.len() > 2);
+
+    engine.make_group_mutually_exclusive(&primary).unwrap();
+
+    for member in &members {
+        let conflicts = &engine.get_spec(member).unwrap().conflicting_tags;
+
+        for other in &members {
+            assert_eq!(
+                conflicts.contains(other),
+                other != member,
+                "Expected {:?} to conflict with {:?}",
+                member,
+                other,
+            );
+        }
+
+        // Existing conflicts are not duplicated
+        let mut deduped = conflicts.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(deduped.len(), conflicts.len());
+    }
+
+    // Now enforced even without conflicting with the group itself
+    for member in &members {
+        let spec = engine.get_spec_mut(member).unwrap();
+        spec.conflicting_tags.retain(|tag| *tag != primary);
+    }
+
+    let tags = [Tag::new("scp"), Tag::new("tale")];
+    assert_eq!(
+        engine.check_tags_with_flags(&tags, CheckFlags::CONFLICTS),
+        Err(Error::IncompatibleTags(Tag::new("scp"), Tag::new("tale"))),
+    );
+
+    // Only current members are affected
+    let essay = engine
+        .add_tag(
+            "essay",
+            TemplateTagSpec {
+                groups: vec![Tag::clone(&primary)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    assert!(engine.get_spec(&essay).unwrap().conflicting_tags.is_empty());
+
+    assert_eq!(
+        engine.make_group_mutually_exclusive(&Tag::new("scp")),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+}
+
+#[test]
+fn group_membership() {
+    let mut engine = setup();
+    let (euclid, object_class) = (Tag::new("euclid"), Tag::new("object-class"));
+    let attribute = Tag::new("attribute");
+
+    assert!(engine.tags_in_group(&object_class).contains(&euclid));
+    engine.remove_from_group(&euclid, &object_class).unwrap();
+    assert!(!engine.tags_in_group(&object_class).contains(&euclid));
+    assert!(engine.has_tag("euclid"));
+    assert!(engine.is_group(&object_class));
+
+    // Removing again is harmless
+    engine.remove_from_group(&euclid, &object_class).unwrap();
+
+    engine.add_to_group(&euclid, &attribute).unwrap();
+    engine.add_to_group(&euclid, &attribute).unwrap();
+    assert!(engine.tags_in_group(&attribute).contains(&euclid));
+    assert_eq!(engine.get_spec(&euclid).unwrap().groups, [attribute]);
+
+    assert_eq!(
+        engine.add_to_group(&euclid, &Tag::new("scp")),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+    assert_eq!(
+        engine.add_to_group(&Tag::new("primary"), &object_class),
+        Err(Error::MissingTag(Tag::new("primary"))),
+    );
+}
+
 #[test]
 fn add_remove_roles() {
     let mut engine = Engine::default();
 
     assert_eq!(engine.has_role("admin"), false);
-    let role = engine.add_role("admin");
+    let role = engine.add_role("admin").unwrap();
     assert_eq!(engine.has_role("admin"), true);
     assert_eq!(engine.has_tag("admin"), false);
 
     engine.delete_role(&role);
     assert_eq!(engine.has_role("fruit"), false);
 }
+
+#[test]
+fn try_delete_roles() {
+    let mut engine = setup();
+    let licensing = Role::new("licensing");
+    let cc = Tag::new("_cc");
+
+    assert_eq!(
+        engine.try_delete_role(&licensing),
+        Err(Error::RoleInUse(
+            Role::clone(&licensing),
+            vec![Tag::clone(&cc)]
+        )),
+    );
+    assert!(engine.has_role("licensing"));
+    assert_eq!(
+        &*engine.get_spec(&cc).unwrap().needed_roles,
+        [Role::clone(&licensing)]
+    );
+
+    engine.get_spec_mut(&cc).unwrap().needed_roles = vec![].into();
+    engine.try_delete_role(&licensing).unwrap();
+    assert!(!engine.has_role("licensing"));
+
+    // Unused roles can be deleted directly
+    engine.try_delete_role(&Role::new("moderator")).unwrap();
+    assert_eq!(
+        engine.try_delete_role(&Role::new("moderator")),
+        Err(Error::MissingRole(Role::new("moderator"))),
+    );
+}
+
+#[test]
+fn snapshot_restore() {
+    let mut engine = setup();
+    let fingerprint = engine.fingerprint();
+    let snapshot = engine.snapshot();
+
+    for name in &["scp", "tale", "_cc", "keter"] {
+        let tag = engine.get_tag(*name).unwrap();
+        engine.delete_tag(&tag);
+    }
+
+    let role = engine.get_role("admin").unwrap();
+    engine.delete_role(&role);
+
+    assert_eq!(engine.has_tag("scp"), false);
+    assert_ne!(engine.fingerprint(), fingerprint);
+
+    engine.restore(snapshot);
+    assert_eq!(engine.has_tag("scp"), true);
+    assert_eq!(engine.has_role("admin"), true);
+    assert_eq!(engine.fingerprint(), fingerprint);
+    assert_eq!(engine.fingerprint(), setup().fingerprint());
+}
+
+#[test]
+fn shrink_to_fit() {
+    let mut engine = setup();
+    let fingerprint = engine.fingerprint();
+
+    for i in 0..100 {
+        let tag = engine
+            .add_tag(format!("temp-{}", i), TemplateTagSpec::default())
+            .unwrap();
+        engine.delete_tag(&tag);
+    }
+
+    engine.shrink_to_fit();
+    assert_eq!(engine.fingerprint(), fingerprint);
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("keter")])
+        .unwrap();
+}
+
+#[test]
+fn name_collisions() {
+    let mut engine = setup();
+
+    // Group names cannot be reused for tags
+    assert_eq!(
+        engine.add_tag("primary", TemplateTagSpec::default()),
+        Err(Error::NameCollision(str!("primary")))
+    );
+    assert_eq!(engine.is_group(&Tag::new("primary")), true);
+
+    // Tag names cannot be reused for groups
+    assert_eq!(
+        engine.add_group("scp"),
+        Err(Error::NameCollision(str!("scp")))
+    );
+    assert_eq!(engine.is_group(&Tag::new("scp")), false);
+    assert_eq!(engine.get_spec(&Tag::new("scp")).is_ok(), true);
+
+    // Re-registering under the same kind is permitted
+    engine.add_group("primary").unwrap();
+    engine.add_tag("scp", TemplateTagSpec::default()).unwrap();
+}
+
+#[test]
+fn duplicate_names() {
+    let mut engine = setup();
+
+    assert_eq!(
+        engine.try_add_group("primary"),
+        Err(Error::NameCollision(str!("primary")))
+    );
+    assert_eq!(
+        engine.try_add_group("scp"),
+        Err(Error::NameCollision(str!("scp")))
+    );
+    assert_eq!(
+        engine.try_add_role("admin"),
+        Err(Error::NameCollision(str!("admin")))
+    );
+
+    // Roles and tags are separate namespaces
+    let group = engine.try_add_group("format").unwrap();
+    assert!(engine.is_group(&group));
+    let role = engine.try_add_role("scp").unwrap();
+    assert!(engine.has_role("scp"));
+
+    assert_eq!(
+        engine.try_add_group("format"),
+        Err(Error::NameCollision(str!("format")))
+    );
+    assert_eq!(
+        engine.try_add_role(&*role),
+        Err(Error::NameCollision(str!("scp")))
+    );
+
+    // Names are still checked against the policy
+    assert_eq!(engine.try_add_role(""), Err(Error::InvalidName(str!(""))));
+}
+
+#[test]
+fn rename_group() {
+    let mut engine = setup();
+    let primary = engine.get_tag("primary").unwrap();
+    let group = engine.rename_group(&primary, "main").unwrap();
+
+    assert!(!engine.has_tag("primary"));
+    assert!(engine.is_group(&group));
+
+    let spec = engine.get_spec(&Tag::new("scp")).unwrap();
+    assert_eq!(spec.groups, [Tag::new("main")]);
+    assert_eq!(spec.conflicting_tags, [Tag::new("main")]);
+
+    let spec = engine.get_spec(&Tag::new("amorphous")).unwrap();
+    assert_eq!(spec.required_tags, [Tag::new("main")]);
+
+    // Behavior is unchanged under the new name
+    assert!(engine
+        .check_tags(&[Tag::new("scp"), Tag::new("tale")])
+        .is_err());
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("amorphous")])
+        .unwrap();
+
+    // Invalid renames
+    assert_eq!(
+        engine.rename_group(&Tag::new("scp"), "xyz"),
+        Err(Error::NotGroup(Tag::new("scp"))),
+    );
+    assert_eq!(
+        engine.rename_group(&primary, "xyz"),
+        Err(Error::NotGroup(Tag::new("primary"))),
+    );
+    assert_eq!(
+        engine.rename_group(&group, "tale"),
+        Err(Error::NameCollision(str!("tale"))),
+    );
+    assert_eq!(
+        engine.rename_group(&group, "licensing"),
+        Err(Error::NameCollision(str!("licensing"))),
+    );
+}
+
+#[test]
+fn prune() {
+    let mut engine = setup();
+    engine.add_role("unused").unwrap();
+    engine.add_group("empty").unwrap();
+
+    let report = engine.prune();
+    assert_eq!(
+        report.roles,
+        [
+            Role::new("member"),
+            Role::new("moderator"),
+            Role::new("unused")
+        ],
+    );
+    assert_eq!(report.groups, [Tag::new("empty")]);
+
+    assert!(!engine.has_role("unused"));
+    assert!(!engine.has_tag("empty"));
+    assert!(engine.has_role("licensing"));
+    assert!(engine.is_group(&Tag::new("primary")));
+    assert!(engine.is_group(&Tag::new("object-class")));
+
+    // Nothing left to remove
+    assert!(engine.prune().is_empty());
+}
+
+#[test]
+fn all_needed_roles() {
+    let mut engine = setup();
+
+    let mut roles = engine.all_needed_roles().into_iter().collect::<Vec<_>>();
+    roles.sort();
+    assert_eq!(
+        roles,
+        [
+            Role::new("admin"),
+            Role::new("licensing"),
+            Role::new("locked")
+        ],
+    );
+    assert!(engine.has_role("moderator"));
+
+    // Matches what pruning keeps
+    engine.prune();
+    assert_eq!(engine.get_roles(), &engine.all_needed_roles());
+}
+
+#[test]
+fn from_parts() {
+    let mut engine = setup();
+    engine.add_group("goi").unwrap();
+
+    let before = engine.fingerprint();
+    let (specs, tags, roles) = engine.into_parts();
+    let engine = Engine::from_parts(specs, tags, roles).unwrap();
+    assert_eq!(engine.fingerprint(), before);
+
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("keter"), Tag::new("humanoid")])
+        .unwrap();
+    assert!(engine
+        .check_tags(&[Tag::new("scp"), Tag::new("tale")])
+        .is_err());
+
+    // Building from scratch
+    let scp = Tag::new("scp");
+    let primary = Tag::new("primary");
+    let member = Role::new("member");
+    let spec = TagSpec::from_template(
+        &scp,
+        TemplateTagSpec {
+            groups: vec![Tag::clone(&primary)],
+            needed_roles: vec![Role::clone(&member)],
+            ..TemplateTagSpec::default()
+        },
+    );
+
+    let specs = vec![(Tag::clone(&scp), spec)]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let tags = [Tag::clone(&scp), Tag::clone(&primary)];
+    let roles = [Role::clone(&member)];
+
+    macro_rules! check {
+        ($specs:expr, $tags:expr, $roles:expr, $result:expr) => {
+            let tags = $tags.iter().cloned().collect::<HashSet<_>>();
+            let roles = $roles.iter().cloned().collect::<HashSet<_>>();
+            let result = Engine::from_parts($specs.clone(), tags, roles).map(|_| ());
+            assert_eq!(result, $result);
+        };
+    }
+
+    check!(specs, tags, roles, Ok(()));
+    check!(
+        specs,
+        tags[..1],
+        roles,
+        Err(Error::MissingTag(Tag::clone(&primary)))
+    );
+    check!(
+        specs,
+        tags[1..],
+        roles,
+        Err(Error::MissingTag(Tag::clone(&scp)))
+    );
+    check!(
+        specs,
+        tags,
+        [] as [Role; 0],
+        Err(Error::MissingRole(member))
+    );
+
+    let mut specs = specs;
+    specs.get_mut(&scp).unwrap().groups = vec![Tag::clone(&scp)];
+    check!(specs, tags, roles, Err(Error::NotGroup(scp)));
+}
+
+#[test]
+fn builder_source() {
+    let mut engine = setup();
+    let source = engine.to_builder_source();
+    assert_eq!(source, engine.clone().to_builder_source());
+
+    let scp = concat!(
+        "engine.add_tag(\n",
+        "    \"scp\",\n",
+        "    TemplateTagSpec {\n",
+        "        conflicting_tags: vec![Tag::new(\"primary\")],\n",
+        "        groups: vec![Tag::new(\"primary\")],\n",
+        "        ..TemplateTagSpec::default()\n",
+        "    },\n",
+        ")?;\n",
+    );
+    assert!(source.contains(scp), "Unexpected source:\n{}", source);
+    assert!(source.contains("engine.add_tag(\"co-authored\", TemplateTagSpec::default())?;\n"));
+
+    // Roles come before groups, which come before tags
+    let position = |needle| source.find(needle).unwrap();
+    assert!(position("engine.add_role(\"admin\")") < position("engine.add_group(\"primary\")"));
+    assert!(position("engine.add_group(\"primary\")") < position("engine.add_tag("));
+
+    engine
+        .set_display_name(&Tag::new("licensing"), Some(str!("Licensing")))
+        .unwrap();
+    engine
+        .add_exclusion_set(vec![Tag::new("scp"), Tag::new("hub")])
+        .unwrap();
+    engine
+        .register_template("new-scp", vec![Tag::new("scp"), Tag::new("euclid")])
+        .unwrap();
+
+    let source = engine.to_builder_source();
+    for line in &[
+        "engine.set_display_name(&Tag::new(\"licensing\"), Some(String::from(\"Licensing\")))?;\n",
+        "engine.add_exclusion_set(vec![Tag::new(\"hub\"), Tag::new(\"scp\")])?;\n",
+        "engine.register_template(\"new-scp\", vec![Tag::new(\"scp\"), Tag::new(\"euclid\")])?;\n",
+    ] {
+        assert!(source.contains(line), "Missing {:?} in:\n{}", line, source);
+    }
+}
@@ -11,6 +11,7 @@
  */
 
 use super::prelude::*;
+use crate::Cardinality;
 
 #[test]
 fn test_has_tags() {
@@ -92,3 +93,338 @@ fn test_count_tags() {
     check!(tags, "attribute", 4);
     check!(tags, "contests", 0);
 }
+
+#[test]
+fn test_count_lenient() {
+    let engine = setup();
+    let primary = Tag::new("primary");
+    let tags = [
+        Tag::new("scp"),
+        Tag::new("xyz"),
+        Tag::new("tale"),
+        Tag::new("humanoid"),
+    ];
+
+    assert_eq!(
+        engine.count_tag(&primary, &tags),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+    assert_eq!(engine.count_tag_lenient(&primary, &tags), 2);
+    assert_eq!(engine.count_tag_lenient(&Tag::new("scp"), &tags), 1);
+    assert_eq!(engine.count_tag_lenient(&Tag::new("xyz"), &tags), 0);
+
+    // Matches the strict count when all tags are registered
+    let tags = [Tag::new("scp"), Tag::new("tale"), Tag::new("humanoid")];
+    assert_eq!(
+        engine.count_tag(&primary, &tags),
+        Ok(engine.count_tag_lenient(&primary, &tags)),
+    );
+}
+
+#[test]
+fn test_tags_matching() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($query:expr, $limit:expr, $include_groups:expr, $expected:expr) => {
+            let tags = engine.tags_matching($query, $limit, $include_groups);
+            let expected = $expected
+                .iter()
+                .map(|name| Tag::new(*name))
+                .collect::<Vec<_>>();
+
+            assert_eq!(tags, expected);
+        };
+    }
+
+    check!(
+        "ic",
+        10,
+        false,
+        [
+            "antimemetic",
+            "cliche2019",
+            "electronic",
+            "esoteric-class",
+            "ontokinetic"
+        ]
+    );
+    check!(
+        "IC",
+        10,
+        true,
+        [
+            "antimemetic",
+            "cliche2019",
+            "electronic",
+            "esoteric-class",
+            "licensing",
+            "ontokinetic"
+        ]
+    );
+    check!("ic", 2, false, ["antimemetic", "cliche2019"]);
+    check!("e", 3, false, ["electronic", "esoteric-class", "euclid"]);
+    check!("ic-", 10, false, ["esoteric-class"]);
+    check!("xyz", 10, true, [] as [&str; 0]);
+}
+
+#[test]
+fn test_dual_tags() {
+    let mut engine = setup();
+    let goi = engine
+        .add_tag(
+            "goi",
+            TemplateTagSpec {
+                is_also_group: true,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert!(engine.is_group(&goi));
+    assert!(engine.get_spec(&goi).is_ok());
+
+    macro_rules! check {
+        ($tags:expr, $count:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            assert_eq!(engine.count_tag(&goi, &tags).unwrap(), $count);
+            assert_eq!(engine.check_tag(&goi, &tags).unwrap(), $count > 0);
+        };
+    }
+
+    check!(["tale"], 0);
+    check!(["tale", "goi"], 1);
+    check!(["tale", "serpents-hand"], 1);
+    check!(["tale", "goi", "serpents-hand"], 2);
+    check!(
+        ["tale", "goi", "serpents-hand", "global-occult-coalition"],
+        3
+    );
+
+    // Removing the group leaves the tag intact
+    engine.delete_group(&goi);
+    assert!(!engine.is_group(&goi));
+    assert!(engine.has_tag("goi"));
+    assert!(engine.get_spec(&goi).is_ok());
+
+    // Deleting a dual tag removes every reference to it as a group
+    let faction = engine
+        .add_tag(
+            "faction",
+            TemplateTagSpec {
+                is_also_group: true,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    let (serpents_hand, attribute) = (Tag::new("serpents-hand"), Tag::new("attribute"));
+    engine.add_to_group(&serpents_hand, &faction).unwrap();
+    let object_class = Tag::new("object-class");
+    engine.add_subgroup(&faction, &object_class).unwrap();
+    engine.add_subgroup(&attribute, &faction).unwrap();
+    engine
+        .set_group_cardinality(
+            &faction,
+            Cardinality {
+                min: 0,
+                max: Some(1),
+            },
+        )
+        .unwrap();
+
+    engine.delete_tag(&faction);
+    assert!(!engine.is_group(&faction));
+    assert!(!engine
+        .get_spec(&serpents_hand)
+        .unwrap()
+        .groups
+        .contains(&faction));
+    assert!(engine.get_parent_groups(&faction).is_empty());
+    assert!(!engine.get_parent_groups(&object_class).contains(&faction));
+    assert_eq!(engine.get_group_cardinality(&faction), None);
+    engine.validate_references().unwrap();
+}
+
+#[test]
+fn test_names() {
+    let engine = setup();
+
+    let mut tags = engine.tag_names().collect::<Vec<_>>();
+    tags.sort();
+    assert_eq!(tags.len(), engine.get_specs().len());
+    assert!(tags.contains(&"scp"));
+    assert!(tags.contains(&"cliche2019"));
+    assert!(!tags.contains(&"primary"));
+    assert_eq!(&tags[..3], ["_cc", "_image", "admin"]);
+
+    let mut roles = engine.role_names().collect::<Vec<_>>();
+    roles.sort();
+    assert_eq!(
+        roles,
+        ["admin", "licensing", "locked", "member", "moderator"]
+    );
+}
+
+#[test]
+fn test_handles() {
+    let engine = setup();
+
+    assert_eq!(engine.tag_handle("scp"), Some(&Tag::new("scp")));
+    assert_eq!(engine.tag_handle("primary"), Some(&Tag::new("primary")));
+    assert_eq!(engine.tag_handle("xyz"), None);
+    assert_eq!(engine.role_handle("admin"), Some(&Role::new("admin")));
+    assert_eq!(engine.role_handle("xyz"), None);
+
+    let tags = ["scp", "keter"]
+        .iter()
+        .filter_map(|name| engine.tag_handle(name))
+        .cloned()
+        .collect::<Vec<_>>();
+    engine.check_tags(&tags).unwrap();
+}
+
+#[test]
+fn test_display_names() {
+    let mut engine = setup();
+    let object_class = Tag::new("object-class");
+    let scp = Tag::new("scp");
+
+    assert_eq!(engine.display_name(&object_class), "object-class");
+    assert_eq!(engine.display_name(&scp), "scp");
+
+    engine
+        .set_display_name(&object_class, Some(str!("Object Class")))
+        .unwrap();
+    engine
+        .set_display_name(&scp, Some(str!("SCP Article")))
+        .unwrap();
+
+    assert_eq!(engine.display_name(&object_class), "Object Class");
+    assert_eq!(engine.display_name(&scp), "SCP Article");
+    assert_eq!(
+        engine.get_spec(&scp).unwrap().display_name.as_deref(),
+        Some("SCP Article"),
+    );
+
+    // Display names are searchable
+    let matches = engine.tags_matching("article", 10, false);
+    assert_eq!(matches, [Tag::clone(&scp)]);
+
+    // Renaming a group keeps its display name
+    let renamed = engine
+        .rename_group(&object_class, "containment-class")
+        .unwrap();
+    assert_eq!(engine.display_name(&renamed), "Object Class");
+
+    engine.set_display_name(&renamed, None).unwrap();
+    assert_eq!(engine.display_name(&renamed), "containment-class");
+
+    assert_eq!(
+        engine.set_display_name(&Tag::new("nonexistent"), None),
+        Err(Error::MissingTag(Tag::new("nonexistent"))),
+    );
+}
+
+#[test]
+fn test_specs_sorted() {
+    let engine = setup();
+    let specs = engine.specs_sorted();
+
+    assert_eq!(specs.len(), engine.get_specs().len());
+    assert!(specs.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(specs.iter().all(|(tag, spec)| spec.tag() == **tag));
+    assert_eq!(specs, engine.specs_sorted());
+
+    let names = specs.iter().map(|(tag, _)| &***tag).collect::<Vec<&str>>();
+    assert_eq!(&names[..3], ["_cc", "_image", "admin"]);
+    assert_eq!(names.last(), Some(&"thaumiel"));
+}
+
+#[test]
+fn test_aliases() {
+    let mut engine = setup();
+    let keter = Tag::new("keter");
+    engine.add_alias("ketr", &keter).unwrap();
+    engine
+        .add_alias("classes", &Tag::new("object-class"))
+        .unwrap();
+
+    assert_eq!(engine.get_tag("ketr"), Ok(Tag::clone(&keter)));
+    assert_eq!(engine.get_tag("classes"), Ok(Tag::new("object-class")));
+    assert!(!engine.has_tag("ketr"));
+
+    // Aliases cannot shadow names in use, or point nowhere
+    assert_eq!(
+        engine.add_alias("scp", &keter),
+        Err(Error::NameCollision(str!("scp"))),
+    );
+    assert_eq!(
+        engine.add_alias("missing", &Tag::new("xyz")),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+
+    // Nor can tags or groups shadow aliases
+    assert_eq!(
+        engine.add_tag("ketr", TemplateTagSpec::default()),
+        Err(Error::NameCollision(str!("ketr"))),
+    );
+    assert_eq!(
+        engine.add_group("ketr"),
+        Err(Error::NameCollision(str!("ketr"))),
+    );
+    assert_eq!(
+        engine.rename_group(&Tag::new("contests"), "ketr"),
+        Err(Error::NameCollision(str!("ketr"))),
+    );
+    assert_eq!(engine.get_tag("ketr"), Ok(Tag::clone(&keter)));
+
+    // They follow the tags they point to
+    let renamed = engine
+        .rename_group(&Tag::new("object-class"), "containment-class")
+        .unwrap();
+    assert_eq!(engine.get_tag("classes"), Ok(renamed));
+
+    engine.delete_tag(&keter);
+    assert_eq!(engine.get_tag("ketr"), Err(Error::NoSuchTag(str!("ketr"))));
+    assert_eq!(engine.get_aliases().len(), 1);
+
+    engine.delete_alias("classes");
+    assert!(engine.get_aliases().is_empty());
+}
+
+#[test]
+fn test_parse_tags() {
+    let mut engine = setup();
+
+    let tags = engine.parse_tags("scp  euclid,humanoid").unwrap();
+    assert_eq!(
+        tags,
+        [Tag::new("scp"), Tag::new("euclid"), Tag::new("humanoid")],
+    );
+
+    // Round-trips through formatting
+    let formatted = Engine::format_tags(&tags);
+    assert_eq!(formatted, "scp euclid humanoid");
+    assert_eq!(engine.parse_tags(&formatted), Ok(tags));
+
+    // Empty entries are skipped
+    assert_eq!(engine.parse_tags(""), Ok(vec![]));
+    assert_eq!(
+        engine.parse_tags(" ,\ttale,,\n_cc, "),
+        Ok(vec![Tag::new("tale"), Tag::new("_cc")]),
+    );
+
+    // Aliases are resolved
+    engine
+        .add_alias("euclid-class", &Tag::new("euclid"))
+        .unwrap();
+    assert_eq!(
+        engine.parse_tags("scp euclid-class"),
+        Ok(vec![Tag::new("scp"), Tag::new("euclid")]),
+    );
+
+    assert_eq!(
+        engine.parse_tags("scp ecuild"),
+        Err(Error::NoSuchTag(str!("ecuild"))),
+    );
+}
@@ -0,0 +1,69 @@
+/*
+ * test/expiry.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::load::Configuration;
+use chrono::{DateTime, TimeZone, Utc};
+
+fn date(year: i32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap()
+}
+
+#[test]
+fn test_expired_tags() {
+    let mut engine = setup();
+    let now = date(2019);
+    assert!(engine.expired_tags(now).is_empty());
+
+    engine
+        .get_spec_mut(&Tag::new("doomsday2018"))
+        .unwrap()
+        .expires_at = Some(date(2018));
+    engine
+        .get_spec_mut(&Tag::new("cliche2019"))
+        .unwrap()
+        .expires_at = Some(date(2020));
+
+    assert_eq!(engine.expired_tags(now), [Tag::new("doomsday2018")]);
+    assert_eq!(
+        engine.expired_tags(date(2021)),
+        [Tag::new("cliche2019"), Tag::new("doomsday2018")],
+    );
+
+    // Expired tags are still valid
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("doomsday2018")])
+        .unwrap();
+}
+
+#[test]
+fn test_expiry_config() {
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "doomsday2018"
+        expires_at = "2018-12-31T23:59:59Z"
+
+        [[tags]]
+        name = "cliche2019"
+        "#,
+    )
+    .expect("Unable to parse config");
+
+    assert_eq!(config.tags[1].expires_at, None);
+
+    let engine = Engine::from_config(config).unwrap();
+    assert_eq!(engine.expired_tags(date(2019)), [Tag::new("doomsday2018")]);
+    assert!(engine.expired_tags(date(2018)).is_empty());
+}
@@ -0,0 +1,66 @@
+/*
+ * test/flags.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::CheckFlags;
+
+#[test]
+fn test_conflicts_only() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($tags:expr, $flags:expr, $valid:expr) => {
+            let result = engine.check_tags_with_flags(&$tags, $flags);
+            assert_eq!(result.is_ok(), $valid, "Unexpected result: {:?}", result);
+        };
+    }
+
+    // Requirement violation
+    let tags = [Tag::new("euclid"), Tag::new("humanoid")];
+    check!(tags, CheckFlags::all(), false);
+    check!(tags, CheckFlags::CONFLICTS, true);
+    check!(tags, CheckFlags::REQUIREMENTS, false);
+
+    // Conflict violation
+    let tags = [Tag::new("tale"), Tag::new("_image"), Tag::new("_cc")];
+    check!(tags, CheckFlags::all(), false);
+    check!(tags, CheckFlags::CONFLICTS, false);
+    check!(tags, CheckFlags::REQUIREMENTS, true);
+
+    // Group exclusivity violation, which is a conflict with the group
+    let tags = [Tag::new("scp"), Tag::new("tale")];
+    check!(tags, CheckFlags::all(), false);
+    check!(tags, CheckFlags::CONFLICTS, false);
+    check!(tags, CheckFlags::CARDINALITY, true);
+    check!(tags, CheckFlags::empty(), true);
+}
+
+#[test]
+fn test_roles_flag() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($flags:expr, $valid:expr) => {
+            let result = engine.check_tag_changes_with_flags(
+                &[Tag::new("tale"), Tag::new("_cc")],
+                &[],
+                &[Tag::new("_cc")],
+                &[Role::new("member")],
+                $flags,
+            );
+            assert_eq!(result.is_ok(), $valid, "Unexpected result: {:?}", result);
+        };
+    }
+
+    check!(CheckFlags::all(), false);
+    check!(CheckFlags::all() - CheckFlags::ROLES, true);
+}
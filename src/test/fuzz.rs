@@ -0,0 +1,53 @@
+/*
+ * test/fuzz.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::load::Configuration;
+use arbitrary::{Arbitrary, Unstructured};
+
+// Deterministic pseudo-random bytes, so failures are reproducible
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_arbitrary_configs() {
+    for seed in 0..1000 {
+        let bytes = random_bytes(seed, 1024);
+        let mut data = Unstructured::new(&bytes);
+        let config = match Configuration::arbitrary(&mut data) {
+            Ok(config) => config,
+            Err(_) => continue,
+        };
+
+        // Applying must never panic, though it may fail
+        let mut engine = Engine::default();
+        if config.clone().apply(&mut engine).is_ok() {
+            engine.lint();
+            engine.validate();
+
+            let tags = engine.get_specs().keys().cloned().collect::<Vec<_>>();
+            let _ = engine.check_tags(&tags);
+        }
+
+        let _ = Engine::with_config_validated(config);
+    }
+}
@@ -0,0 +1,521 @@
+/*
+ * test/graph.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::{Cardinality, LintWarning};
+
+#[test]
+fn test_edges() {
+    let engine = setup();
+    let edges = engine.edges();
+
+    macro_rules! check {
+        ($list:expr, $tag:expr, $other:expr) => {
+            let edge = (Tag::new($tag), Tag::new($other));
+            assert!($list.contains(&edge), "Missing edge {:?}", edge);
+        };
+    }
+
+    check!(edges.requires, "euclid", "scp");
+    check!(edges.requires, "creepypasta", "tale");
+    check!(edges.conflicts, "_image", "_cc");
+    check!(edges.conflicts, "scp", "primary");
+    check!(edges.memberships, "safe", "object-class");
+    check!(edges.memberships, "hub", "primary");
+
+    // Output is deterministic
+    assert_eq!(edges, engine.edges());
+    assert_eq!(edges, setup().edges());
+
+    let mut requires = edges.requires.clone();
+    requires.sort();
+    assert_eq!(edges.requires, requires);
+
+    // Required groups and alternatives are requirements too
+    let mut engine = setup();
+    engine
+        .add_tag(
+            "review",
+            TemplateTagSpec {
+                required_groups: vec![Tag::new("object-class")],
+                required_any_of: vec![vec![Tag::new("scp"), Tag::new("tale")]],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let edges = engine.edges();
+    check!(edges.requires, "review", "object-class");
+    check!(edges.requires, "review", "scp");
+    check!(edges.requires, "review", "tale");
+}
+
+#[test]
+fn test_can_coexist() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($a:expr, $b:expr, $expected:expr) => {
+            let (a, b) = (Tag::new($a), Tag::new($b));
+            let result = engine.can_coexist(&a, &b).expect("Unable to check tags");
+            assert_eq!(
+                result, $expected,
+                "Unexpected result for {:?} and {:?}",
+                a, b
+            );
+
+            let result = engine.can_coexist(&b, &a).expect("Unable to check tags");
+            assert_eq!(
+                result, $expected,
+                "Result not symmetric for {:?} and {:?}",
+                b, a
+            );
+        };
+    }
+
+    check!("scp", "tale", false);
+    check!("scp", "safe", true);
+    check!("scp", "scp", true);
+    check!("_image", "_cc", false);
+    check!("tale", "_cc", true);
+
+    assert!(engine
+        .can_coexist(&Tag::new("scp"), &Tag::new("xyz"))
+        .is_err());
+}
+
+#[test]
+fn test_common_groups() {
+    let engine = setup();
+
+    macro_rules! check {
+        ($a:expr, $b:expr, $groups:expr) => {
+            let groups = engine
+                .common_groups(&Tag::new($a), &Tag::new($b))
+                .expect("Unable to get common groups");
+            let expected = $groups
+                .iter()
+                .map(|name| Tag::new(*name))
+                .collect::<Vec<_>>();
+            assert_eq!(groups, expected);
+        };
+    }
+
+    check!("safe", "euclid", ["object-class"]);
+    check!("scp", "tale", ["primary"]);
+    check!("scp", "safe", [] as [&str; 0]);
+    check!("co-authored", "co-authored", [] as [&str; 0]);
+
+    assert_eq!(
+        engine.common_groups(&Tag::new("safe"), &Tag::new("object-class")),
+        Err(Error::MissingTag(Tag::new("object-class"))),
+    );
+}
+
+#[test]
+fn test_locked_tags() {
+    let mut engine = setup();
+    engine
+        .add_tag(
+            "review",
+            TemplateTagSpec {
+                required_groups: vec![Tag::new("object-class")],
+                required_any_of: vec![vec![Tag::new("scp"), Tag::new("tale")]],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    macro_rules! check {
+        ($tags:expr, $locked:expr) => {
+            let tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            let locked = engine
+                .locked_tags(&tags)
+                .expect("Unable to get locked tags");
+            assert_eq!(locked, $locked);
+        };
+    }
+
+    check!(["scp", "euclid"], ["scp"]);
+    check!(["scp", "euclid", "humanoid"], ["scp"]);
+    check!(["tale", "humanoid"], ["tale"]);
+    check!(["tale", "creepypasta", "humanoid"], ["tale"]);
+    check!(["tale", "_image"], [] as [&str; 0]);
+    check!(["humanoid"], [] as [&str; 0]);
+
+    assert!(engine
+        .locked_tags(&[Tag::new("scp"), Tag::new("xyz")])
+        .is_err());
+
+    // As are the only tags satisfying required groups and alternatives
+    check!(["scp", "keter", "review"], ["scp", "keter"]);
+    check!(["scp", "hub", "keter", "review"], ["scp", "keter"]);
+}
+
+#[test]
+fn test_mutual_requirements() {
+    let mut engine = setup();
+    assert!(engine.mutual_requirements().is_empty());
+
+    macro_rules! requires {
+        ($tag:expr, $required:expr) => {
+            engine
+                .add_tag(
+                    $tag,
+                    TemplateTagSpec {
+                        required_tags: $required.iter().map(|name| Tag::new(*name)).collect(),
+                        ..TemplateTagSpec::default()
+                    },
+                )
+                .unwrap();
+        };
+    }
+
+    requires!("yin", ["yang"]);
+    requires!("yang", ["yin", "scp"]);
+    requires!("alpha", ["omega", "omega"]);
+    requires!("omega", ["alpha"]);
+    requires!("one-way", ["yin"]);
+
+    assert_eq!(
+        engine.mutual_requirements(),
+        [
+            (Tag::new("alpha"), Tag::new("omega")),
+            (Tag::new("yang"), Tag::new("yin")),
+        ],
+    );
+}
+
+#[test]
+fn test_requires_transitively() {
+    let mut engine = setup();
+
+    macro_rules! check {
+        ($tag:expr, $target:expr, $result:expr) => {
+            let (tag, target) = (Tag::new($tag), Tag::new($target));
+            assert_eq!(
+                engine.requires_transitively(&tag, &target),
+                Ok($result),
+                "Expected {:?} {}to require {:?}",
+                tag,
+                if $result { "" } else { "not " },
+                target,
+            );
+        };
+    }
+
+    check!("creepypasta", "tale", true);
+    check!("euclid", "scp", true);
+    check!("scp", "tale", false);
+    check!("creepypasta", "scp", false);
+    check!("tale", "creepypasta", false);
+
+    // Groups are expanded through their members
+    check!("ontokinetic", "primary", true);
+    check!("ontokinetic", "tale", true);
+
+    // Cycles terminate
+    engine
+        .add_tag(
+            "yin",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("creepypasta")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    engine
+        .get_spec_mut(&Tag::new("creepypasta"))
+        .unwrap()
+        .required_tags
+        .push(Tag::new("yin"));
+    check!("yin", "tale", true);
+    check!("yin", "scp", false);
+
+    assert_eq!(
+        engine.requires_transitively(&Tag::new("scp"), &Tag::new("xyz")),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_tag_depth() {
+    let mut engine = setup();
+
+    macro_rules! check {
+        ($tag:expr, $depth:expr) => {
+            assert_eq!(engine.tag_depth(&Tag::new($tag)), Ok($depth));
+        };
+    }
+
+    check!("scp", 0);
+    check!("tale", 0);
+    check!("creepypasta", 1);
+    check!("euclid", 1);
+    check!("ontokinetic", 1);
+
+    engine
+        .add_tag(
+            "pasta-analysis",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("creepypasta")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    check!("pasta-analysis", 2);
+
+    // Groups are followed through their members
+    engine
+        .add_tag(
+            "attribute-list",
+            TemplateTagSpec {
+                required_groups: vec![Tag::new("attribute")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    check!("attribute-list", 2);
+
+    assert_eq!(
+        engine.tag_depth(&Tag::new("primary")),
+        Err(Error::MissingTag(Tag::new("primary"))),
+    );
+
+    // Cycles are reported rather than followed forever
+    engine
+        .get_spec_mut(&Tag::new("tale"))
+        .unwrap()
+        .required_tags
+        .push(Tag::new("pasta-analysis"));
+    assert_eq!(
+        engine.tag_depth(&Tag::new("creepypasta")),
+        Err(Error::InvalidConfig(LintWarning::RequirementCycle(vec![
+            Tag::new("creepypasta"),
+            Tag::new("tale"),
+            Tag::new("pasta-analysis"),
+        ]))),
+    );
+}
+
+#[test]
+fn test_implicit_conflicts() {
+    let mut engine = setup();
+    let before = engine.find_implicit_conflicts();
+
+    let needs_primary = engine
+        .add_tag(
+            "needs-primary",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("primary")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let no_primary = engine
+        .add_tag(
+            "no-primary",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("primary")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let conflicts = engine.find_implicit_conflicts();
+    assert!(conflicts.contains(&(Tag::clone(&needs_primary), Tag::clone(&no_primary))));
+    assert!(!before.contains(&(Tag::clone(&needs_primary), Tag::clone(&no_primary))));
+    assert!(conflicts.iter().all(|(a, b)| a < b));
+
+    // Direct conflicts aren't repeated
+    engine
+        .get_spec_mut(&no_primary)
+        .unwrap()
+        .conflicting_tags
+        .push(Tag::clone(&needs_primary));
+
+    let conflicts = engine.find_implicit_conflicts();
+    assert!(!conflicts.contains(&(needs_primary, no_primary)));
+}
+
+#[test]
+fn test_compatibility_matrix() {
+    let engine = setup();
+    let matrix = engine.compatibility_matrix();
+    let (scp, tale, safe) = (Tag::new("scp"), Tag::new("tale"), Tag::new("safe"));
+
+    assert_eq!(matrix.tags().len(), engine.get_specs().len());
+    assert_eq!(matrix.get(&scp, &tale), Some(false));
+    assert_eq!(matrix.get(&tale, &scp), Some(false));
+    assert_eq!(matrix.get(&scp, &safe), Some(true));
+    assert_eq!(matrix.get(&scp, &scp), Some(true));
+    assert_eq!(matrix.get(&scp, &Tag::new("primary")), None);
+
+    // Output is deterministic
+    assert_eq!(matrix, setup().compatibility_matrix());
+    assert_eq!(matrix.to_csv(), setup().compatibility_matrix().to_csv());
+
+    let mut small = Engine::default();
+    small.add_tag("a", TemplateTagSpec::default()).unwrap();
+    small
+        .add_tag(
+            "b,c",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("a")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        small.compatibility_matrix().to_csv(),
+        ",a,\"b,c\"\na,yes,no\n\"b,c\",no,yes\n",
+    );
+
+    // Exclusion sets are respected, as check_tags does
+    let mut engine = setup();
+    let (keter, euclid) = (Tag::new("keter"), Tag::new("euclid"));
+    assert_eq!(engine.compatibility_matrix().get(&safe, &keter), Some(true));
+
+    engine
+        .add_exclusion_set(vec![Tag::clone(&safe), Tag::clone(&keter)])
+        .unwrap();
+    let matrix = engine.compatibility_matrix();
+    assert_eq!(matrix.get(&safe, &keter), Some(false));
+    assert_eq!(matrix.get(&keter, &safe), Some(false));
+    assert_eq!(matrix.get(&safe, &euclid), Some(true));
+    assert_eq!(
+        engine.check_tags(&[Tag::clone(&scp), Tag::clone(&safe), Tag::clone(&keter)]),
+        Err(Error::IncompatibleTags(
+            Tag::clone(&safe),
+            Tag::clone(&keter)
+        )),
+    );
+}
+
+#[test]
+fn test_tags_conflicting_with_group() {
+    let engine = setup();
+
+    assert_eq!(
+        engine.tags_conflicting_with_group(&Tag::new("primary")),
+        [Tag::new("hub"), Tag::new("scp"), Tag::new("tale")],
+    );
+    assert_eq!(
+        engine.tags_conflicting_with_group(&Tag::new("contests")),
+        [Tag::new("cliche2019"), Tag::new("doomsday2018")],
+    );
+    assert!(engine
+        .tags_conflicting_with_group(&Tag::new("attribute"))
+        .is_empty());
+}
+
+#[test]
+fn test_minimal_valid_sets() {
+    let mut engine = setup();
+
+    macro_rules! check {
+        ($tag:expr, $max:expr, $expected:expr) => {{
+            let expected = $expected
+                .iter()
+                .map(|tags| tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                engine.minimal_valid_sets(&Tag::new($tag), $max).unwrap(),
+                expected,
+            );
+        }};
+    }
+
+    check!("euclid", 10, [["euclid", "scp"]]);
+    check!("scp", 10, [["scp"]]);
+    check!("creepypasta", 10, [["creepypasta", "tale"]]);
+
+    // Each member of a required group is tried
+    engine
+        .add_tag(
+            "crossover",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("primary")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    check!(
+        "crossover",
+        10,
+        [
+            ["crossover", "hub"],
+            ["crossover", "scp"],
+            ["crossover", "tale"]
+        ]
+    );
+    check!("crossover", 1, [["crossover", "hub"]]);
+    assert!(engine
+        .minimal_valid_sets(&Tag::new("crossover"), 0)
+        .unwrap()
+        .is_empty());
+
+    // Unsatisfiable tags have no valid tagsets
+    engine
+        .add_tag(
+            "paradox",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("scp"), Tag::new("tale")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert!(engine
+        .minimal_valid_sets(&Tag::new("paradox"), 10)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        engine.minimal_valid_sets(&Tag::new("xyz"), 10),
+        Err(Error::MissingTag(Tag::new("xyz"))),
+    );
+}
+
+#[test]
+fn test_tag_info() {
+    let mut engine = setup();
+    engine
+        .set_group_cardinality(&Tag::new("object-class"), Cardinality::at_most(1))
+        .unwrap();
+
+    let info = engine.tag_info(&Tag::new("euclid")).unwrap();
+    assert_eq!(info.tag, Tag::new("euclid"));
+    assert_eq!(info.display_name, "euclid");
+    assert!(info.enabled);
+    assert_eq!(info.required_tags, vec![Tag::new("scp")]);
+    assert_eq!(info.groups, vec![Tag::new("object-class")]);
+    assert_eq!(info.exclusive_groups, vec![Tag::new("object-class")]);
+    assert!(info.conflicting_tags.is_empty());
+
+    let info = engine.tag_info(&Tag::new("scp")).unwrap();
+    assert!(info.exclusive_groups.is_empty());
+    assert!(info.dependents.contains(&Tag::new("euclid")));
+    assert!(info.dependents.contains(&Tag::new("keter")));
+    assert!(!info.dependents.contains(&Tag::new("scp")));
+
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["tag"], "scp");
+
+    assert_eq!(
+        engine.tag_info(&Tag::new("object-class")),
+        Err(Error::MissingTag(Tag::new("object-class"))),
+    );
+}
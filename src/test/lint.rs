@@ -0,0 +1,378 @@
+/*
+ * test/lint.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::{Cardinality, LintWarning, SchemaRules, SchemaViolation};
+
+// The usual setup leaves the "goi" group unregistered, so it can be added as a dual tag.
+fn valid_setup() -> Engine {
+    let mut engine = setup();
+    engine.add_group("goi").unwrap();
+    engine
+}
+
+#[test]
+fn test_requires_own_group() {
+    let mut engine = valid_setup();
+    assert_eq!(engine.lint(), []);
+
+    engine
+        .add_tag(
+            "essay",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("primary")],
+                conflicting_tags: vec![Tag::new("primary")],
+                groups: vec![Tag::new("primary")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.lint(),
+        [LintWarning::RequiresOwnGroup(
+            Tag::new("essay"),
+            Tag::new("primary"),
+        )],
+    );
+
+    // The requirement is always satisfied
+    engine.check_tags(&[Tag::new("essay")]).unwrap();
+}
+
+#[test]
+fn test_validate() {
+    let mut engine = valid_setup();
+    assert!(engine.is_valid_config());
+    assert_eq!(engine.validate(), []);
+
+    // Warnings which are only suspicious don't count
+    engine
+        .add_tag(
+            "essay",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("primary")],
+                groups: vec![Tag::new("primary")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert!(engine.is_valid_config());
+    assert!(!engine.lint().is_empty());
+
+    macro_rules! requires {
+        ($tag:expr, $required:expr) => {
+            engine
+                .add_tag(
+                    $tag,
+                    TemplateTagSpec {
+                        required_tags: $required.iter().map(|name| Tag::new(*name)).collect(),
+                        ..TemplateTagSpec::default()
+                    },
+                )
+                .unwrap();
+        };
+    }
+
+    requires!("rock", ["paper"]);
+    requires!("paper", ["scissors"]);
+    requires!("scissors", ["rock", "scp"]);
+    requires!("one-way", ["rock"]);
+
+    assert!(!engine.is_valid_config());
+    assert_eq!(
+        engine.validate(),
+        [LintWarning::RequirementCycle(vec![
+            Tag::new("paper"),
+            Tag::new("scissors"),
+            Tag::new("rock"),
+        ])],
+    );
+    assert!(engine.lint().contains(&engine.validate()[0]));
+
+    // Breaking the cycle makes the configuration valid again
+    engine.delete_tag(&Tag::new("rock"));
+    assert!(engine.is_valid_config());
+
+    // Cycles through required groups and alternatives are found as well
+    let hand = engine.add_group("hand").unwrap();
+    engine
+        .add_tag(
+            "rock",
+            TemplateTagSpec {
+                required_any_of: vec![vec![Tag::new("paper"), Tag::new("tale")]],
+                groups: vec![Tag::clone(&hand)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+    engine
+        .add_tag(
+            "paper",
+            TemplateTagSpec {
+                required_groups: vec![Tag::clone(&hand)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.validate(),
+        [LintWarning::RequirementCycle(vec![
+            Tag::new("paper"),
+            Tag::new("rock"),
+        ])],
+    );
+}
+
+#[test]
+fn test_broken_references() {
+    let mut engine = valid_setup();
+
+    engine
+        .add_tag(
+            "narcissist",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("narcissist")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    engine
+        .add_tag(
+            "orphan",
+            TemplateTagSpec {
+                implied_tags: vec![Tag::new("nonexistent")],
+                needed_roles: vec![Role::new("nobody")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert!(!engine.is_valid_config());
+    assert_eq!(
+        engine.validate(),
+        [
+            LintWarning::SelfReference(Tag::new("narcissist")),
+            LintWarning::DanglingReference(Tag::new("orphan"), Tag::new("nonexistent")),
+            LintWarning::DanglingRole(Tag::new("orphan"), Role::new("nobody")),
+        ],
+    );
+    assert!(engine.validate().iter().all(LintWarning::is_structural));
+}
+
+#[test]
+fn test_validate_references() {
+    let mut engine = valid_setup();
+    assert_eq!(engine.validate_references(), Ok(()));
+
+    engine
+        .add_tag(
+            "orphan",
+            TemplateTagSpec {
+                required_tags: vec![Tag::new("nonexistent")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.validate_references(),
+        Err(Error::InvalidConfig(LintWarning::DanglingReference(
+            Tag::new("orphan"),
+            Tag::new("nonexistent"),
+        ))),
+    );
+
+    // When checking, the dangling reference only looks like an unmet requirement
+    assert_eq!(
+        engine.check_tags(&[Tag::new("orphan")]),
+        Err(Error::RequiresTags(
+            Tag::new("orphan"),
+            vec![Tag::new("nonexistent")],
+        )),
+    );
+
+    engine.add_group("nonexistent").unwrap();
+    assert_eq!(engine.validate_references(), Ok(()));
+}
+
+#[test]
+fn test_strict_mode() {
+    let mut engine = valid_setup();
+    let object_class = Tag::new("object-class");
+    let spec = TemplateTagSpec {
+        groups: vec![Tag::clone(&object_class)],
+        conflicting_tags: vec![Tag::clone(&object_class)],
+        required_tags: vec![Tag::new("scp")],
+        ..TemplateTagSpec::default()
+    };
+
+    // Unchanged outside of strict mode
+    engine.add_tag("neutralized", spec.clone()).unwrap();
+    assert_eq!(engine.validate(), []);
+
+    let warning =
+        LintWarning::NonExclusiveConflict(Tag::new("neutralized"), Tag::clone(&object_class));
+
+    engine.set_strict(true);
+    assert!(engine.is_strict());
+    assert!(engine.validate().contains(&warning));
+    assert!(engine
+        .validate()
+        .contains(&LintWarning::NonExclusiveConflict(
+            Tag::new("scp"),
+            Tag::new("primary"),
+        )));
+    assert_eq!(
+        engine.add_tag("decommissioned", spec.clone()),
+        Err(Error::InvalidConfig(LintWarning::NonExclusiveConflict(
+            Tag::new("decommissioned"),
+            Tag::clone(&object_class),
+        ))),
+    );
+    assert!(!engine.has_tag("decommissioned"));
+
+    // Conflicting with a proper tag is always allowed
+    engine
+        .add_tag(
+            "explained",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("scp")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    // Marking the group exclusive permits the conflict
+    engine
+        .set_group_cardinality(&object_class, Cardinality::at_most(1))
+        .unwrap();
+    assert!(engine.is_exclusive_group(&object_class));
+    assert!(!engine.validate().contains(&warning));
+    engine.add_tag("decommissioned", spec).unwrap();
+}
+
+#[test]
+fn test_asymmetric_conflicts() {
+    let mut engine = valid_setup();
+    engine
+        .add_tag(
+            "illustrated",
+            TemplateTagSpec {
+                conflicting_tags: vec![Tag::new("_image"), Tag::new("licensing")],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.lint(),
+        [LintWarning::AsymmetricConflict(
+            Tag::new("illustrated"),
+            Tag::new("_image"),
+        )],
+    );
+    assert!(!engine.lint()[0].is_structural());
+    assert!(engine.is_valid_config());
+
+    engine.symmetrize_conflicts();
+    assert_eq!(engine.lint(), []);
+
+    macro_rules! conflicts {
+        ($tag:expr) => {
+            engine.get_spec(&Tag::new($tag)).unwrap().conflicting_tags
+        };
+    }
+
+    assert_eq!(
+        conflicts!("_image"),
+        [Tag::new("_cc"), Tag::new("illustrated")]
+    );
+
+    // Group conflicts are left alone
+    assert_eq!(conflicts!("_cc"), [Tag::new("_image")]);
+
+    // Already symmetric, so nothing changes
+    let fingerprint = engine.fingerprint();
+    engine.symmetrize_conflicts();
+    assert_eq!(engine.fingerprint(), fingerprint);
+}
+
+#[test]
+fn test_check_schema() {
+    let mut engine = Engine::default();
+    let primary = engine.add_group("primary").unwrap();
+    let roles = ["admin", "moderator", "member"]
+        .iter()
+        .map(|name| engine.add_role(*name).unwrap())
+        .collect::<Vec<_>>();
+
+    engine
+        .add_tag(
+            "scp",
+            TemplateTagSpec {
+                groups: vec![Tag::clone(&primary)],
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    let loose = engine
+        .add_tag(
+            "loose",
+            TemplateTagSpec {
+                needed_roles: roles,
+                ..TemplateTagSpec::default()
+            },
+        )
+        .unwrap();
+
+    // Nothing is enforced by default
+    assert!(engine.check_schema(&SchemaRules::default()).is_empty());
+
+    let rules = SchemaRules {
+        tags_need_group: true,
+        ..SchemaRules::default()
+    };
+    assert_eq!(
+        engine.check_schema(&rules),
+        [SchemaViolation::Ungrouped(Tag::clone(&loose))],
+    );
+
+    let rules = SchemaRules {
+        tags_need_group: true,
+        groups_need_display_name: true,
+        max_roles: Some(2),
+    };
+    let violations = engine.check_schema(&rules);
+    assert_eq!(
+        violations,
+        [
+            SchemaViolation::Ungrouped(Tag::clone(&loose)),
+            SchemaViolation::UnnamedGroup(Tag::clone(&primary)),
+            SchemaViolation::TooManyRoles(Tag::clone(&loose), 3),
+        ],
+    );
+    assert_eq!(violations[2].to_string(), "loose needs 3 roles");
+
+    engine
+        .set_display_name(&primary, Some(str!("Primary")))
+        .unwrap();
+    engine.add_to_group(&loose, &primary).unwrap();
+    assert_eq!(
+        engine.check_schema(&rules),
+        [SchemaViolation::TooManyRoles(Tag::clone(&loose), 3)],
+    );
+}
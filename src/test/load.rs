@@ -0,0 +1,912 @@
+/*
+ * test/load.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::load::{self, Configuration, TagConfig, CONFIG_VERSION};
+use crate::{Cardinality, LintWarning};
+use std::convert::{TryFrom, TryInto};
+use std::error::Error as StdError;
+use std::io;
+
+fn tag_config(name: &str, groups: &[&str], conflicts_with: &[&str]) -> TagConfig {
+    let strings = |items: &[&str]| Some(items.iter().map(|s| str!(*s)).collect());
+
+    TagConfig {
+        name: str!(name),
+        groups: strings(groups),
+        conflicts_with: strings(conflicts_with),
+        ..TagConfig::default()
+    }
+}
+
+pub fn config() -> Configuration {
+    Configuration {
+        version: CONFIG_VERSION,
+        roles: vec![str!("member")],
+        role_sets: vec![],
+        tags: vec![
+            tag_config("scp", &["primary"], &[]),
+            tag_config("tale", &["primary"], &["scp"]),
+        ],
+        groups: vec![],
+        templates: vec![],
+        aliases: vec![],
+    }
+}
+
+#[test]
+fn test_from_path() {
+    let config = load::from_path("misc/config.toml").expect("Unable to load example config");
+    assert!(config.roles.contains(&str!("member")));
+    assert!(config.tags.iter().any(|tag| tag.name == "scp"));
+
+    // Missing files report the underlying IO error
+    let error = load::from_path("misc/nonexistent.toml").unwrap_err();
+    match error {
+        Error::Io(ref inner) => assert_eq!(inner.kind(), io::ErrorKind::NotFound),
+        _ => panic!("Expected Error::Io, got {:?}", error),
+    }
+
+    assert_eq!(error.code(), "io");
+    assert!(error.source().is_some());
+    assert_eq!(error, Error::Io(io::ErrorKind::NotFound.into()));
+
+    // As do invalid files
+    let error = load::from_path("Cargo.toml").unwrap_err();
+    let message = match error {
+        Error::Parse(ref message, _) => message.clone(),
+        _ => panic!("Expected Error::Parse, got {:?}", error),
+    };
+
+    assert_eq!(error.code(), "parse");
+    let source = error.source().expect("Parse error has no source");
+    assert!(source.is::<toml::de::Error>());
+    assert_eq!(source.to_string(), message);
+    assert_eq!(error, Error::Parse(message, None));
+    assert!(Error::Parse(str!("bad"), None).source().is_none());
+    assert!(Error::MissingTag(Tag::new("scp")).source().is_none());
+}
+
+#[test]
+fn test_migrate() {
+    let mut config: Configuration = toml::from_str(
+        r#"
+        roles = ["member"]
+
+        [[tags]]
+        name = "scp"
+        groups = ["primary"]
+        "#,
+    )
+    .expect("Unable to parse version 1 config");
+
+    assert_eq!(config.version, 1);
+    assert_eq!(config.tags[0].priority, None);
+
+    config.migrate();
+    assert_eq!(config.version, CONFIG_VERSION);
+
+    let tag = &config.tags[0];
+    assert_eq!(tag.public, Some(false));
+    assert_eq!(tag.implies, Some(vec![]));
+    assert_eq!(tag.priority, Some(0));
+    assert_eq!(tag.groups, Some(vec![str!("primary")]));
+
+    // Migrating again changes nothing
+    let migrated = config.clone();
+    config.migrate();
+    assert_eq!(config, migrated);
+
+    // Applying migrates first
+    let mut old = migrated.clone();
+    old.version = 1;
+    let engine = Engine::from_config(old).unwrap();
+    assert_eq!(engine.get_spec(&Tag::new("scp")).unwrap().priority, 0);
+
+    // Newer optional fields need no migration
+    let mut config: Configuration = toml::from_str(
+        r#"
+        version = 2
+        roles = ["member"]
+
+        [[tags]]
+        name = "scp"
+        "#,
+    )
+    .expect("Unable to parse version 2 config");
+
+    let original = config.clone();
+    config.migrate();
+    assert_eq!(config, original);
+
+    let tag = &config.tags[0];
+    assert_eq!(tag.enabled, None);
+    assert_eq!(tag.requires_expr, None);
+    assert_eq!(tag.publish_requires, None);
+    assert!(config.role_sets.is_empty());
+    assert!(config.aliases.is_empty());
+
+    let engine = Engine::from_config(config).unwrap();
+    assert!(engine.is_tag_enabled(&Tag::new("scp")));
+    engine.check_tags_for_publish(&[Tag::new("scp")]).unwrap();
+}
+
+#[test]
+fn test_templates() {
+    let mut engine = setup();
+    let tags = vec![Tag::new("scp"), Tag::new("euclid")];
+
+    engine.register_template("new-scp", tags.clone()).unwrap();
+    assert_eq!(engine.template("new-scp"), Some(&tags[..]));
+    assert_eq!(engine.template("new-tale"), None);
+    engine
+        .check_tags(engine.template("new-scp").unwrap())
+        .unwrap();
+
+    // Templates are validated when registered
+    assert_eq!(
+        engine.register_template("bad", vec![Tag::new("scp"), Tag::new("tale")]),
+        Err(Error::IncompatibleViaGroup(
+            Tag::new("scp"),
+            Tag::new("tale"),
+            Tag::new("primary"),
+        )),
+    );
+    assert!(engine
+        .register_template("bad", vec![Tag::new("scp"), Tag::new("scp")])
+        .is_err());
+    assert_eq!(engine.template("bad"), None);
+
+    engine.delete_template("new-scp");
+    assert!(engine.get_templates().is_empty());
+
+    // Templates can be loaded from a configuration
+    let config: Configuration = toml::from_str(
+        r#"
+        version = 2
+        roles = []
+
+        [[tags]]
+        name = "scp"
+
+        [[tags]]
+        name = "euclid"
+        requires = ["scp"]
+
+        [[templates]]
+        name = "new-scp"
+        tags = ["scp", "euclid"]
+        "#,
+    )
+    .expect("Unable to parse config with templates");
+
+    let engine = Engine::from_config(config.clone()).unwrap();
+    assert_eq!(engine.template("new-scp"), Some(&tags[..]));
+
+    let mut config = config;
+    config.templates[0].tags.pop();
+    config.templates[0].tags.push(str!("xyz"));
+    assert_eq!(
+        Engine::from_config(config).err(),
+        Some(Error::NoSuchTag(str!("xyz"))),
+    );
+}
+
+#[test]
+fn test_publish_requires() {
+    let mut engine = setup();
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "featured"
+        requires = ["scp"]
+        publish_requires = ["licensing"]
+        "#,
+    )
+    .expect("Unable to parse config with publish requirements");
+
+    let tag = engine.register_tag_config(&config.tags[0]).unwrap();
+    let spec = engine.get_spec(&tag).unwrap();
+    assert_eq!(spec.required_tags, [Tag::new("scp")]);
+    assert_eq!(spec.publish_required_tags, [Tag::new("licensing")]);
+
+    let toml = toml::to_string(&config).unwrap();
+    assert!(toml.contains("publish_requires = [\"licensing\"]"));
+
+    let tags = [Tag::new("scp"), Tag::clone(&tag)];
+    engine.check_tags(&tags).unwrap();
+    assert!(engine.check_tags_for_publish(&tags).is_err());
+}
+
+#[test]
+fn test_requires_expr() {
+    let mut engine = setup();
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "recontained"
+        requires_expr = "scp & (safe | euclid) & (humanoid | amorphous | scp)"
+        "#,
+    )
+    .expect("Unable to parse config with requirement expression");
+
+    let tag = engine.register_tag_config(&config.tags[0]).unwrap();
+    let spec = engine.get_spec(&tag).unwrap();
+    assert_eq!(spec.required_tags, [Tag::new("scp")]);
+    assert_eq!(
+        spec.required_any_of,
+        [
+            vec![Tag::new("safe"), Tag::new("euclid")],
+            vec![Tag::new("humanoid"), Tag::new("amorphous"), Tag::new("scp")],
+        ],
+    );
+
+    macro_rules! check {
+        ($tags:expr) => {{
+            let mut tags = $tags.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+            tags.push(Tag::clone(&tag));
+            engine.check_tags(&tags)
+        }};
+    }
+
+    check!(["scp", "safe"]).unwrap();
+    check!(["scp", "euclid", "humanoid"]).unwrap();
+    assert_eq!(
+        check!(["scp", "keter"]),
+        Err(Error::RequiresAnyOf(
+            Tag::clone(&tag),
+            vec![Tag::new("safe"), Tag::new("euclid")],
+        )),
+    );
+    assert_eq!(
+        check!(["tale"]),
+        Err(Error::RequiresTags(Tag::clone(&tag), vec![Tag::new("scp")])),
+    );
+
+    // OR distributes over AND
+    let mut config = config.tags[0].clone();
+    config.requires_expr = Some(str!("(scp & safe) | tale"));
+    engine.register_tag_config(&config).unwrap();
+    let spec = engine.get_spec(&tag).unwrap();
+    assert!(spec.required_tags.is_empty());
+    assert_eq!(
+        spec.required_any_of,
+        [
+            vec![Tag::new("scp"), Tag::new("tale")],
+            vec![Tag::new("safe"), Tag::new("tale")],
+        ],
+    );
+
+    // Malformed expressions name the tag and expression
+    let malformed = [
+        ("scp & (safe | euclid", "unclosed parenthesis"),
+        ("scp &", "unexpected end"),
+        ("scp safe", "expected an operator"),
+        ("| scp", "expected a name"),
+        ("()", "expected a name"),
+        ("scp)", "unopened parenthesis"),
+    ];
+
+    for &(expr, reason) in &malformed {
+        config.requires_expr = Some(str!(expr));
+        let error = Error::InvalidRequirement(str!(&config.name), str!(expr), reason);
+        assert_eq!(engine.register_tag_config(&config), Err(error));
+    }
+
+    config.requires_expr = Some(str!("scp &"));
+    assert_eq!(
+        engine.register_tag_config(&config).unwrap_err().to_string(),
+        format!(
+            "Invalid requirement expression: \"scp &\" for {} (unexpected end)",
+            config.name,
+        ),
+    );
+
+    // Distributing alternatives is bounded
+    let expr = (0..10)
+        .map(|_| "(scp & tale)")
+        .collect::<Vec<_>>()
+        .join(" | ");
+    config.requires_expr = Some(expr.clone());
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::InvalidRequirement(
+            str!(&config.name),
+            expr,
+            "too many clauses",
+        )),
+    );
+
+    config.requires_expr = Some(str!("scp | xyz"));
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::NoSuchTag(str!("xyz"))),
+    );
+}
+
+#[test]
+fn test_enabled() {
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "scp"
+
+        [[tags]]
+        name = "old-format"
+        enabled = false
+        "#,
+    )
+    .expect("Unable to parse config with disabled tag");
+
+    let engine = Engine::from_config(config).unwrap();
+    assert!(engine.is_tag_enabled(&Tag::new("scp")));
+    assert!(!engine.is_tag_enabled(&Tag::new("old-format")));
+}
+
+#[test]
+fn test_declared_groups() {
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "scp"
+        groups = ["primary"]
+        conflicts_with = ["archived"]
+
+        [[groups]]
+        name = "archived"
+        display_name = "Archived Pages"
+
+        [[groups]]
+        name = "primary"
+        "#,
+    )
+    .expect("Unable to parse config with groups");
+
+    // Survives being written back out
+    let serialized = toml::to_string(&config).expect("Unable to serialize config");
+    let config: Configuration = toml::from_str(&serialized).expect("Unable to reparse config");
+    assert_eq!(config.groups.len(), 2);
+
+    let engine = Engine::from_config(config.clone()).unwrap();
+    let archived = Tag::new("archived");
+    assert!(engine.is_group(&archived));
+    assert!(engine.tags_in_group(&archived).is_empty());
+    assert_eq!(engine.display_name(&archived), "Archived Pages");
+    assert_eq!(engine.display_name(&Tag::new("primary")), "primary");
+    assert_eq!(
+        engine.get_spec(&Tag::new("scp")).unwrap().conflicting_tags,
+        [Tag::clone(&archived)],
+    );
+
+    // Applying again keeps the group
+    let mut engine = engine;
+    config.apply(&mut engine).unwrap();
+    assert!(engine.is_group(&archived));
+
+    // Groups cannot reuse a tag's name
+    let mut config: Configuration = toml::from_str(&serialized).unwrap();
+    config.groups[0].name = str!("scp");
+    assert_eq!(
+        Engine::from_config(config).err(),
+        Some(Error::NameCollision(str!("scp"))),
+    );
+}
+
+#[test]
+fn test_role_sets() {
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = ["member", "moderator", "admin"]
+
+        [[role_sets]]
+        name = "staff"
+        roles = ["moderator", "admin"]
+
+        [[tags]]
+        name = "featured"
+        roles = ["staff"]
+        "#,
+    )
+    .expect("Unable to parse config with role sets");
+
+    // Survives being written back out
+    let serialized = toml::to_string(&config).expect("Unable to serialize config");
+    let config: Configuration = toml::from_str(&serialized).expect("Unable to reparse config");
+    assert_eq!(config.role_sets.len(), 1);
+
+    let mut engine = Engine::from_config(config.clone()).unwrap();
+    let (staff, admin) = (Role::new("staff"), Role::new("admin"));
+    let featured = [Tag::new("featured")];
+    engine
+        .check_tag_changes(&[], &featured, &[], &[admin])
+        .unwrap();
+
+    // Removed sets are no longer expanded, but remain if declared as roles
+    let mut config = config;
+    config.role_sets.clear();
+    config.roles.push(str!("staff"));
+    config.apply(&mut engine).unwrap();
+    assert!(engine.has_role("staff"));
+    assert_eq!(engine.get_role_set(&staff), None);
+
+    // The example configuration uses them too
+    let config = load::from_path("misc/config.toml").unwrap();
+    let engine = Engine::from_config(config).unwrap();
+    assert!(engine.get_role_set(&staff).is_some());
+}
+
+#[test]
+fn test_aliases() {
+    let config: Configuration = toml::from_str(
+        r#"
+        roles = []
+
+        [[tags]]
+        name = "scp"
+        groups = ["primary"]
+
+        [[tags]]
+        name = "tale"
+        groups = ["primary"]
+
+        [[aliases]]
+        name = "article"
+        tag = "scp"
+
+        [[aliases]]
+        name = "kind"
+        tag = "primary"
+        "#,
+    )
+    .unwrap();
+
+    let mut engine = Engine::from_config(config.clone()).unwrap();
+    assert_eq!(engine.get_tag("article").unwrap(), Tag::new("scp"));
+    assert_eq!(engine.get_tag("kind").unwrap(), Tag::new("primary"));
+    assert_eq!(engine.get_aliases().len(), 2);
+
+    // Reapplying replaces the aliases
+    let mut changed = config.clone();
+    changed.aliases.truncate(1);
+    changed.aliases[0].tag = str!("tale");
+    changed.apply(&mut engine).unwrap();
+    assert_eq!(engine.get_tag("article").unwrap(), Tag::new("tale"));
+    assert_eq!(engine.get_tag("kind"), Err(Error::NoSuchTag(str!("kind"))));
+
+    // The target must exist
+    let mut missing = config.clone();
+    missing.aliases[0].tag = str!("hub");
+    assert_eq!(
+        Engine::from_config(missing).unwrap_err(),
+        Error::NoSuchTag(str!("hub")),
+    );
+
+    // Other references cannot go through aliases, whether loading fresh or reapplying
+    let mut referencing = config.clone();
+    referencing.tags[1].requires = Some(vec![str!("article")]);
+    assert_eq!(
+        Engine::from_config(referencing.clone()).unwrap_err(),
+        Error::NoSuchTag(str!("article")),
+    );
+
+    let mut engine = Engine::from_config(config.clone()).unwrap();
+    assert_eq!(
+        engine.register_tag_config(&referencing.tags[1]),
+        Err(Error::NoSuchTag(str!("article"))),
+    );
+
+    // Reapplying removes old aliases before adding tags, so their names can be reused
+    let mut renamed = config;
+    renamed.aliases.clear();
+    renamed.tags[0].name = str!("article");
+    renamed.apply(&mut engine).unwrap();
+    assert!(engine.get_aliases().is_empty());
+    assert!(engine.has_tag("article"));
+    assert!(!engine.has_tag("scp"));
+}
+
+#[test]
+fn test_try_from() {
+    let config: Configuration = toml::from_str(
+        r#"
+        version = 2
+        roles = ["member"]
+
+        [[tags]]
+        name = "scp"
+        groups = ["primary"]
+        roles = ["member"]
+
+        [[tags]]
+        name = "tale"
+        groups = ["primary"]
+        roles = ["member"]
+        conflicts_with = ["scp"]
+
+        [[tags]]
+        name = "euclid"
+        groups = ["object-class"]
+        requires = ["scp"]
+        "#,
+    )
+    .expect("Unable to parse config");
+
+    let engine = Engine::try_from(config.clone()).unwrap();
+    assert_eq!(
+        engine.fingerprint(),
+        Engine::from_config(config.clone()).unwrap().fingerprint()
+    );
+    engine
+        .check_tags(&[Tag::new("scp"), Tag::new("euclid")])
+        .unwrap();
+    assert_eq!(
+        engine.check_tags(&[Tag::new("tale"), Tag::new("euclid")]),
+        Err(Error::RequiresTags(
+            Tag::new("euclid"),
+            vec![Tag::new("scp")]
+        )),
+    );
+
+    // Works in generic conversion contexts
+    fn convert<T: TryInto<Engine, Error = Error>>(value: T) -> crate::Result<Engine> {
+        value.try_into()
+    }
+
+    let mut config = config;
+    config.tags[2].requires = Some(vec![str!("xyz")]);
+    assert_eq!(
+        convert(config.clone()).err(),
+        Some(Error::NoSuchTag(str!("xyz"))),
+    );
+
+    // Applying fails too, undoing any changes made before the failure
+    let mut engine = engine;
+    let fingerprint = engine.fingerprint();
+    config.roles.clear();
+    assert_eq!(
+        config.apply(&mut engine),
+        Err(Error::NoSuchRole(str!("member"))),
+    );
+    assert!(engine.has_role("member"));
+    assert_eq!(engine.fingerprint(), fingerprint);
+}
+
+#[test]
+#[cfg(feature = "toml_edit")]
+fn test_to_toml_preserving() {
+    let original = std::fs::read_to_string("misc/config.toml").unwrap();
+    let config: Configuration = toml::from_str(&original).unwrap();
+    let mut engine = Engine::from_config(config).unwrap();
+
+    // Nothing has changed, so neither does the file
+    assert_eq!(
+        load::to_toml_preserving(&original, &engine).unwrap(),
+        original
+    );
+
+    let appliance = Tag::new("appliance");
+    engine.get_spec_mut(&appliance).unwrap().required_tags =
+        vec![Tag::new("scp"), Tag::new("concept")];
+    engine.get_spec_mut(&appliance).unwrap().required_any_of =
+        vec![vec![Tag::new("tale"), Tag::new("admin")]];
+    engine.delete_tag(&Tag::new("_cc"));
+    engine.add_tag("hub", TemplateTagSpec::default()).unwrap();
+    engine.add_role("janitor").unwrap();
+    engine.add_alias("article", &Tag::new("scp")).unwrap();
+    engine.add_alias("story", &Tag::new("concept")).unwrap();
+
+    let edited = load::to_toml_preserving(&original, &engine).unwrap();
+    assert!(
+        edited.contains("requires = [\"scp\", \"concept\"]\nrequires_expr = \"(tale | admin)\"")
+    );
+    assert!(!edited.contains("name = \"_cc\""));
+    assert!(edited.contains("[[tags]]\nname = \"hub\"\n"));
+    assert!(edited.contains("    \"admin\",\n    \"janitor\",\n]"));
+    assert!(edited.contains("name = \"story\"\ntag = \"concept\"\n"));
+    assert!(edited.ends_with("[[aliases]]\nname = \"article\"\ntag = \"scp\"\n"));
+
+    // Comments and unrelated values are untouched
+    for line in original.lines().filter(|line| line.starts_with('#')) {
+        assert!(edited.contains(line), "Lost comment {:?}", line);
+    }
+
+    assert!(edited.contains("name = \"concept\"\ngroups = [\"attribute\"]\nroles = [\"member\"]\nrequires = [\"scp\"]\n"));
+
+    // The result loads as the edited engine
+    let config: Configuration = toml::from_str(&edited).unwrap();
+    let reloaded = Engine::from_config(config).unwrap();
+    assert_eq!(reloaded.fingerprint(), engine.fingerprint());
+    assert_eq!(
+        load::to_toml_preserving(&edited, &reloaded).unwrap(),
+        edited
+    );
+}
+
+#[test]
+fn test_apply_roles_only() {
+    let mut config = config();
+    let mut engine = Engine::default();
+    config.clone().apply(&mut engine).unwrap();
+
+    engine.get_spec_mut(&Tag::new("scp")).unwrap().priority = 3;
+    let specs = engine.get_specs().clone();
+
+    config.roles.push(str!("staff"));
+    config.tags.clear();
+    config.apply_roles_only(&mut engine);
+
+    assert!(engine.has_role("member"));
+    assert!(engine.has_role("staff"));
+    assert!(engine.has_tag("scp"));
+    assert_eq!(engine.get_specs(), &specs);
+
+    let drift = config.drift(&engine).unwrap();
+    assert!(drift.added_roles.is_empty());
+    assert!(drift.removed_roles.is_empty());
+}
+
+#[test]
+fn test_apply_roles_only_keeps_needed() {
+    let mut config = config();
+    config.tags[0].roles = Some(vec![str!("member")]);
+
+    let mut engine = Engine::default();
+    config.clone().apply(&mut engine).unwrap();
+
+    let scp = Tag::new("scp");
+    assert_eq!(
+        &*engine.get_spec(&scp).unwrap().needed_roles,
+        [Role::new("member")],
+    );
+
+    // Roles absent from the config are kept, so locked tags stay locked
+    config.roles.clear();
+    config.apply_roles_only(&mut engine);
+    assert!(engine.has_role("member"));
+    assert_eq!(
+        &*engine.get_spec(&scp).unwrap().needed_roles,
+        [Role::new("member")],
+    );
+    assert_eq!(
+        engine.check_tag_changes(&[], &[Tag::clone(&scp)], &[], &[]),
+        Err(Error::MissingRoles(vec![Role::new("member")].into())),
+    );
+}
+
+#[test]
+fn test_register_tag_config() {
+    let mut engine = setup();
+    let config = TagConfig {
+        name: str!("anomalous"),
+        groups: Some(vec![str!("attribute"), str!("behavior")]),
+        roles: Some(vec![str!("member")]),
+        requires: Some(vec![str!("scp")]),
+        conflicts_with: Some(vec![str!("tale")]),
+        priority: Some(2),
+        ..TagConfig::default()
+    };
+
+    let tag = engine.register_tag_config(&config).unwrap();
+    assert_eq!(tag, Tag::new("anomalous"));
+    assert!(engine.is_group(&Tag::new("behavior")));
+
+    let spec = engine.get_spec(&tag).unwrap();
+    assert_eq!(spec.required_tags, [Tag::new("scp")]);
+    assert_eq!(spec.conflicting_tags, [Tag::new("tale")]);
+    assert!(spec.implied_tags.is_empty());
+    assert_eq!(spec.groups, [Tag::new("attribute"), Tag::new("behavior")]);
+    assert_eq!(&*spec.needed_roles, [Role::new("member")]);
+    assert!(!spec.public);
+    assert_eq!(spec.priority, 2);
+
+    // Registering again replaces the specification
+    let config = tag_config("anomalous", &[], &[]);
+    engine.register_tag_config(&config).unwrap();
+    let spec = engine.get_spec(&tag).unwrap();
+    assert!(spec.groups.is_empty());
+    assert!(spec.required_tags.is_empty());
+    assert_eq!(spec.priority, 0);
+
+    // Referenced tags must already exist, and failures register nothing
+    let before = engine.fingerprint();
+    let configs = [
+        tag_config("unknown-requirement", &["new-group"], &["nonexistent"]),
+        TagConfig {
+            requires: Some(vec![str!("nonexistent")]),
+            ..tag_config("unknown-requirement", &["new-group"], &[])
+        },
+        TagConfig {
+            requires_expr: Some(str!("scp & (tale | nonexistent)")),
+            ..tag_config("unknown-requirement", &["new-group"], &[])
+        },
+        TagConfig {
+            publish_requires: Some(vec![str!("nonexistent")]),
+            ..tag_config("unknown-requirement", &["new-group"], &[])
+        },
+        TagConfig {
+            implies: Some(vec![str!("nonexistent")]),
+            ..tag_config("unknown-requirement", &["new-group"], &[])
+        },
+    ];
+
+    for config in &configs {
+        assert_eq!(
+            engine.register_tag_config(config),
+            Err(Error::NoSuchTag(str!("nonexistent"))),
+        );
+        assert!(!engine.has_tag("unknown-requirement"));
+        assert!(!engine.has_tag("new-group"));
+        assert_eq!(engine.fingerprint(), before);
+    }
+
+    let config = TagConfig {
+        roles: Some(vec![str!("nonexistent")]),
+        ..tag_config("unknown-requirement", &[], &[])
+    };
+    assert!(engine.register_tag_config(&config).is_err());
+    assert!(!engine.has_tag("unknown-requirement"));
+
+    // Existing tags are left as they were
+    let config = TagConfig {
+        requires_expr: Some(str!("scp &")),
+        ..tag_config("anomalous", &["attribute"], &[])
+    };
+    assert!(engine.register_tag_config(&config).is_err());
+    assert!(engine.get_spec(&tag).unwrap().groups.is_empty());
+    assert_eq!(engine.fingerprint(), before);
+}
+
+#[test]
+fn test_register_tag_config_alias() {
+    let mut engine = setup();
+    let scp = Tag::new("scp");
+    engine.add_alias("article", &scp).unwrap();
+    let before = engine.get_spec(&scp).unwrap().clone();
+
+    // The alias is not followed, so the canonical tag is not overwritten
+    let config = tag_config("article", &[], &[]);
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::NameCollision(str!("article"))),
+    );
+    assert_eq!(engine.get_spec(&scp), Ok(&before));
+    assert_eq!(engine.get_tag("article"), Ok(Tag::clone(&scp)));
+
+    // Nor is it taken as a group name, and the tag is not registered
+    let fingerprint = engine.fingerprint();
+    let config = tag_config("tagged-article", &["article"], &[]);
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::NameCollision(str!("article"))),
+    );
+    assert!(!engine.has_tag("tagged-article"));
+    assert_eq!(engine.get_tag("article"), Ok(scp));
+    assert_eq!(engine.fingerprint(), fingerprint);
+
+    // Invalid names are rejected, even when the tag refers to itself
+    let config = tag_config("", &[], &[""]);
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::InvalidName(str!(""))),
+    );
+    assert_eq!(engine.fingerprint(), fingerprint);
+}
+
+#[test]
+fn test_register_tag_config_strict() {
+    let mut engine = Engine::default();
+    config().apply(&mut engine).unwrap();
+    engine.set_strict(true);
+
+    // "primary" permits any number of members, so conflicting with it is rejected
+    let config = tag_config("hub", &[], &["primary"]);
+    assert_eq!(
+        engine.register_tag_config(&config),
+        Err(Error::InvalidConfig(LintWarning::NonExclusiveConflict(
+            Tag::new("hub"),
+            Tag::new("primary"),
+        ))),
+    );
+    assert!(!engine.has_tag("hub"));
+
+    // Also when replacing an existing tag's specification
+    let config = tag_config("scp", &["primary"], &["primary"]);
+    assert!(engine.register_tag_config(&config).is_err());
+    assert!(engine
+        .get_spec(&Tag::new("scp"))
+        .unwrap()
+        .conflicting_tags
+        .is_empty());
+
+    // Allowed once the group is exclusive
+    let cardinality = Cardinality {
+        min: 0,
+        max: Some(1),
+    };
+    engine
+        .set_group_cardinality(&Tag::new("primary"), cardinality)
+        .unwrap();
+    let config = tag_config("hub", &[], &["primary"]);
+    engine.register_tag_config(&config).unwrap();
+    assert_eq!(engine.validate(), []);
+}
+
+#[test]
+fn test_with_config_validated() {
+    let engine = Engine::with_config_validated(config()).unwrap();
+    assert!(engine.is_valid_config());
+    assert_eq!(
+        engine.fingerprint(),
+        Engine::from_config(config()).unwrap().fingerprint()
+    );
+
+    let mut config = config();
+    config.tags.push(TagConfig {
+        requires: Some(vec![str!("yang")]),
+        ..tag_config("yin", &[], &[])
+    });
+    config.tags.push(TagConfig {
+        requires: Some(vec![str!("yin")]),
+        ..tag_config("yang", &[], &[])
+    });
+
+    // Cycles are only rejected when validating
+    Engine::from_config(config.clone()).unwrap();
+
+    let result = Engine::with_config_validated(config);
+    let cycle = LintWarning::RequirementCycle(vec![Tag::new("yang"), Tag::new("yin")]);
+    assert_eq!(result.as_ref().err(), Some(&Error::InvalidConfig(cycle)));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid configuration: requirement cycle yang -> yin -> yang",
+    );
+
+    // Undeclared references fail either way
+    let mut config = self::config();
+    config
+        .tags
+        .push(tag_config("orphan", &[], &["nonexistent"]));
+    assert!(Engine::from_config(config.clone()).is_err());
+    assert!(Engine::with_config_validated(config).is_err());
+}
+
+#[test]
+#[cfg(feature = "toml_edit")]
+fn test_to_toml_preserving_inline() {
+    let original = r#"roles = []
+tags = [{ name = "scp" }, { name = "tale" }]
+"#;
+
+    let config: Configuration = toml::from_str(original).unwrap();
+    let mut engine = Engine::from_config(config).unwrap();
+    engine.delete_tag(&Tag::new("tale"));
+    engine.add_tag("hub", TemplateTagSpec::default()).unwrap();
+
+    // Inline arrays are rewritten as arrays of tables
+    let edited = load::to_toml_preserving(original, &engine).unwrap();
+    assert_eq!(
+        edited,
+        "roles = []\n\n[[tags]]\nname = \"scp\"\n\n[[tags]]\nname = \"hub\"\n",
+    );
+
+    let config: Configuration = toml::from_str(&edited).unwrap();
+    let reloaded = Engine::from_config(config).unwrap();
+    assert_eq!(reloaded.fingerprint(), engine.fingerprint());
+}
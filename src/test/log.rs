@@ -0,0 +1,115 @@
+/*
+ * test/log.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use log::kv::{self, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::{Mutex, Once};
+
+static INIT: Once = Once::new();
+static LOGGER: CaptureLogger = CaptureLogger {
+    events: Mutex::new(Vec::new()),
+};
+
+struct CaptureLogger {
+    events: Mutex<Vec<Event>>,
+}
+
+#[derive(Debug, Default)]
+struct Event {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl<'kvs> VisitSource<'kvs> for Event {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.fields.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+impl Event {
+    fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "tag_guard" && metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let mut event = Event {
+                message: record.args().to_string(),
+                ..Event::default()
+            };
+
+            record
+                .key_values()
+                .visit(&mut event)
+                .expect("Unable to visit fields");
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn capture() -> &'static CaptureLogger {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).expect("Unable to set logger");
+        log::set_max_level(LevelFilter::Warn);
+    });
+
+    &LOGGER
+}
+
+#[test]
+fn test_log_failures() {
+    let logger = capture();
+    let engine = setup();
+
+    // Successful checks are not logged
+    let tags = [Tag::new("scp"), Tag::new("keter")];
+    engine.check_tags(&tags).expect("Valid tags failed check");
+
+    // Failed checks are logged with the error code and failing tag as fields
+    let tags = [Tag::new("scp"), Tag::new("tale")];
+    let error = engine
+        .check_tags(&tags)
+        .expect_err("Invalid tags passed check");
+    assert_eq!(error.code(), "incompatible_via_group");
+
+    let events = logger.events.lock().unwrap();
+    let event = events
+        .iter()
+        .find(|event| event.field("tags") == Some(r#"[Tag("scp"), Tag("tale")]"#))
+        .expect("No log event for failed check");
+
+    assert_eq!(event.message, "Tag check failed");
+    assert_eq!(event.field("code"), Some("incompatible_via_group"));
+    assert_eq!(event.field("tag"), Some(r#"Some(Tag("scp"))"#));
+    assert_eq!(
+        event.field("error"),
+        Some(r#"IncompatibleViaGroup(Tag("scp"), Tag("tale"), Tag("primary"))"#),
+    );
+    assert_eq!(event.fields.len(), 4);
+    assert!(!events.iter().any(|event| event
+        .fields
+        .iter()
+        .any(|(_, value)| value.contains("keter"))));
+}
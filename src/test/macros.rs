@@ -0,0 +1,48 @@
+/*
+ * test/macros.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+
+define_tags! {
+    /// Tags for the test vocabulary.
+    enum Vocabulary {
+        Scp => "scp",
+        Keter => "keter",
+        Rating => "rating",
+    }
+}
+
+#[test]
+fn test_define_tags() {
+    let mut engine = setup();
+    assert!(!engine.has_tag("rating"));
+
+    Vocabulary::register_all(&mut engine).unwrap();
+    assert!(engine.has_tag("rating"));
+
+    // Existing specifications are left alone
+    let spec = engine.get_spec(&Vocabulary::Keter.as_tag()).unwrap();
+    assert_eq!(spec.required_tags, [Tag::new("scp")]);
+
+    assert_eq!(Vocabulary::ALL.len(), 3);
+    assert_eq!(Vocabulary::Rating.name(), "rating");
+
+    let tags: Vec<Tag> = Vocabulary::ALL.iter().map(|&tag| tag.into()).collect();
+    assert_eq!(
+        tags,
+        [Tag::new("scp"), Tag::new("keter"), Tag::new("rating")]
+    );
+    engine.check_tags(&tags).unwrap();
+
+    let tags = [Vocabulary::Keter.into()];
+    assert!(engine.check_tags(&tags).is_err());
+}
@@ -10,11 +10,30 @@
  * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
  */
 
+mod cardinality;
 mod changes;
 mod check;
+mod diff;
 mod engine;
 mod exists;
+#[cfg(feature = "chrono")]
+mod expiry;
+mod flags;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+mod graph;
+mod lint;
+mod load;
+#[cfg(feature = "log")]
+mod log;
+mod macros;
+mod policy;
+mod resolver;
+#[cfg(feature = "schemars")]
+mod schema;
 mod setup;
+mod spec;
+mod tag;
 
 mod prelude {
     pub use super::setup::setup;
@@ -0,0 +1,77 @@
+/*
+ * test/policy.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::NamePolicy;
+
+fn lowercase_engine() -> Engine {
+    let mut engine = Engine::default();
+    engine.set_name_policy(NamePolicy {
+        max_length: Some(16),
+        allowed_char: |c| !c.is_uppercase() && !c.is_whitespace(),
+    });
+    engine
+}
+
+#[test]
+fn test_default_policy() {
+    let mut engine = Engine::default();
+
+    engine
+        .add_tag("Big Tag", TemplateTagSpec::default())
+        .unwrap();
+    engine.add_group("Big Group").unwrap();
+    engine.add_role("Big Role").unwrap();
+
+    assert_eq!(
+        engine.add_tag("", TemplateTagSpec::default()),
+        Err(Error::InvalidName(str!("")))
+    );
+}
+
+#[test]
+fn test_restricted_policy() {
+    let mut tag_engine = lowercase_engine();
+    let mut group_engine = lowercase_engine();
+
+    macro_rules! check {
+        ($name:expr, $valid:expr) => {
+            let tag = tag_engine.add_tag($name, TemplateTagSpec::default());
+            let group = group_engine.add_group($name);
+            let role = group_engine.add_role($name);
+
+            if $valid {
+                assert!(tag.is_ok(), "Tag {:?} was rejected", $name);
+                assert!(group.is_ok(), "Group {:?} was rejected", $name);
+                assert!(role.is_ok(), "Role {:?} was rejected", $name);
+            } else {
+                let error = Error::InvalidName(str!($name));
+                assert_eq!(tag, Err(error));
+
+                let error = Error::InvalidName(str!($name));
+                assert_eq!(group, Err(error));
+
+                let error = Error::InvalidName(str!($name));
+                assert_eq!(role, Err(error));
+            }
+        };
+    }
+
+    check!("scp", true);
+    check!("_image", true);
+    check!("object-class", true);
+    check!("SCP", false);
+    check!("object class", false);
+    check!("tab\tname", false);
+    check!("much-too-long-of-a-name", false);
+    check!("", false);
+}
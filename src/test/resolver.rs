@@ -0,0 +1,126 @@
+/*
+ * test/resolver.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+use crate::TagResolver;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct MockResolver {
+    specs: HashMap<&'static str, TemplateTagSpec>,
+    groups: Vec<&'static str>,
+    lookups: Arc<AtomicUsize>,
+}
+
+impl TagResolver for MockResolver {
+    fn exists(&self, name: &str) -> bool {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        self.specs.contains_key(name) || self.groups.contains(&name)
+    }
+
+    fn spec(&self, name: &str) -> Option<TagSpec> {
+        let template = self.specs.get(name)?.clone();
+        Some(TagSpec::from_template(&Tag::new(name), template))
+    }
+}
+
+#[test]
+fn test_resolver() {
+    let mut engine = setup();
+    let mut resolver = MockResolver::default();
+    let lookups = Arc::clone(&resolver.lookups);
+
+    resolver.specs.insert(
+        "scp-001-proposal",
+        TemplateTagSpec {
+            required_tags: vec![Tag::new("scp")],
+            conflicting_tags: vec![Tag::new("joke")],
+            groups: vec![Tag::new("proposals")],
+            ..TemplateTagSpec::default()
+        },
+    );
+    resolver.specs.insert("joke", TemplateTagSpec::default());
+    resolver.specs.insert(
+        "broken",
+        TemplateTagSpec {
+            required_tags: vec![Tag::new("nonexistent")],
+            ..TemplateTagSpec::default()
+        },
+    );
+    resolver.specs.insert(
+        "half-broken",
+        TemplateTagSpec {
+            required_tags: vec![Tag::new("humor")],
+            conflicting_tags: vec![Tag::new("nonexistent")],
+            ..TemplateTagSpec::default()
+        },
+    );
+    resolver.specs.insert("humor", TemplateTagSpec::default());
+    resolver.groups.push("proposals");
+
+    let proposal = Tag::new("scp-001-proposal");
+    let tags = [Tag::new("scp"), Tag::new("keter"), Tag::clone(&proposal)];
+    assert_eq!(
+        engine.check_tags_resolving(&tags),
+        Err(Error::NoSuchTag(str!("scp-001-proposal"))),
+    );
+
+    engine.set_resolver(resolver);
+    engine.check_tags_resolving(&tags).unwrap();
+
+    // Resolved tags and their references are registered
+    assert!(engine.has_tag("scp-001-proposal"));
+    assert!(engine.has_tag("joke"));
+    assert!(engine.is_group(&Tag::new("proposals")));
+    assert_eq!(
+        engine.get_spec(&proposal).unwrap().required_tags,
+        [Tag::new("scp")],
+    );
+
+    // And cached, so the resolver is not consulted again
+    let count = lookups.load(Ordering::SeqCst);
+    engine.check_tags_resolving(&tags).unwrap();
+    assert_eq!(lookups.load(Ordering::SeqCst), count);
+
+    // Resolved rules are enforced
+    let mut tags = tags.to_vec();
+    tags.push(Tag::new("joke"));
+    assert_eq!(
+        engine.check_tags_resolving(&tags),
+        Err(Error::IncompatibleTags(proposal, Tag::new("joke"))),
+    );
+
+    // References must be resolvable too
+    assert_eq!(
+        engine.resolve_tag("broken"),
+        Err(Error::NoSuchTag(str!("nonexistent"))),
+    );
+    assert!(!engine.has_tag("broken"));
+
+    // Nothing registered before the failure is kept
+    let fingerprint = engine.fingerprint();
+    assert_eq!(
+        engine.resolve_tag("half-broken"),
+        Err(Error::NoSuchTag(str!("nonexistent"))),
+    );
+    assert!(!engine.has_tag("half-broken"));
+    assert!(!engine.has_tag("humor"));
+    assert_eq!(engine.fingerprint(), fingerprint);
+
+    engine.clear_resolver();
+    assert_eq!(
+        engine.resolve_tag("broken"),
+        Err(Error::NoSuchTag(str!("broken"))),
+    );
+}
@@ -0,0 +1,40 @@
+/*
+ * test/schema.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use crate::load::json_schema;
+use serde_json::Value;
+
+#[test]
+fn test_json_schema() {
+    let schema = json_schema();
+    let schema: Value = serde_json::from_str(&schema).expect("Schema is not valid JSON");
+
+    for name in &["roles", "tags"] {
+        assert!(
+            schema["properties"].get(name).is_some(),
+            "Configuration missing property {:?}",
+            name,
+        );
+    }
+
+    let tag_config = &schema["definitions"]["TagConfig"];
+    for name in &["name", "groups", "roles", "requires", "conflicts_with"] {
+        assert!(
+            tag_config["properties"].get(name).is_some(),
+            "TagConfig missing property {:?}",
+            name,
+        );
+    }
+
+    // Only the name is mandatory
+    assert_eq!(tag_config["required"], Value::from(vec!["name"]));
+}
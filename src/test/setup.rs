@@ -11,8 +11,13 @@
  */
 
 use crate::prelude::*;
+use crate::Result;
 
 pub fn setup() -> Engine {
+    build().expect("Unable to build test engine")
+}
+
+fn build() -> Result<Engine> {
     let mut engine = Engine::default();
 
     engine.add_tag(
@@ -22,7 +27,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("primary")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "tale",
@@ -31,7 +36,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("primary")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "creepypasta",
@@ -41,7 +46,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("tale")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "hub",
@@ -50,7 +55,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("primary")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "safe",
@@ -59,7 +64,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("scp")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "euclid",
@@ -68,7 +73,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("scp")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "keter",
@@ -77,7 +82,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("scp")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "thaumiel",
@@ -86,7 +91,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("scp")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "esoteric-class",
@@ -95,7 +100,7 @@ pub fn setup() -> Engine {
             required_tags: vec![Tag::new("scp")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "_image",
@@ -104,7 +109,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("licensing")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "_cc",
@@ -114,7 +119,7 @@ pub fn setup() -> Engine {
             needed_roles: vec![Role::new("licensing")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "amorphous",
@@ -123,7 +128,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("attribute")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "antimemetic",
@@ -132,7 +137,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("attribute")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "electronic",
@@ -141,7 +146,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("attribute")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "humanoid",
@@ -150,7 +155,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("attribute")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "ontokinetic",
@@ -159,7 +164,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("attribute")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "global-occult-coalition",
@@ -167,7 +172,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("goi")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "marshall-carter-and-dark",
@@ -175,7 +180,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("goi")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "serpents-hand",
@@ -183,9 +188,9 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("goi")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
-    engine.add_tag("co-authored", TemplateTagSpec::default());
+    engine.add_tag("co-authored", TemplateTagSpec::default())?;
 
     engine.add_tag(
         "admin",
@@ -194,7 +199,7 @@ pub fn setup() -> Engine {
             needed_roles: vec![Role::new("admin")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "doomsday2018",
@@ -204,7 +209,7 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("contests")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
     engine.add_tag(
         "cliche2019",
@@ -214,18 +219,19 @@ pub fn setup() -> Engine {
             groups: vec![Tag::new("contests")],
             ..TemplateTagSpec::default()
         },
-    );
+    )?;
 
-    engine.add_group("attribute");
-    engine.add_group("contests");
-    engine.add_group("licensing");
-    engine.add_group("primary");
+    engine.add_group("attribute")?;
+    engine.add_group("contests")?;
+    engine.add_group("licensing")?;
+    engine.add_group("object-class")?;
+    engine.add_group("primary")?;
 
-    engine.add_role("admin");
-    engine.add_role("moderator");
-    engine.add_role("licensing");
-    engine.add_role("member");
-    engine.add_role("locked");
+    engine.add_role("admin")?;
+    engine.add_role("moderator")?;
+    engine.add_role("licensing")?;
+    engine.add_role("member")?;
+    engine.add_role("locked")?;
 
-    engine
+    Ok(engine)
 }
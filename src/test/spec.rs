@@ -0,0 +1,75 @@
+/*
+ * test/spec.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+
+#[test]
+fn test_spec_serde() {
+    let spec = TemplateTagSpec {
+        required_tags: vec![Tag::new("scp")],
+        required_groups: vec![Tag::new("attribute")],
+        required_any_of: vec![vec![Tag::new("safe"), Tag::new("euclid")]],
+        publish_required_tags: vec![Tag::new("licensing")],
+        conflicting_tags: vec![Tag::new("tale"), Tag::new("hub")],
+        implied_tags: vec![Tag::new("humanoid")],
+        needed_roles: vec![Role::new("member")],
+        public: false,
+        groups: vec![Tag::new("object-class")],
+        is_also_group: true,
+        priority: 4,
+        display_name: Some(str!("Spec")),
+        #[cfg(feature = "chrono")]
+        expires_at: None,
+    };
+
+    let json = serde_json::to_string(&spec).expect("Unable to serialize spec");
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["required_tags"], serde_json::json!(["scp"]));
+    assert_eq!(value["needed_roles"], serde_json::json!(["member"]));
+
+    let result: TemplateTagSpec = serde_json::from_str(&json).expect("Unable to deserialize");
+    assert_eq!(result, spec);
+
+    // Empty lists are skipped, and missing fields use defaults
+    let json = serde_json::to_string(&TemplateTagSpec::default()).unwrap();
+    assert!(!json.contains("required_tags"));
+    assert!(!json.contains("display_name"));
+
+    let result: TemplateTagSpec = serde_json::from_str(r#"{"groups": ["primary"]}"#).unwrap();
+    assert_eq!(result.groups, [Tag::new("primary")]);
+    assert!(result.required_tags.is_empty());
+
+    // Empty names are rejected
+    let result = serde_json::from_str::<TemplateTagSpec>(r#"{"groups": [""]}"#);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_spec_entry() {
+    let engine = setup();
+    let scp = Tag::new("scp");
+
+    let (tag, spec) = engine.spec_entry(&scp).unwrap();
+    assert_eq!(*tag, scp);
+    assert_eq!(*spec.tag_ref(), scp);
+    assert_eq!(spec.tag(), scp);
+    assert_eq!(Ok(spec), engine.get_spec(&scp));
+
+    // Both borrow the engine's stored name, rather than the one passed in
+    assert_eq!(tag.as_ptr(), spec.tag_ref().as_ptr());
+    assert_ne!(tag.as_ptr(), scp.as_ptr());
+
+    assert_eq!(
+        engine.spec_entry(&Tag::new("primary")),
+        Err(Error::MissingTag(Tag::new("primary"))),
+    );
+}
@@ -0,0 +1,41 @@
+/*
+ * test/tag.rs
+ *
+ * tag-guard - Configurable tag enforcement library
+ * Copyright (c) 2019 Ammon Smith
+ *
+ * tag-guard is available free of charge under the terms of the MIT
+ * License. You are free to redistribute and/or modify it under those
+ * terms. It is distributed in the hopes that it will be useful, but
+ * WITHOUT ANY WARRANTY. See the LICENSE file for more details.
+ */
+
+use super::prelude::*;
+
+#[test]
+fn test_tag_str_eq() {
+    let tag = Tag::new("scp");
+
+    assert!(tag == "scp");
+    assert!(tag != "tale");
+    assert!("scp" == tag);
+    assert!("tale" != tag);
+    assert!(tag == *"scp");
+    assert!(*"scp" == tag);
+
+    let names = ["safe", "euclid", "keter"];
+    let tags = names.iter().map(|name| Tag::new(*name)).collect::<Vec<_>>();
+    assert_eq!(tags, names);
+}
+
+#[test]
+fn test_role_str_eq() {
+    let role = Role::new("admin");
+
+    assert!(role == "admin");
+    assert!(role != "member");
+    assert!("admin" == role);
+    assert!("member" != role);
+    assert!(role == *"admin");
+    assert!(*"admin" == role);
+}